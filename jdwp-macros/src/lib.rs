@@ -1,9 +1,9 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{format_ident, quote};
 use syn::{
     parse::{Parse, ParseStream},
     spanned::Spanned,
-    Data, Error, Fields, Index, ItemStruct, LitInt, PathArguments, Token, Type,
+    Data, Error, Field, Fields, Index, ItemStruct, LitInt, PathArguments, Token, Type,
 };
 
 #[proc_macro_derive(JdwpReadable, attributes(skip))]
@@ -54,7 +54,9 @@ pub fn jdwp_readable(item: TokenStream) -> TokenStream {
     }
 }
 
-#[proc_macro_derive(JdwpWritable)]
+/// Writes the fields in declaration order, except the ones marked with
+/// `#[skip]`, e.g. the reserved fields of the replies that are only ever read.
+#[proc_macro_derive(JdwpWritable, attributes(skip))]
 pub fn jdwp_writable(item: TokenStream) -> TokenStream {
     let derive_input = syn::parse_macro_input!(item as syn::DeriveInput);
 
@@ -63,27 +65,38 @@ pub fn jdwp_writable(item: TokenStream) -> TokenStream {
             let ident = derive_input.ident;
             let generic_params = derive_input.generics.params;
             let generics_where = derive_input.generics.where_clause;
-            let write = match &struct_data.fields {
-                Fields::Unit => quote!(),
-                Fields::Named(named) => {
-                    let fields = named.named.iter().map(|f| {
+            // the accessors of the written fields
+            let fields = match &struct_data.fields {
+                Fields::Unit => Vec::new(),
+                Fields::Named(named) => named
+                    .named
+                    .iter()
+                    .filter(|f| !is_skipped(f))
+                    .map(|f| {
                         let name = f.ident.as_ref().unwrap(); // same as above here
-                        quote!(self.#name.write(write)?)
-                    });
-                    quote!(#(#fields;)*)
-                }
-                Fields::Unnamed(unnamed) => {
-                    let fields = (0..unnamed.unnamed.len()).map(|i| {
+                        quote!(self.#name)
+                    })
+                    .collect(),
+                Fields::Unnamed(unnamed) => unnamed
+                    .unnamed
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, f)| !is_skipped(f))
+                    .map(|(i, _)| {
                         let idx = Index::from(i);
-                        quote!(self.#idx.write(write)?)
-                    });
-                    quote!(#(#fields;)*)
-                }
+                        quote!(self.#idx)
+                    })
+                    .collect(),
             };
             let tokens = quote! {
                 impl<#generic_params> ::jdwp::codec::JdwpWritable for #ident<#generic_params> #generics_where {
                     fn write<W: ::std::io::Write>(&self, write: &mut ::jdwp::codec::JdwpWriter<W>) -> ::std::io::Result<()> {
-                        #write
+                        #(#fields.write(write)?;)*
+                        Ok(())
+                    }
+
+                    fn write_parts<'__parts>(&'__parts self, parts: &mut ::jdwp::codec::DataParts<'__parts>) -> ::std::io::Result<()> {
+                        #(#fields.write_parts(parts)?;)*
                         Ok(())
                     }
                 }
@@ -105,67 +118,138 @@ pub fn jdwp_writable(item: TokenStream) -> TokenStream {
     }
 }
 
-struct CommandAttr {
-    reply_type: Type,
-    command: ShortCommandAttr,
-}
+/// Implements `ByteTag` for a fieldless `#[repr(u8)]` enum, the tag being the
+/// discriminant.
+#[proc_macro_derive(ByteTag)]
+pub fn byte_tag(item: TokenStream) -> TokenStream {
+    let derive_input = syn::parse_macro_input!(item as syn::DeriveInput);
 
-impl Parse for CommandAttr {
-    fn parse(input: ParseStream) -> syn::Result<Self> {
-        let reply_type = input.parse()?;
-        let _ = input.parse::<Token![,]>()?;
-        Ok(CommandAttr {
-            reply_type,
-            command: input.parse()?,
-        })
+    let enum_data = match &derive_input.data {
+        Data::Enum(enum_data) => enum_data,
+        _ => {
+            return Error::new(
+                derive_input.ident.span(),
+                "Can derive ByteTag only for enums",
+            )
+            .to_compile_error()
+            .into()
+        }
+    };
+    let is_repr_u8 = derive_input.attrs.iter().any(|attr| {
+        attr.path.is_ident("repr")
+            && attr
+                .parse_args::<syn::Ident>()
+                .map_or(false, |repr| repr == "u8")
+    });
+    if !is_repr_u8 {
+        return Error::new(
+            derive_input.ident.span(),
+            "Can derive ByteTag only for #[repr(u8)] enums",
+        )
+        .to_compile_error()
+        .into();
     }
+    if let Some(variant) = enum_data
+        .variants
+        .iter()
+        .find(|v| !matches!(v.fields, Fields::Unit))
+    {
+        return Error::new(
+            variant.fields.span(),
+            "Can derive ByteTag only for enums without fields",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let ident = derive_input.ident;
+    let generic_params = derive_input.generics.params;
+    let generics_where = derive_input.generics.where_clause;
+    let variants = enum_data.variants.iter().map(|v| &v.ident);
+    let tokens = quote! {
+        impl<#generic_params> ::jdwp::codec::ByteTag for #ident<#generic_params> #generics_where {
+            fn byte_tag(&self) -> u8 {
+                match self {
+                    #(Self::#variants => Self::#variants as u8,)*
+                }
+            }
+        }
+    };
+    tokens.into()
+}
+
+fn is_skipped(field: &Field) -> bool {
+    field.attrs.iter().any(|attr| attr.path.is_ident("skip"))
 }
 
-struct ShortCommandAttr {
+/// The arguments of `jdwp_command`, either `(ReplyType, set, id)` or just
+/// `(set, id)`, in which case the reply type is the `{Command}Reply`.
+struct CommandAttr {
+    reply_type: Option<Type>,
     command_set: LitInt,
     command_id: LitInt,
 }
 
-impl ShortCommandAttr {
-    fn long(self, reply_type: Type) -> CommandAttr {
-        CommandAttr {
-            reply_type,
-            command: self,
+impl Parse for CommandAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let reply_type = if input.peek(LitInt) {
+            None
+        } else {
+            let reply_type: Type = input.parse()?;
+            input.parse::<Token![,]>()?;
+            // with only two arguments it was the command set that was not a literal
+            if input.peek(LitInt) && !input.peek2(Token![,]) {
+                return Err(Error::new(
+                    reply_type.span(),
+                    "expected the command set as an integer literal",
+                ));
+            }
+            Some(reply_type)
+        };
+        let command_set = parse_command_byte(input, "command set")?;
+        input.parse::<Token![,]>()?;
+        let command_id = parse_command_byte(input, "command id")?;
+        if !input.is_empty() {
+            return Err(input.error("unexpected tokens after the command id"));
         }
+        Ok(CommandAttr {
+            reply_type,
+            command_set,
+            command_id,
+        })
     }
 }
 
-impl Parse for ShortCommandAttr {
-    fn parse(input: ParseStream) -> syn::Result<Self> {
-        let command_set = input.parse()?;
-        let _ = input.parse::<Token![,]>()?;
-        Ok(ShortCommandAttr {
-            command_set,
-            command_id: input.parse()?,
-        })
+fn parse_command_byte(input: ParseStream, what: &str) -> syn::Result<LitInt> {
+    let lit = input.parse::<LitInt>().map_err(|e| {
+        Error::new(
+            e.span(),
+            format!("expected the {what} as an integer literal"),
+        )
+    })?;
+    if lit.base10_parse::<u8>().is_err() {
+        return Err(Error::new(
+            lit.span(),
+            format!("the {what} must fit into a u8"),
+        ));
     }
+    Ok(lit)
 }
 
 #[proc_macro_attribute]
 pub fn jdwp_command(attr: TokenStream, item: TokenStream) -> TokenStream {
     let item = syn::parse_macro_input!(item as syn::ItemStruct);
 
-    let attr = syn::parse::<CommandAttr>(attr.clone()).or_else(|_| {
-        syn::parse::<ShortCommandAttr>(attr)
-            .and_then(|sca| syn::parse_str(&format!("{}Reply", item.ident)).map(|t| sca.long(t)))
-    });
     let CommandAttr {
         reply_type,
-        command: ShortCommandAttr {
-            command_set,
-            command_id,
-        },
-    } = match attr {
-        Ok(attr) => attr,
-        Err(err) => return err.to_compile_error().into(),
-    };
-
+        command_set,
+        command_id,
+    } = syn::parse_macro_input!(attr as CommandAttr);
     let ident = &item.ident;
+    let reply_type = reply_type.unwrap_or_else(|| {
+        let reply_ident = format_ident!("{}Reply", ident);
+        syn::parse_quote!(#reply_ident)
+    });
 
     let new = if item.fields.is_empty() {
         quote!()
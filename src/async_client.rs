@@ -0,0 +1,246 @@
+//! An asynchronous client built on [tokio], enabled by the `tokio` feature.
+//!
+//! The packets are encoded and decoded the same way as in the blocking
+//! [JdwpClient](crate::client::JdwpClient), only the socket I/O is async.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+        TcpStream, ToSocketAddrs,
+    },
+    sync::{mpsc, oneshot, Mutex as AsyncMutex},
+    task::JoinHandle,
+};
+
+use crate::{
+    client::{decode_reply, encode_command, ClientError, HANDSHAKE, ID_SIZES},
+    codec::{JdwpReadable, JdwpReader},
+    commands::{
+        event::Composite,
+        virtual_machine::{IDSizeInfo, IDSizes},
+        Command,
+    },
+    enums::ErrorCode,
+    xorshift::XorShift32,
+    PacketHeader, PacketMeta,
+};
+
+type WaitingMap = Arc<Mutex<Option<HashMap<u32, oneshot::Sender<Result<Vec<u8>, ClientError>>>>>>;
+
+/// An asynchronous counterpart of the [JdwpClient](crate::client::JdwpClient).
+///
+/// The replies are read by a task spawned on the current tokio runtime, so
+/// the commands can be sent concurrently from several tasks.
+#[derive(Debug)]
+pub struct AsyncJdwpClient {
+    writer: AsyncMutex<OwnedWriteHalf>,
+    id_sizes: IDSizeInfo,
+    waiting: WaitingMap,
+    host_events_rx: AsyncMutex<mpsc::UnboundedReceiver<Composite>>,
+    next_id: Mutex<XorShift32>,
+    reader_handle: JoinHandle<()>,
+}
+
+impl AsyncJdwpClient {
+    /// Connects to the target VM and does the handshake.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub async fn attach<A: ToSocketAddrs>(addr: A) -> Result<Self, ClientError> {
+        let mut stream = TcpStream::connect(addr).await?;
+
+        stream.write_all(HANDSHAKE).await?;
+        let handshake = &mut [0; HANDSHAKE.len()];
+        stream.read_exact(handshake).await?;
+        if handshake != HANDSHAKE {
+            return Err(ClientError::FailedHandshake);
+        }
+
+        let mut next_id = XorShift32::new(0xDEAD);
+
+        // same as in the blocking client
+        let (id_sizes, early_events) = fetch_id_sizes(&mut stream, next_id.next()).await?;
+
+        let waiting = Arc::new(Mutex::new(Some(HashMap::new())));
+        let (host_events_tx, host_events_rx) = mpsc::unbounded_channel();
+        for data in early_events {
+            // the receiver is right here
+            host_events_tx.send(decode_reply(data, &id_sizes)?).unwrap();
+        }
+
+        let (read, write) = stream.into_split();
+        let reader_handle = tokio::spawn(read_packets(
+            read,
+            waiting.clone(),
+            host_events_tx,
+            id_sizes.clone(),
+        ));
+
+        Ok(Self {
+            writer: AsyncMutex::new(write),
+            id_sizes,
+            waiting,
+            host_events_rx: AsyncMutex::new(host_events_rx),
+            next_id: Mutex::new(next_id),
+            reader_handle,
+        })
+    }
+
+    /// The sizes of the IDs used by the target VM, fetched when connecting.
+    pub fn id_sizes(&self) -> &IDSizeInfo {
+        &self.id_sizes
+    }
+
+    /// Sends the command and waits for its reply.
+    ///
+    /// Unlike with the blocking client,
+    /// [Dispose](crate::commands::virtual_machine::Dispose) is not special, so
+    /// it might fail with [Disconnected](ClientError::Disconnected) if the
+    /// target VM closes the connection before replying.
+    pub async fn send<C: Command>(&self, command: C) -> Result<C::Output, ClientError> {
+        let id = self.next_id.lock().unwrap().next();
+
+        let (reply_tx, reply_rx) = oneshot::channel();
+        match self.waiting.lock().unwrap().as_mut() {
+            Some(map) => map.insert(id, reply_tx),
+            None => return Err(ClientError::Disconnected),
+        };
+
+        let (header, data) = encode_command(&command, id, &self.id_sizes)?;
+        {
+            let mut writer = self.writer.lock().await;
+            writer.write_all(&header).await?;
+            for part in data.slices() {
+                writer.write_all(part).await?;
+            }
+        }
+        log::trace!("[{:x}] sent command {}: {:?}", id, C::ID, command);
+
+        let data = reply_rx.await.map_err(|_| ClientError::Disconnected)??;
+
+        let result = decode_reply(data, &self.id_sizes)?;
+        log::trace!("[{:x}] data: {:#?}", id, result);
+        Ok(result)
+    }
+
+    /// Waits for the next composite event sent by the target VM, `None` once
+    /// the connection is closed.
+    pub async fn recv_event(&self) -> Option<Composite> {
+        self.host_events_rx.lock().await.recv().await
+    }
+}
+
+impl Drop for AsyncJdwpClient {
+    fn drop(&mut self) {
+        self.reader_handle.abort();
+    }
+}
+
+/// Sends [IDSizes] and waits for its reply, returning the events that
+/// arrived in the meantime undecoded, see the blocking client.
+async fn fetch_id_sizes(
+    stream: &mut TcpStream,
+    id: u32,
+) -> Result<(IDSizeInfo, Vec<Vec<u8>>), ClientError> {
+    let (header, data) = encode_command(&IDSizes, id, &ID_SIZES)?;
+    stream.write_all(&header).await?;
+    for part in data.slices() {
+        stream.write_all(part).await?;
+    }
+
+    let mut early_events = Vec::new();
+    loop {
+        let header = &mut [0; PacketHeader::JDWP_SIZE];
+        stream.read_exact(header).await?;
+        let header = PacketHeader::read(&mut JdwpReader::new(&header[..], ID_SIZES))?;
+
+        let mut data = vec![0; header.length as usize - PacketHeader::JDWP_SIZE];
+        stream.read_exact(&mut data).await?;
+
+        match header.meta {
+            PacketMeta::Command(Composite::ID) => early_events.push(data),
+            PacketMeta::Reply(ErrorCode::None) if header.id == id => {
+                return Ok((decode_reply(data, &ID_SIZES)?, early_events))
+            }
+            PacketMeta::Reply(error_code) if header.id == id => {
+                return Err(ClientError::HostError(error_code))
+            }
+            _ => log::warn!(
+                "Received an unexpected packet from the JVM, ignoring: {:?}",
+                header
+            ),
+        }
+    }
+}
+
+async fn read_packets(
+    mut read: OwnedReadHalf,
+    waiting: WaitingMap,
+    host_events_tx: mpsc::UnboundedSender<Composite>,
+    id_sizes: IDSizeInfo,
+) {
+    loop {
+        if let Err(e) = read_packet(&mut read, &waiting, &host_events_tx, &id_sizes).await {
+            log::error!("Failed to read incoming data: {}", e);
+            break;
+        }
+    }
+    if let Some(waiting) = waiting.lock().unwrap().take() {
+        for reply in waiting.into_values() {
+            let _ = reply.send(Err(ClientError::Disconnected));
+        }
+    }
+}
+
+async fn read_packet(
+    read: &mut OwnedReadHalf,
+    waiting: &WaitingMap,
+    host_events_tx: &mpsc::UnboundedSender<Composite>,
+    id_sizes: &IDSizeInfo,
+) -> Result<(), ClientError> {
+    let header = &mut [0; PacketHeader::JDWP_SIZE];
+    read.read_exact(header).await?;
+    let header = PacketHeader::read(&mut JdwpReader::new(&header[..], id_sizes.clone()))?;
+
+    let mut data = vec![0; header.length as usize - PacketHeader::JDWP_SIZE];
+    read.read_exact(&mut data).await?;
+
+    let reply = match header.meta {
+        PacketMeta::Command(Composite::ID) => {
+            let composite: Composite = decode_reply(data, id_sizes)?;
+            log::trace!("[host] event: {:#?}", composite);
+            // nobody listening for the events is fine
+            let _ = host_events_tx.send(composite);
+            return Ok(());
+        }
+        PacketMeta::Command(command_id) => {
+            log::warn!(
+                "Unknown command received from the host, ignoring: {}",
+                command_id
+            );
+            return Ok(());
+        }
+        PacketMeta::Reply(ErrorCode::None) => Ok(data),
+        PacketMeta::Reply(error_code) => Err(ClientError::HostError(error_code)),
+    };
+
+    let waiter = waiting
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|map| map.remove(&header.id));
+    match waiter {
+        // the sender might have given up on the reply
+        Some(waiter) => drop(waiter.send(reply)),
+        None => log::warn!(
+            "Received an unexpected packet from the JVM, ignoring: {:?}",
+            header
+        ),
+    }
+    Ok(())
+}
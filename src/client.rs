@@ -1,36 +1,114 @@
 use std::{
     collections::HashMap,
-    io::{self, Cursor, Read, Write},
-    net::{Shutdown, TcpStream, ToSocketAddrs},
+    fmt::{self, Debug, Formatter},
+    io::{self, Cursor, IoSlice, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
     sync::{
-        mpsc::{self, Receiver, Sender},
+        mpsc::{self, Receiver, SendError, Sender},
         Arc, Mutex,
     },
     thread::{self, JoinHandle},
+    time::{Duration, Instant},
 };
 
 use thiserror::Error;
 
 use crate::{
-    codec::{JdwpReadable, JdwpReader, JdwpWritable, JdwpWriter},
+    codec::{DataParts, JdwpReadable, JdwpReader, JdwpWritable, JdwpWriter},
     commands::{
-        event::Composite,
-        virtual_machine::{Dispose, IDSizeInfo},
+        event::{Composite, Event},
+        event_request,
+        virtual_machine::{Dispose, IDSizeInfo, IDSizes},
         Command,
     },
+    enums::{EventKind, ModifierKind, Tag},
+    jvm::FieldType,
+    recording::EventRecorder,
+    types::RequestID,
     xorshift::XorShift32,
-    ErrorCode, PacketHeader, PacketMeta,
+    CommandId, ErrorCode, PacketHeader, PacketMeta,
 };
 
-type WaitingMap = Arc<Mutex<HashMap<u32, Sender<Result<Vec<u8>, ClientError>>>>>;
+/// The commands awaiting their replies, taken (and set to `None`) by the
+/// reader thread once it stops so that nothing waits forever.
+type WaitingMap = Arc<Mutex<Option<HashMap<u32, Waiting>>>>;
+type Subscriptions = Arc<Mutex<HashMap<RequestID, Sender<Event>>>>;
+type Recorder = Arc<Mutex<Option<EventRecorder>>>;
+type Tracer = Arc<Mutex<Option<Trace>>>;
+type Reply = Receiver<Result<Vec<u8>, ClientError>>;
+
+#[derive(Debug)]
+struct Waiting {
+    reply: Sender<Result<Vec<u8>, ClientError>>,
+    // set when the command is an event request that is being subscribed to
+    events: Option<Sender<Event>>,
+}
 
 #[derive(Debug)]
 pub struct JdwpClient {
     writer: JdwpWriter<TcpStream>,
     host_events_rx: Receiver<Composite>,
     waiting: WaitingMap,
-    next_id: XorShift32,
+    subscriptions: Subscriptions,
+    recorder: Recorder,
+    tracer: Tracer,
+    next_id: IdGenerator,
     reader_handle: Option<JoinHandle<ClientError>>,
+    dispose_on_drop: bool,
+}
+
+struct IdGenerator(Box<dyn FnMut() -> u32 + Send>);
+
+impl Debug for IdGenerator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("IdGenerator")
+    }
+}
+
+struct Trace(Box<dyn FnMut(TraceEvent<'_>) + Send>);
+
+impl Debug for Trace {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Trace")
+    }
+}
+
+impl Trace {
+    fn fire(tracer: &Tracer, event: TraceEvent<'_>) {
+        if let Some(trace) = tracer.lock().unwrap().as_mut() {
+            (trace.0)(event);
+        }
+    }
+}
+
+/// A packet that went over the wire, see [JdwpClient::set_trace].
+///
+/// The lengths are the ones from the packet headers, so they include the
+/// 11 bytes of the header itself, and the data is the rest of the packet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceEvent<'a> {
+    /// A command sent to the target VM
+    Command {
+        id: u32,
+        command: CommandId,
+        length: u32,
+        data: &'a [u8],
+    },
+    /// A reply received from the target VM, with no data if it is an error
+    Reply {
+        id: u32,
+        error_code: ErrorCode,
+        length: u32,
+        data: &'a [u8],
+    },
+    /// A command received from the target VM, which in practice are only
+    /// the [composite events](Composite)
+    Event {
+        id: u32,
+        command: CommandId,
+        length: u32,
+        data: &'a [u8],
+    },
 }
 
 #[derive(Debug, Error)]
@@ -39,45 +117,198 @@ pub enum ClientError {
     FailedHandshake,
     #[error("{0}")]
     HostError(ErrorCode),
-    #[error("Too much data received from the host ({actual}/{expected} bytes)")]
-    TooMuchDataReceived { expected: usize, actual: usize },
+    /// The reply was not read fully, which means that either the reply type
+    /// does not match what the host sent or the host sent something off-spec.
+    #[error("Only {consumed} bytes of the {expected}-byte reply were read")]
+    TrailingData { expected: usize, consumed: usize },
     #[error(transparent)]
     IoError(#[from] io::Error),
     #[error("The client was disposed either by the Dispose command or by a network error already consumed")]
     Disposed,
+    /// The connection was closed before the reply arrived, e.g. because the
+    /// target VM has exited.
+    #[error("The connection was closed while waiting for the reply")]
+    Disconnected,
+    #[error("Illegal argument: {0}")]
+    IllegalArgument(String),
+    /// The operation cannot be done by the target VM or in its current state,
+    /// e.g. a missing capability or a thread that is not suspended.
+    #[error("Illegal state: {0}")]
+    IllegalState(String),
+    #[error("The operation is undefined for primitive types")]
+    NotApplicableToPrimitive,
+    /// The target VM does not know the class file version of the type, with
+    /// the host error that it has failed with, see
+    /// [class_file_version](crate::highlevel::ReferenceType::class_file_version).
+    #[error("The class file version is unavailable: {0}")]
+    ClassFileVersionUnavailable(ErrorCode),
+    /// An argument does not fit its parameter, see
+    /// [check_arguments](crate::highlevel::Method::check_arguments).
+    #[error("Argument {index} is of type {actual:?}, but {expected:?} was expected")]
+    ArgumentTypeMismatch {
+        index: usize,
+        expected: FieldType,
+        actual: Tag,
+    },
+    /// The target VM would reject an event request with this modifier, see
+    /// [EventRequestBuilder](crate::highlevel::EventRequestBuilder).
+    #[error("The {modifier:?} modifier cannot be used with the {event_kind:?} events")]
+    ModifierNotApplicable {
+        modifier: ModifierKind,
+        event_kind: EventKind,
+    },
+}
+
+pub(crate) const HANDSHAKE: &[u8] = b"JDWP-Handshake";
+
+/// The ID sizes that are assumed where the target VM cannot be asked for
+/// them, e.g. before it was asked or when replaying a recording.
+pub(crate) const ID_SIZES: IDSizeInfo = IDSizeInfo {
+    field_id_size: 8,
+    method_id_size: 8,
+    object_id_size: 8,
+    reference_type_id_size: 8,
+    frame_id_size: 8,
+};
+
+/// A socket that the target VMs connect to, see [JdwpClient::listen].
+#[derive(Debug)]
+pub struct JdwpListener {
+    listener: TcpListener,
 }
 
-const HANDSHAKE: &[u8] = b"JDWP-Handshake";
+impl JdwpListener {
+    /// The address this is listening on, e.g. to get the port when
+    /// listening on port 0.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Blocks until a target VM connects and does the handshake with it.
+    pub fn accept(&self) -> Result<JdwpClient, ClientError> {
+        let (stream, _) = self.listener.accept()?;
+        JdwpClient::from_stream(stream)
+    }
+}
 
 impl JdwpClient {
     pub fn attach<A: ToSocketAddrs>(addr: A) -> Result<JdwpClient, ClientError> {
-        let mut stream = TcpStream::connect(addr)?;
+        Self::connect(addr, None)
+    }
+
+    /// Same as [attach](JdwpClient::attach), but gives up with a
+    /// [TimedOut](io::ErrorKind::TimedOut) error if either connecting or the
+    /// handshake take longer than the given timeout, e.g. when the target VM
+    /// is not up yet.
+    ///
+    /// The timeout only applies to that initial phase, the commands sent
+    /// afterwards wait for their replies for as long as needed.
+    pub fn attach_timeout<A: ToSocketAddrs>(
+        addr: A,
+        timeout: Duration,
+    ) -> Result<JdwpClient, ClientError> {
+        Self::connect(addr, Some(timeout))
+    }
+
+    fn connect<A: ToSocketAddrs>(
+        addr: A,
+        timeout: Option<Duration>,
+    ) -> Result<JdwpClient, ClientError> {
+        let Some(timeout) = timeout else {
+            return Self::from_stream(TcpStream::connect(addr)?);
+        };
+        // same as TcpStream::connect, try every address until one works
+        let mut last_error = io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any addresses",
+        );
+        for addr in addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, timeout) {
+                Ok(stream) => {
+                    stream.set_read_timeout(Some(timeout))?;
+                    stream.set_write_timeout(Some(timeout))?;
+                    return Self::from_stream(stream);
+                }
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error.into())
+    }
+
+    /// Starts listening for the target VMs that connect to the debugger
+    /// themselves, i.e. the ones launched with `server=n`.
+    pub fn listen<A: ToSocketAddrs>(addr: A) -> io::Result<JdwpListener> {
+        TcpListener::bind(addr).map(|listener| JdwpListener { listener })
+    }
 
-        stream.write_all(HANDSHAKE)?;
+    /// Does the handshake over an already established connection, e.g. the
+    /// one accepted from a target VM that was launched with `server=n`.
+    ///
+    /// Right after the handshake the target VM is asked for its
+    /// [ID sizes](JdwpClient::id_sizes), which all the IDs are encoded with.
+    ///
+    /// The read and write timeouts of the stream only apply to the handshake
+    /// and the ID sizes, they are cleared afterwards.
+    pub fn from_stream(mut stream: TcpStream) -> Result<JdwpClient, ClientError> {
         let handshake = &mut [0; HANDSHAKE.len()];
-        stream.read_exact(handshake)?;
+        stream
+            .write_all(HANDSHAKE)
+            .and_then(|_| stream.read_exact(handshake))
+            .map_err(timed_out)?;
         if handshake != HANDSHAKE {
             return Err(ClientError::FailedHandshake);
         }
 
-        let waiting = Arc::new(Mutex::new(HashMap::new()));
+        let mut next_id = IdGenerator(Box::new({
+            let mut xorshift = XorShift32::new(0xDEAD);
+            move || xorshift.next()
+        }));
+
+        let (id_sizes, early_events) =
+            fetch_id_sizes(&mut stream, (next_id.0)()).map_err(|e| match e {
+                ClientError::IoError(e) => ClientError::IoError(timed_out(e)),
+                e => e,
+            })?;
+        stream.set_read_timeout(None)?;
+        stream.set_write_timeout(None)?;
+
+        let waiting = Arc::new(Mutex::new(Some(HashMap::new())));
+        let subscriptions = Arc::new(Mutex::new(HashMap::new()));
+        let recorder = Arc::new(Mutex::new(None));
+        let tracer = Arc::new(Mutex::new(None));
         let (host_events_tx, host_events_rx) = mpsc::channel();
 
-        // todo: hardcode fetching it here I guess
-        let id_sizes = IDSizeInfo {
-            field_id_size: 8,
-            method_id_size: 8,
-            object_id_size: 8,
-            reference_type_id_size: 8,
-            frame_id_size: 8,
-        };
+        for data in early_events {
+            let composite = decode_reply(data, &id_sizes)?;
+            log::trace!("[host] early event: {:#?}", composite);
+            // the receiver is right here
+            host_events_tx.send(composite).unwrap();
+        }
 
         let reader_handle = thread::spawn({
             let mut reader = JdwpReader::new(stream.try_clone()?, id_sizes.clone());
             let waiting = waiting.clone();
+            let subscriptions = subscriptions.clone();
+            let recorder = recorder.clone();
+            let tracer = tracer.clone();
             move || loop {
-                if let Err(e) = read_packet(&mut reader, &waiting, &host_events_tx) {
+                if let Err(e) = read_packet(
+                    &mut reader,
+                    &waiting,
+                    &subscriptions,
+                    &recorder,
+                    &tracer,
+                    &host_events_tx,
+                ) {
                     log::error!("Failed to read incoming data: {}", e);
+                    // e.g. the VM died after sending the VmDeath event,
+                    // which was already routed like any other event
+                    if let Some(waiting) = waiting.lock().unwrap().take() {
+                        for waiter in waiting.into_values() {
+                            // the sender might have given up on the reply
+                            let _ = waiter.reply.send(Err(ClientError::Disconnected));
+                        }
+                    }
                     break e;
                 }
             }
@@ -87,49 +318,239 @@ impl JdwpClient {
             writer: JdwpWriter::new(stream, id_sizes),
             host_events_rx,
             waiting,
-            next_id: XorShift32::new(0xDEAD),
+            subscriptions,
+            recorder,
+            tracer,
+            next_id,
             reader_handle: Some(reader_handle),
+            dispose_on_drop: true,
         })
     }
 
+    /// The sizes of the IDs used by the target VM, fetched with [IDSizes]
+    /// when connecting.
+    pub fn id_sizes(&self) -> &IDSizeInfo {
+        &self.writer.id_sizes
+    }
+
+    /// Replaces the generator of the packet ids of the sent commands, e.g.
+    /// to make them deterministic in tests or to match them with an external
+    /// packet capture.
+    ///
+    /// The ids must not repeat while the commands that used them are still
+    /// awaiting their replies, otherwise the replies will get mixed up.
+    pub fn with_id_generator(mut self, generator: impl FnMut() -> u32 + Send + 'static) -> Self {
+        self.next_id = IdGenerator(Box::new(generator));
+        self
+    }
+
+    /// Whether to send [Dispose] when the client is dropped, which is the
+    /// default.
+    ///
+    /// Disposing makes the target VM resume the threads suspended by the
+    /// debugger and clear the event requests right away, instead of whenever
+    /// it notices that the connection was closed.
+    /// Turn it off to leave the target VM as it is.
+    pub fn set_dispose_on_drop(&mut self, dispose_on_drop: bool) {
+        self.dispose_on_drop = dispose_on_drop;
+    }
+
+    /// Starts writing every packet received from the target VM to the given
+    /// recorder, or stops it if `None` is given, so that the events can be
+    /// replayed later with an [EventReplayer](crate::recording::EventReplayer).
+    ///
+    /// Failing to write a packet does not affect the connection, it is only
+    /// logged.
+    pub fn set_recorder(&mut self, recorder: Option<EventRecorder>) {
+        *self.recorder.lock().unwrap() = recorder;
+    }
+
+    /// Calls the given hook with every packet sent to or received from the
+    /// target VM from now on, including the raw data, replacing the previous
+    /// hook.
+    ///
+    /// This is meant for debugging the protocol itself without a packet
+    /// capture. The hook is called from the reader thread for the received
+    /// packets, so it should be quick. A command is traced right before it is
+    /// written, so it always comes before its reply.
+    /// The handshake and the [IDSizes] exchange when connecting happen
+    /// before any hook can be set, so those are never traced.
+    pub fn set_trace(&mut self, trace: impl FnMut(TraceEvent<'_>) + Send + 'static) {
+        *self.tracer.lock().unwrap() = Some(Trace(Box::new(trace)));
+    }
+
+    /// Events that were not routed to a subscriber, see
+    /// [subscribe](JdwpClient::subscribe).
+    ///
+    /// Composite events are split up, so that only the events that nobody
+    /// subscribed to end up here.
     pub fn host_events(&self) -> &Receiver<Composite> {
         &self.host_events_rx
     }
 
+    /// Blocks on the [host events](JdwpClient::host_events) one composite
+    /// event at a time, ending once the connection is closed.
+    ///
+    /// The packets are read and demultiplexed by a separate thread, with the
+    /// replies routed to the pending [send](JdwpClient::send) calls and the
+    /// events queued here, so it's fine to interleave the sends with event
+    /// reading - the events that arrive in the meantime are not lost, and the
+    /// sends never see them.
+    /// The order of the events is preserved.
+    pub fn events(&self) -> mpsc::Iter<'_, Composite> {
+        self.host_events_rx.iter()
+    }
+
+    /// Sends the command and waits for its reply.
+    ///
+    /// A reply with an error code fails with
+    /// [HostError](ClientError::HostError), see
+    /// [send_raw](JdwpClient::send_raw) for getting the code as is.
     pub fn send<C: Command>(&mut self, command: C) -> Result<C::Output, ClientError> {
-        match self.reader_handle {
-            Some(ref handle) if handle.is_finished() => {
-                return Err(self.reader_handle.take().unwrap().join().unwrap())
-            }
-            None => return Err(ClientError::Disposed),
-            _ => {}
+        self.send_inner(command, None)
+    }
+
+    /// Sends all of the commands before waiting for any of the replies, so
+    /// that the target VM processes them back to back instead of waiting for
+    /// a round-trip after each one.
+    ///
+    /// The replies are in the order of the commands and fail separately, the
+    /// outer error is only for failing to send the commands.
+    /// [Dispose] cannot be sent this way.
+    pub fn send_all<C: Command>(
+        &mut self,
+        commands: impl IntoIterator<Item = C>,
+    ) -> Result<Vec<Result<C::Output, ClientError>>, ClientError> {
+        if C::ID == Dispose::ID {
+            return Err(ClientError::IllegalArgument(
+                "Dispose cannot be sent with other commands".to_owned(),
+            ));
         }
+        let pending = commands
+            .into_iter()
+            .map(|command| self.write_command(&command, None))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(pending
+            .into_iter()
+            .map(|(id, reply)| self.wait_reply::<C>(id, reply))
+            .collect())
+    }
 
-        let (waiting_tx, waiting_rx) = mpsc::channel();
+    /// Sends a command with a list reply and calls the callback with each of
+    /// the elements as they are decoded, instead of collecting them into a
+    /// [Vec].
+    ///
+    /// This bounds the memory used by the decoded elements of huge replies,
+    /// e.g. [AllClassesWithGeneric](crate::commands::virtual_machine::AllClassesWithGeneric)
+    /// on a large application - only the raw reply data is kept whole.
+    ///
+    /// Returns the number of the elements.
+    pub fn send_streaming<C, T>(
+        &mut self,
+        command: C,
+        mut callback: impl FnMut(T),
+    ) -> Result<u32, ClientError>
+    where
+        C: Command<Output = Vec<T>>,
+        T: JdwpReadable,
+    {
+        let (id, reply) = self.write_command(&command, None)?;
+        let data = reply.recv().expect("Sender hung up, this cannot happen")?;
 
-        let id = self.next_id.next();
+        let len = data.len();
+        let mut cursor = Cursor::new(data);
+        let mut reader = JdwpReader::new(&mut cursor, self.writer.id_sizes.clone());
+        let count = u32::read(&mut reader)?;
+        for _ in 0..count {
+            callback(T::read(&mut reader)?);
+        }
+        log::trace!("[{:x}] streamed {} elements", id, count);
 
-        // see comment below
-        if C::ID != Dispose::ID {
-            self.waiting.lock().unwrap().insert(id, waiting_tx);
+        if cursor.position() < len as u64 {
+            return Err(ClientError::TrailingData {
+                expected: len,
+                consumed: cursor.position() as usize,
+            });
         }
+        Ok(count)
+    }
 
-        let mut data = Vec::new();
-        command.write(&mut JdwpWriter::new(
-            &mut data,
-            self.writer.id_sizes.clone(),
-        ))?;
+    /// Sets an event request and routes all the events it generates to the
+    /// given sender instead of the [host events](JdwpClient::host_events).
+    ///
+    /// The subscription is registered before any further packets from the
+    /// host are processed, so no events can slip by between the request being
+    /// set and the subscription.
+    ///
+    /// Once the receiving side is dropped, the events are routed back to the
+    /// host events.
+    /// Note that this does not clear the event request itself.
+    pub fn subscribe(
+        &mut self,
+        request: event_request::Set,
+        events: Sender<Event>,
+    ) -> Result<RequestID, ClientError> {
+        self.send_inner(request, Some(events))
+    }
 
-        let header = PacketHeader {
-            length: (PacketHeader::JDWP_SIZE + data.len()) as u32,
-            id,
-            meta: PacketMeta::Command(C::ID),
-        };
+    /// Stops routing the events of the given request to its subscriber.
+    ///
+    /// Same as with [subscribe](JdwpClient::subscribe), this does not clear
+    /// the event request itself.
+    pub fn unsubscribe(&mut self, request_id: RequestID) {
+        self.subscriptions.lock().unwrap().remove(&request_id);
+    }
 
-        header.write(&mut self.writer)?;
-        self.writer.write_all(&data)?;
+    /// Measures the round-trip time of a cheap command, [IDSizes].
+    ///
+    /// Useful for health checks of the connection or for detecting a slow or
+    /// overloaded target VM.
+    pub fn ping(&mut self) -> Result<Duration, ClientError> {
+        let start = Instant::now();
+        self.send(IDSizes)?;
+        Ok(start.elapsed())
+    }
+
+    /// Sends a command that is not modeled by this crate, e.g. a vendor
+    /// extension, and waits for its reply.
+    ///
+    /// The caller is responsible for encoding the command data and decoding
+    /// the reply data, which are passed as is.
+    /// The error code of the reply is returned instead of being turned into
+    /// a [HostError](ClientError::HostError), the error replies carry no data.
+    /// [Dispose] cannot be sent this way.
+    pub fn send_raw(
+        &mut self,
+        command_set: u8,
+        command: u8,
+        data: &[u8],
+    ) -> Result<(ErrorCode, Vec<u8>), ClientError> {
+        let command_id = CommandId::new(command_set, command);
+        if command_id == Dispose::ID {
+            return Err(ClientError::IllegalArgument(
+                "Dispose cannot be sent as a raw command".to_owned(),
+            ));
+        }
+        let (id, reply) = self.register(command_id, None)?;
+
+        let header = encode_header(command_id, id, data.len(), &self.writer.id_sizes)?;
+        self.write_packet(command_id, id, &header, &[data])?;
+
+        log::trace!("[{:x}] sent raw command {}: {:?}", id, command_id, data);
+
+        match reply.recv().expect("Sender hung up, this cannot happen") {
+            Ok(data) => Ok((ErrorCode::None, data)),
+            Err(ClientError::HostError(error_code)) => Ok((error_code, Vec::new())),
+            Err(e) => Err(e),
+        }
+    }
 
-        log::trace!("[{:x}] sent command {}: {:?}", header.id, C::ID, command);
+    fn send_inner<C: Command>(
+        &mut self,
+        command: C,
+        events: Option<Sender<Event>>,
+    ) -> Result<C::Output, ClientError> {
+        let (id, reply) = self.write_command(&command, events)?;
 
         // special handling for the dispose command because
         // we don't always get the response header for it
@@ -151,33 +572,244 @@ impl JdwpClient {
             return Ok(unsafe { std::mem::transmute_copy(&()) });
         }
 
-        let data = waiting_rx
-            .recv()
-            .expect("Sender hung up, this cannot happen")?;
+        self.wait_reply::<C>(id, reply)
+    }
 
-        let len = data.len();
-        let mut cursor = Cursor::new(data);
-        let result = C::Output::read(&mut JdwpReader::new(
-            &mut cursor,
-            self.writer.id_sizes.clone(),
-        ))?;
+    /// Registers the command as awaiting its reply and writes it, returning
+    /// its id and where the reply will arrive.
+    fn write_command<C: Command>(
+        &mut self,
+        command: &C,
+        events: Option<Sender<Event>>,
+    ) -> Result<(u32, Reply), ClientError> {
+        let (id, reply) = self.register(C::ID, events)?;
 
-        log::trace!("[{:x}] data: {:#?}", header.id, result);
+        let (header, data) = encode_command(command, id, &self.writer.id_sizes)?;
+        self.write_packet(C::ID, id, &header, &data.slices())?;
 
-        if cursor.position() < len as u64 {
-            Err(ClientError::TooMuchDataReceived {
-                actual: len,
-                expected: cursor.position() as usize,
-            })
-        } else {
-            Ok(result)
+        log::trace!("[{:x}] sent command {}: {:?}", id, C::ID, command);
+
+        Ok((id, reply))
+    }
+
+    fn write_packet(
+        &mut self,
+        command: CommandId,
+        id: u32,
+        header: &[u8],
+        data: &[&[u8]],
+    ) -> io::Result<()> {
+        // the traced data is in one piece, so it's only copied for tracing
+        if self.tracer.lock().unwrap().is_some() {
+            let data = data.concat();
+            let event = TraceEvent::Command {
+                id,
+                command,
+                length: (header.len() + data.len()) as u32,
+                data: &data,
+            };
+            Trace::fire(&self.tracer, event);
+        }
+        let mut parts = vec![header];
+        parts.extend_from_slice(data);
+        write_packet(&mut *self.writer, &parts)
+    }
+
+    /// Picks the id for the next command and registers it as awaiting its
+    /// reply.
+    fn register(
+        &mut self,
+        command_id: CommandId,
+        events: Option<Sender<Event>>,
+    ) -> Result<(u32, Reply), ClientError> {
+        match self.reader_handle {
+            Some(ref handle) if handle.is_finished() => {
+                return Err(self.reader_handle.take().unwrap().join().unwrap())
+            }
+            None => return Err(ClientError::Disposed),
+            _ => {}
+        }
+
+        let (waiting_tx, waiting_rx) = mpsc::channel();
+
+        let id = (self.next_id.0)();
+
+        // the reply to dispose is not waited for, see send_inner
+        if command_id != Dispose::ID {
+            let waiting = Waiting {
+                reply: waiting_tx,
+                events,
+            };
+            match self.waiting.lock().unwrap().as_mut() {
+                Some(map) => map.insert(id, waiting),
+                // the reader thread stopped right after the check above
+                None => return Err(ClientError::Disconnected),
+            };
+        }
+
+        Ok((id, waiting_rx))
+    }
+
+    fn wait_reply<C: Command>(&self, id: u32, reply: Reply) -> Result<C::Output, ClientError> {
+        let data = reply.recv().expect("Sender hung up, this cannot happen")?;
+
+        let result = decode_reply(data, &self.writer.id_sizes)?;
+        log::trace!("[{:x}] data: {:#?}", id, result);
+        Ok(result)
+    }
+}
+
+impl Drop for JdwpClient {
+    fn drop(&mut self) {
+        // not disposed or disconnected already
+        if self.dispose_on_drop && self.reader_handle.is_some() {
+            // the target VM might be gone already, nothing to do about it
+            if let Err(e) = self.send(Dispose) {
+                log::debug!("Failed to dispose the dropped client: {}", e);
+            }
+        }
+    }
+}
+
+/// Turns the timeouts into [TimedOut](io::ErrorKind::TimedOut) errors.
+fn timed_out(e: io::Error) -> io::Error {
+    match e.kind() {
+        // that's what the timeouts look like on unix
+        io::ErrorKind::WouldBlock => io::Error::from(io::ErrorKind::TimedOut),
+        _ => e,
+    }
+}
+
+/// Sends [IDSizes] and waits for its reply right on the stream, before the
+/// reader thread is started.
+///
+/// The events that arrive in the meantime (e.g.
+/// [VmStart](crate::commands::event::VmStart) of a suspended target VM) can
+/// only be decoded once the sizes are known, so they are returned as is.
+fn fetch_id_sizes(
+    stream: &mut TcpStream,
+    id: u32,
+) -> Result<(IDSizeInfo, Vec<Vec<u8>>), ClientError> {
+    // the command has no data, so the assumed sizes do not matter for it
+    let (header, data) = encode_command(&IDSizes, id, &ID_SIZES)?;
+    let mut parts = vec![&header[..]];
+    parts.extend(data.slices());
+    write_packet(stream, &parts)?;
+
+    let mut reader = JdwpReader::new(stream, ID_SIZES);
+    let mut early_events = Vec::new();
+    loop {
+        let header = PacketHeader::read(&mut reader)?;
+        let mut data = vec![0; header.length as usize - PacketHeader::JDWP_SIZE];
+        reader.read_exact(&mut data)?;
+
+        match header.meta {
+            PacketMeta::Command(Composite::ID) => early_events.push(data),
+            PacketMeta::Reply(ErrorCode::None) if header.id == id => {
+                let id_sizes = decode_reply(data, &ID_SIZES)?;
+                log::trace!("[{:x}] id sizes: {:#?}", id, id_sizes);
+                return Ok((id_sizes, early_events));
+            }
+            PacketMeta::Reply(error_code) if header.id == id => {
+                return Err(ClientError::HostError(error_code))
+            }
+            _ => log::warn!(
+                "Received an unexpected packet from the JVM, ignoring: {:?}",
+                header
+            ),
+        }
+    }
+}
+
+/// Serializes the command into the header and the data of its packet.
+/// Encodes the header and the data of the command, with the large byte
+/// arrays of the data borrowed from the command, see
+/// [write_parts](JdwpWritable::write_parts).
+pub(crate) fn encode_command<'a, C: Command>(
+    command: &'a C,
+    id: u32,
+    id_sizes: &IDSizeInfo,
+) -> io::Result<(Vec<u8>, DataParts<'a>)> {
+    let mut data = DataParts::new(id_sizes.clone());
+    command.write_parts(&mut data)?;
+    let header = encode_header(C::ID, id, data.len(), id_sizes)?;
+    Ok((header, data))
+}
+
+fn encode_header(
+    command_id: CommandId,
+    id: u32,
+    data_len: usize,
+    id_sizes: &IDSizeInfo,
+) -> io::Result<Vec<u8>> {
+    let header = PacketHeader {
+        length: (PacketHeader::JDWP_SIZE + data_len) as u32,
+        id,
+        meta: PacketMeta::Command(command_id),
+    };
+
+    let mut header_bytes = Vec::with_capacity(PacketHeader::JDWP_SIZE);
+    header.write(&mut JdwpWriter::new(&mut header_bytes, id_sizes.clone()))?;
+    Ok(header_bytes)
+}
+
+/// Reads the reply data, making sure that all of it was consumed.
+pub(crate) fn decode_reply<T: JdwpReadable>(
+    data: Vec<u8>,
+    id_sizes: &IDSizeInfo,
+) -> Result<T, ClientError> {
+    let len = data.len();
+    let mut cursor = Cursor::new(data);
+    let result = T::read(&mut JdwpReader::new(&mut cursor, id_sizes.clone()))?;
+
+    if cursor.position() < len as u64 {
+        Err(ClientError::TrailingData {
+            expected: len,
+            consumed: cursor.position() as usize,
+        })
+    } else {
+        Ok(result)
+    }
+}
+
+/// Writes all the parts of a packet with vectored writes, so that the large
+/// byte arrays borrowed by [encode_command] are sent as they are, without
+/// copying them into one buffer with the rest of the packet.
+fn write_packet(write: &mut impl Write, parts: &[&[u8]]) -> io::Result<()> {
+    let mut parts = parts.to_vec();
+    let mut first = 0;
+    while first < parts.len() {
+        let slices = parts[first..]
+            .iter()
+            .map(|part| IoSlice::new(part))
+            .collect::<Vec<_>>();
+        match write.write_vectored(&slices) {
+            Ok(0) if parts[first..].iter().any(|part| !part.is_empty()) => {
+                return Err(io::Error::from(io::ErrorKind::WriteZero))
+            }
+            Ok(mut n) => {
+                // skip what was written, the empty parts included
+                while first < parts.len() && n >= parts[first].len() {
+                    n -= parts[first].len();
+                    first += 1;
+                }
+                if n > 0 {
+                    parts[first] = &parts[first][n..];
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
         }
     }
+    Ok(())
 }
 
 fn read_packet(
     reader: &mut JdwpReader<TcpStream>,
     waiting: &WaitingMap,
+    subscriptions: &Subscriptions,
+    recorder: &Recorder,
+    tracer: &Tracer,
     host_events_tx: &Sender<Composite>,
 ) -> Result<(), ClientError> {
     let header = PacketHeader::read(reader)?;
@@ -185,6 +817,28 @@ fn read_packet(
 
     reader.read_exact(&mut data)?;
 
+    let event = match header.meta {
+        PacketMeta::Command(command) => TraceEvent::Event {
+            id: header.id,
+            command,
+            length: header.length,
+            data: &data,
+        },
+        PacketMeta::Reply(error_code) => TraceEvent::Reply {
+            id: header.id,
+            error_code,
+            length: header.length,
+            data: &data,
+        },
+    };
+    Trace::fire(tracer, event);
+
+    if let Some(recorder) = recorder.lock().unwrap().as_mut() {
+        if let Err(e) = recorder.record(&reader.id_sizes, &header, &data) {
+            log::warn!("Failed to record the packet: {}", e);
+        }
+    }
+
     let to_send = match header.meta {
         // handle the host-sent commands;
         // the only one is the Event command
@@ -196,7 +850,36 @@ fn read_packet(
 
             log::trace!("[host] event: {:#?}", composite);
 
-            host_events_tx.send(composite).unwrap();
+            let mut subscriptions = subscriptions.lock().unwrap();
+            let events = composite
+                .events
+                .into_iter()
+                .filter_map(|event| {
+                    let Some(request_id) = event.request_id() else {
+                        return Some(event);
+                    };
+                    let Some(subscriber) = subscriptions.get(&request_id) else {
+                        return Some(event);
+                    };
+                    match subscriber.send(event) {
+                        Ok(()) => None,
+                        Err(SendError(event)) => {
+                            // the subscriber is gone, route to host events
+                            subscriptions.remove(&request_id);
+                            Some(event)
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            if !events.is_empty() {
+                host_events_tx
+                    .send(Composite {
+                        suspend_policy: composite.suspend_policy,
+                        events,
+                    })
+                    .unwrap();
+            }
             return Ok(());
         }
         PacketMeta::Command(command_id) => {
@@ -216,8 +899,24 @@ fn read_packet(
         }
     };
 
-    match waiting.lock().unwrap().remove(&header.id) {
-        Some(waiter) => waiter.send(to_send).unwrap(), // one-shot channel send
+    let waiter = waiting
+        .lock()
+        .unwrap()
+        .as_mut()
+        .and_then(|map| map.remove(&header.id));
+    match waiter {
+        Some(waiter) => {
+            if let (Some(events), Ok(data)) = (waiter.events, &to_send) {
+                let request_id = RequestID::read(&mut JdwpReader::new(
+                    &mut Cursor::new(data),
+                    reader.id_sizes.clone(),
+                ))?;
+                subscriptions.lock().unwrap().insert(request_id, events);
+            }
+            // the sender might have given up on the reply, e.g. when send_all
+            // fails to write the rest of its commands
+            drop(waiter.reply.send(to_send))
+        }
         None => log::warn!(
             "Received an unexpected packet from the JVM, ignoring: {:?}",
             header
@@ -225,3 +924,373 @@ fn read_packet(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::net::{SocketAddr, TcpListener};
+
+    use super::*;
+    use crate::{
+        commands::virtual_machine::{RedefineClasses, RedefiningClass},
+        recording::EventReplayer,
+        types::{ObjectID, ReferenceTypeID},
+    };
+
+    /// Starts a fake host that replies to the [IDSizes] sent when attaching,
+    /// then reads the header of a single command and responds with whatever
+    /// bytes the given function returns for it, and closes the connection.
+    fn scripted_host(
+        respond: impl FnOnce(&[u8]) -> Vec<u8> + Send + 'static,
+    ) -> io::Result<(SocketAddr, JoinHandle<io::Result<()>>)> {
+        let listener = TcpListener::bind("localhost:0")?;
+        let addr = listener.local_addr()?;
+
+        let host = thread::spawn(move || {
+            let (mut stream, _) = listener.accept()?;
+            let handshake = &mut [0; HANDSHAKE.len()];
+            stream.read_exact(handshake)?;
+            stream.write_all(HANDSHAKE)?;
+
+            let header = &mut [0; PacketHeader::JDWP_SIZE];
+            stream.read_exact(header)?;
+            stream.write_all(&reply_packet(header, &id_sizes_reply(0)))?;
+
+            stream.read_exact(header)?;
+
+            stream.write_all(&respond(header))
+        });
+        Ok((addr, host))
+    }
+
+    /// Starts a fake host that replies to a single command with the given
+    /// data after a delay.
+    fn fake_host(
+        data: Vec<u8>,
+        delay: Duration,
+    ) -> io::Result<(SocketAddr, JoinHandle<io::Result<()>>)> {
+        scripted_host(move |header| {
+            thread::sleep(delay);
+            reply_packet(header, &data)
+        })
+    }
+
+    /// A successful reply to the command with the given header.
+    fn reply_packet(header: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut reply = Vec::new();
+        reply.extend_from_slice(&((PacketHeader::JDWP_SIZE + data.len()) as u32).to_be_bytes());
+        reply.extend_from_slice(&header[4..8]); // the id
+        reply.extend_from_slice(&[0x80, 0, 0]); // reply flag and no error
+        reply.extend_from_slice(data);
+        reply
+    }
+
+    /// A composite event packet with a single VmDeath event.
+    fn vm_death_event() -> Vec<u8> {
+        let mut event = Vec::new();
+        event.extend_from_slice(&(PacketHeader::JDWP_SIZE as u32 + 10).to_be_bytes());
+        event.extend_from_slice(&0u32.to_be_bytes()); // the id
+        event.extend_from_slice(&[0, 64, 100]); // the Composite command
+        event.push(0); // suspend policy None
+        event.extend_from_slice(&1u32.to_be_bytes()); // one event
+        event.push(99); // VmDeath
+        event.extend_from_slice(&0u32.to_be_bytes()); // request id
+        event
+    }
+
+    fn id_sizes_reply(extra: usize) -> Vec<u8> {
+        let mut data = 8u32.to_be_bytes().repeat(5);
+        data.resize(data.len() + extra, 0);
+        data
+    }
+
+    /// Writes at most a few bytes at a time, to make sure partial vectored
+    /// writes are handled.
+    struct ChunkedWriter(Vec<u8>);
+
+    impl Write for ChunkedWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(7);
+            self.0.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+            let mut n = 0;
+            for buf in bufs {
+                let written = self.write(buf)?;
+                n += written;
+                if written < buf.len() {
+                    break;
+                }
+            }
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn vectored_write() -> io::Result<()> {
+        let bytes = (0..1024 * 1024).map(|i| i as u8).collect::<Vec<_>>();
+        let bytes_ptr = bytes.as_ptr();
+        let command = RedefineClasses::new(vec![RedefiningClass::new(
+            // SAFETY: it's never sent anywhere
+            unsafe { ReferenceTypeID::new(42) },
+            bytes,
+        )]);
+
+        let (header, data) = encode_command(&command, 1, &ID_SIZES)?;
+        // the class file is sent from where it is
+        assert!(data.slices().iter().any(|part| part.as_ptr() == bytes_ptr));
+
+        // the plain path, the header and the data written one after another
+        let mut expected = header.clone();
+        command.write(&mut JdwpWriter::new(&mut expected, ID_SIZES))?;
+
+        let mut parts = vec![&header[..]];
+        parts.extend(data.slices());
+        let mut writer = ChunkedWriter(Vec::new());
+        write_packet(&mut writer, &parts)?;
+        assert!(writer.0 == expected, "the vectored write is different");
+
+        Ok(())
+    }
+
+    #[test]
+    fn ping() -> Result<(), ClientError> {
+        const DELAY: Duration = Duration::from_millis(50);
+
+        let (addr, host) = fake_host(id_sizes_reply(0), DELAY)?;
+
+        let mut client = JdwpClient::attach(addr)?;
+        let ping = client.ping()?;
+        assert!(ping >= DELAY, "{ping:?} is less than the delay");
+        assert!(ping < Duration::from_secs(5), "{ping:?} is too long");
+
+        host.join().unwrap()?;
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_data() -> Result<(), ClientError> {
+        let (addr, host) = fake_host(id_sizes_reply(4), Duration::ZERO)?;
+
+        let mut client = JdwpClient::attach(addr)?;
+        let result = client.send(IDSizes);
+        assert!(
+            matches!(
+                result,
+                Err(ClientError::TrailingData {
+                    expected: 24,
+                    consumed: 20
+                })
+            ),
+            "{result:?}"
+        );
+
+        host.join().unwrap()?;
+        Ok(())
+    }
+
+    #[test]
+    fn vm_death_while_waiting() -> Result<(), ClientError> {
+        // the host sends the VmDeath event instead of the reply and exits
+        let (addr, host) = scripted_host(|_| vm_death_event())?;
+
+        let mut client = JdwpClient::attach(addr)?;
+        let result = client.send(IDSizes);
+        assert!(
+            matches!(result, Err(ClientError::Disconnected)),
+            "{result:?}"
+        );
+
+        let composite = client.host_events().recv_timeout(Duration::from_secs(5));
+        assert!(
+            matches!(
+                composite.as_ref().map(|c| &c.events[..]),
+                Ok([Event::VmDeath(_)])
+            ),
+            "{composite:?}"
+        );
+
+        host.join().unwrap()?;
+        Ok(())
+    }
+
+    #[test]
+    fn id_generator() -> Result<(), ClientError> {
+        let (header_tx, header_rx) = mpsc::channel();
+        let (addr, host) = scripted_host(move |header| {
+            header_tx.send(header.to_vec()).unwrap();
+            reply_packet(header, &id_sizes_reply(0))
+        })?;
+
+        let mut ids = 42..;
+        let mut client = JdwpClient::attach(addr)?.with_id_generator(move || ids.next().unwrap());
+        client.send(IDSizes)?;
+
+        let header = header_rx.recv().unwrap();
+        assert_eq!(header[4..8], 42u32.to_be_bytes());
+
+        host.join().unwrap()?;
+        Ok(())
+    }
+
+    /// Writes a byte at a time, so that the reads on the other side get
+    /// split up.
+    fn trickle(stream: &mut TcpStream, bytes: &[u8]) -> io::Result<()> {
+        for byte in bytes {
+            stream.write_all(&[*byte])?;
+            thread::sleep(Duration::from_millis(1));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn fragmented_delivery() -> Result<(), ClientError> {
+        let listener = TcpListener::bind("localhost:0")?;
+        let addr = listener.local_addr()?;
+
+        let host = thread::spawn(move || {
+            let (mut stream, _) = listener.accept()?;
+            stream.set_nodelay(true)?;
+
+            let handshake = &mut [0; HANDSHAKE.len()];
+            stream.read_exact(handshake)?;
+            trickle(&mut stream, HANDSHAKE)?;
+
+            let header = &mut [0; PacketHeader::JDWP_SIZE];
+            stream.read_exact(header)?;
+            trickle(&mut stream, &reply_packet(header, &id_sizes_reply(0)))?;
+
+            stream.read_exact(header)?;
+            trickle(&mut stream, &reply_packet(header, &id_sizes_reply(0)))
+        });
+
+        let mut client = JdwpClient::attach(addr)?;
+        let reply = client.send(IDSizes)?;
+        assert_eq!(reply.object_id_size, 8);
+
+        host.join().unwrap()?;
+        Ok(())
+    }
+
+    #[test]
+    fn fetched_id_sizes() -> Result<(), ClientError> {
+        let listener = TcpListener::bind("localhost:0")?;
+        let addr = listener.local_addr()?;
+
+        let host = thread::spawn(move || {
+            let (mut stream, _) = listener.accept()?;
+            let handshake = &mut [0; HANDSHAKE.len()];
+            stream.read_exact(handshake)?;
+            stream.write_all(HANDSHAKE)?;
+
+            let header = &mut [0; PacketHeader::JDWP_SIZE];
+            stream.read_exact(header)?;
+            // an event before the reply, decoded with the sizes from the reply
+            let mut packets = vm_death_event();
+            let sizes = [8u32, 8, 4, 8, 8].map(u32::to_be_bytes).concat();
+            packets.extend(reply_packet(header, &sizes));
+            stream.write_all(&packets)
+        });
+
+        let client = JdwpClient::attach(addr)?;
+        assert_eq!(client.id_sizes().object_id_size, 4);
+        assert_eq!(client.id_sizes().method_id_size, 8);
+
+        let composite = client.host_events().recv_timeout(Duration::from_secs(5));
+        assert!(
+            matches!(
+                composite.as_ref().map(|c| &c.events[..]),
+                Ok([Event::VmDeath(_)])
+            ),
+            "{composite:?}"
+        );
+
+        host.join().unwrap()?;
+        Ok(())
+    }
+
+    #[test]
+    fn handshake_timeout() -> io::Result<()> {
+        // the connection is accepted by the OS, but the handshake never comes
+        let listener = TcpListener::bind("localhost:0")?;
+
+        let start = Instant::now();
+        let result = JdwpClient::attach_timeout(listener.local_addr()?, Duration::from_millis(100));
+        let elapsed = start.elapsed();
+
+        assert!(
+            matches!(&result, Err(ClientError::IoError(e)) if e.kind() == io::ErrorKind::TimedOut),
+            "{result:?}"
+        );
+        assert!(elapsed < Duration::from_secs(5), "{elapsed:?} is too long");
+        Ok(())
+    }
+
+    /// A buffer that is still accessible after being given away to the
+    /// recorder.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            Write::write(&mut *self.0.lock().unwrap(), buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn record_and_replay() -> Result<(), ClientError> {
+        let (addr, host) = scripted_host(|header| {
+            let mut packets = vm_death_event();
+            packets.extend(reply_packet(header, &id_sizes_reply(0)));
+            packets
+        })?;
+
+        let buffer = SharedBuffer::default();
+        let mut client = JdwpClient::attach(addr)?;
+        client.set_recorder(Some(EventRecorder::new(buffer.clone())));
+        // the event comes first, so both are recorded once the reply is in
+        client.send(IDSizes)?;
+        host.join().unwrap()?;
+
+        let recording = buffer.0.lock().unwrap().clone();
+        let replayed = EventReplayer::new(&recording[..]).collect::<Result<Vec<_>, _>>()?;
+        assert!(
+            matches!(&replayed[..], [Composite { events, .. }] if matches!(events[..], [Event::VmDeath(_)])),
+            "{replayed:?}"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn replay_with_recorded_id_sizes() -> Result<(), ClientError> {
+        let mut recording = 4u32.to_be_bytes().repeat(5);
+        recording.extend_from_slice(&(PacketHeader::JDWP_SIZE as u32 + 14).to_be_bytes());
+        recording.extend_from_slice(&0u32.to_be_bytes()); // the id
+        recording.extend_from_slice(&[0, 64, 100]); // the Composite command
+        recording.push(0); // suspend policy None
+        recording.extend_from_slice(&1u32.to_be_bytes()); // one event
+        recording.push(90); // VmStart
+        recording.extend_from_slice(&0u32.to_be_bytes()); // request id
+        recording.extend_from_slice(&0xCAFEu32.to_be_bytes()); // 4-byte thread id
+
+        // SAFETY: it's only compared
+        let thread = unsafe { ObjectID::new(0xCAFE) };
+        let replayed = EventReplayer::new(&recording[..]).collect::<Result<Vec<_>, _>>()?;
+        assert!(
+            matches!(&replayed[..], [Composite { events, .. }] if matches!(&events[..], [Event::VmStart(e)] if *e.thread_id == thread)),
+            "{replayed:?}"
+        );
+
+        Ok(())
+    }
+}
@@ -6,7 +6,7 @@ use std::{
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 use paste::paste;
 
-pub use jdwp_macros::{JdwpReadable, JdwpWritable};
+pub use jdwp_macros::{ByteTag, JdwpReadable, JdwpWritable};
 
 use crate::commands::virtual_machine::IDSizeInfo;
 
@@ -17,7 +17,9 @@ pub struct JdwpWriter<W: Write> {
 }
 
 impl<W: Write> JdwpWriter<W> {
-    pub(crate) fn new(write: W, id_sizes: IDSizeInfo) -> Self {
+    /// Creates a writer with the given ID sizes, e.g. to encode the data of
+    /// a [raw command](crate::client::JdwpClient::send_raw).
+    pub fn new(write: W, id_sizes: IDSizeInfo) -> Self {
         Self { write, id_sizes }
     }
 }
@@ -36,39 +38,62 @@ impl<W: Write> DerefMut for JdwpWriter<W> {
     }
 }
 
+/// The encoded data of a command, with the large byte arrays borrowed from
+/// the command instead of being copied in, see [JdwpWritable::write_parts].
 #[derive(Debug)]
-pub struct JdwpReader<R: Read> {
-    read: R,
-    buffered_byte: Option<u8>,
-    pub(crate) id_sizes: IDSizeInfo,
+pub struct DataParts<'a> {
+    writer: JdwpWriter<Vec<u8>>,
+    // the borrowed byte arrays and the positions in the buffer they go at
+    borrowed: Vec<(usize, &'a [u8])>,
 }
 
-impl<R: Read> JdwpReader<R> {
-    pub(crate) fn new(read: R, id_sizes: IDSizeInfo) -> Self {
+impl<'a> DataParts<'a> {
+    pub(crate) fn new(id_sizes: IDSizeInfo) -> Self {
         Self {
-            read,
-            buffered_byte: None,
-            id_sizes,
+            writer: JdwpWriter::new(Vec::new(), id_sizes),
+            borrowed: Vec::new(),
         }
     }
 
-    pub(crate) fn peek_u8(&mut self) -> io::Result<u8> {
-        let b = self.read.read_u8()?;
-        let prev = self.buffered_byte.replace(b);
-        assert!(prev.is_none(), "Already contained unconsumed tag byte");
-        Ok(b)
+    /// The writer for everything that is not borrowed.
+    pub(crate) fn writer(&mut self) -> &mut JdwpWriter<Vec<u8>> {
+        &mut self.writer
     }
 
-    /// This wins over read_u8 from the deref-ed [Writer].
-    /// It exists to first consume the peeked byte after calling [peek_u8].
-    ///
-    /// Other read methods do not consume the buffered byte, but peek_u8
-    /// is only called before reading a tag byte.
-    pub(crate) fn read_u8(&mut self) -> io::Result<u8> {
-        match self.buffered_byte.take() {
-            Some(b) => Ok(b),
-            None => self.read.read_u8(),
+    /// Appends the bytes without copying them.
+    pub(crate) fn borrow(&mut self, bytes: &'a [u8]) {
+        self.borrowed.push((self.writer.len(), bytes));
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.writer.len() + self.borrowed.iter().map(|(_, b)| b.len()).sum::<usize>()
+    }
+
+    /// The parts of the data in order, ready for a vectored write.
+    pub(crate) fn slices(&self) -> Vec<&[u8]> {
+        let mut slices = Vec::with_capacity(self.borrowed.len() * 2 + 1);
+        let mut start = 0;
+        for &(at, bytes) in &self.borrowed {
+            slices.push(&self.writer[start..at]);
+            slices.push(bytes);
+            start = at;
         }
+        slices.push(&self.writer[start..]);
+        slices
+    }
+}
+
+#[derive(Debug)]
+pub struct JdwpReader<R: Read> {
+    read: R,
+    pub(crate) id_sizes: IDSizeInfo,
+}
+
+impl<R: Read> JdwpReader<R> {
+    /// Creates a reader with the given ID sizes, e.g. to decode the reply to
+    /// a [raw command](crate::client::JdwpClient::send_raw).
+    pub fn new(read: R, id_sizes: IDSizeInfo) -> Self {
+        Self { read, id_sizes }
     }
 }
 
@@ -92,6 +117,27 @@ pub trait JdwpReadable: Sized {
 
 pub trait JdwpWritable {
     fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()>;
+
+    /// Same as [write](JdwpWritable::write), but the large byte arrays, such
+    /// as the class files of
+    /// [RedefineClasses](crate::commands::virtual_machine::RedefineClasses),
+    /// can be borrowed instead of copied, and then sent as separate slices
+    /// of a vectored write.
+    ///
+    /// The default just writes everything into the buffer.
+    fn write_parts<'a>(&'a self, parts: &mut DataParts<'a>) -> io::Result<()> {
+        self.write(parts.writer())
+    }
+}
+
+/// A tag byte that tells what follows it on the wire, such as the
+/// [Tag](crate::enums::Tag), or a value that is sent prefixed with one, such
+/// as the [Value](crate::types::Value).
+///
+/// It can be derived for custom fieldless `#[repr(u8)]` enums, the tag of
+/// each variant being its discriminant.
+pub trait ByteTag {
+    fn byte_tag(&self) -> u8;
 }
 
 impl JdwpReadable for () {
@@ -178,20 +224,37 @@ macro_rules! int_io {
 
 int_io![i16, u16, i32, u32, i64, u64, f32, f64];
 
+/// The strings are standard UTF-8 on the wire, as the JDWP spec says, and
+/// HotSpot converts them from and to the modified UTF-8 of the JVM itself.
+///
+/// Other target VMs might send the modified UTF-8 (CESU-8 with the NUL encoded
+/// as two bytes) as is though, so when the bytes are not valid UTF-8 they are
+/// decoded as that instead.
 impl JdwpReadable for String {
     #[inline]
     fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
         let mut bytes = vec![0; u32::read(read)? as usize];
         read.read_exact(&mut bytes)?;
-        String::from_utf8(bytes).map_err(|_| Error::from(ErrorKind::InvalidData))
+        String::from_utf8(bytes).or_else(|e| {
+            cesu8::from_java_cesu8(e.as_bytes())
+                .map(|string| string.into_owned())
+                .map_err(|_| Error::from(ErrorKind::InvalidData))
+        })
     }
 }
 
+/// Always written as standard UTF-8, see the [JdwpReadable] impl.
 impl JdwpWritable for String {
     fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
         (self.len() as u32).write(write)?;
         write.write_all(self.as_bytes())
     }
+
+    fn write_parts<'a>(&'a self, parts: &mut DataParts<'a>) -> io::Result<()> {
+        (self.len() as u32).write(parts.writer())?;
+        parts.borrow(self.as_bytes());
+        Ok(())
+    }
 }
 
 impl<T: JdwpReadable> JdwpReadable for Vec<T> {
@@ -213,4 +276,151 @@ impl<T: JdwpWritable> JdwpWritable for Vec<T> {
         }
         Ok(())
     }
+
+    fn write_parts<'a>(&'a self, parts: &mut DataParts<'a>) -> io::Result<()> {
+        (self.len() as u32).write(parts.writer())?;
+        for item in self {
+            item.write_parts(parts)?;
+        }
+        Ok(())
+    }
+}
+
+/// Same as the [Vec] impl, but a list longer than the capacity fails with
+/// [InvalidData](ErrorKind::InvalidData) instead of being truncated, as the
+/// rest of it would be left unread.
+///
+/// Enabled by the `arrayvec` feature, e.g. to decode a small list reply of
+/// [send_raw](crate::client::JdwpClient::send_raw) without allocating.
+#[cfg(feature = "arrayvec")]
+impl<T: JdwpReadable, const N: usize> JdwpReadable for arrayvec::ArrayVec<T, N> {
+    fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
+        let len = u32::read(read)?;
+        if len as usize > N {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{len} items do not fit into the capacity of {N}"),
+            ));
+        }
+        let mut res = arrayvec::ArrayVec::new();
+        for _ in 0..len {
+            res.push(T::read(read)?);
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(feature = "arrayvec")]
+impl<T: JdwpWritable, const N: usize> JdwpWritable for arrayvec::ArrayVec<T, N> {
+    fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
+        (self.len() as u32).write(write)?;
+        for item in self {
+            item.write(write)?;
+        }
+        Ok(())
+    }
+}
+
+macro_rules! tuple_io {
+    ($(($($types:ident),+)),* $(,)?) => {
+        $(
+            impl<$($types: JdwpReadable),+> JdwpReadable for ($($types,)+) {
+                #[inline]
+                fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
+                    // tuple fields are evaluated left to right
+                    Ok(($($types::read(read)?,)+))
+                }
+            }
+
+            impl<$($types: JdwpWritable),+> JdwpWritable for ($($types,)+) {
+                #[inline]
+                fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
+                    #[allow(non_snake_case)]
+                    let ($($types,)+) = self;
+                    $($types.write(write)?;)+
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+tuple_io![
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H),
+    (A, B, C, D, E, F, G, H, I),
+    (A, B, C, D, E, F, G, H, I, J),
+    (A, B, C, D, E, F, G, H, I, J, K),
+    (A, B, C, D, E, F, G, H, I, J, K, L),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ID_SIZES;
+
+    #[derive(Debug, PartialEq, JdwpReadable, JdwpWritable)]
+    struct Reserved {
+        value: u16,
+        #[skip]
+        reserved: u8,
+    }
+
+    #[test]
+    fn skip_on_write() -> io::Result<()> {
+        let mut bytes = Vec::new();
+        let reserved = Reserved {
+            value: 42,
+            reserved: 7,
+        };
+        reserved.write(&mut JdwpWriter::new(&mut bytes, ID_SIZES))?;
+        assert_eq!(bytes, [0, 42]);
+
+        // it's still read
+        bytes.push(7);
+        let read = Reserved::read(&mut JdwpReader::new(&bytes[..], ID_SIZES))?;
+        assert_eq!(read, reserved);
+
+        Ok(())
+    }
+
+    #[test]
+    fn tuple_round_trip() -> io::Result<()> {
+        let tuple = (42u8, true, -7i32, u64::MAX, 1.5f64);
+
+        let mut bytes = Vec::new();
+        tuple.write(&mut JdwpWriter::new(&mut bytes, ID_SIZES))?;
+        assert_eq!(bytes.len(), 1 + 1 + 4 + 8 + 8);
+
+        let read = <(u8, bool, i32, u64, f64)>::read(&mut JdwpReader::new(&bytes[..], ID_SIZES))?;
+        assert_eq!(read, tuple);
+
+        Ok(())
+    }
+
+    #[test]
+    fn modified_utf8_string() -> io::Result<()> {
+        let string = "emoji 😀 and a \0 nul";
+        let encoded = cesu8::to_java_cesu8(string);
+        assert_ne!(encoded, string.as_bytes());
+
+        let mut bytes = Vec::new();
+        (encoded.len() as u32).write(&mut JdwpWriter::new(&mut bytes, ID_SIZES))?;
+        bytes.extend_from_slice(&encoded);
+        let read = String::read(&mut JdwpReader::new(&bytes[..], ID_SIZES))?;
+        assert_eq!(read, string);
+
+        // garbage is still rejected
+        let bytes: &[u8] = &[0, 0, 0, 1, 0xFF];
+        let result = String::read(&mut JdwpReader::new(bytes, ID_SIZES));
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::InvalidData);
+
+        Ok(())
+    }
 }
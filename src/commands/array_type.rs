@@ -17,6 +17,7 @@ pub struct NewInstance {
 }
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NewInstanceReply {
     // should always be Tag::Array
     _tag: Tag,
@@ -0,0 +1,141 @@
+use std::io::{self, Read};
+
+use crate::{
+    codec::{JdwpReadable, JdwpReader, JdwpWritable},
+    enums::InvokeOptions,
+    types::{ClassID, FieldID, MethodID, TaggedObjectID, ThreadID, Untagged, Value},
+};
+
+use super::{jdwp_command, object_reference::InvokeMethodReply};
+
+/// Returns the immediate superclass of a class.
+///
+/// The return is null if the class is `java.lang.Object`.
+#[jdwp_command(Option<ClassID>, 3, 1)]
+#[derive(Debug, JdwpWritable)]
+pub struct Superclass {
+    /// The class type ID.
+    class_id: ClassID,
+}
+
+/// Sets the value of one or more static fields.
+///
+/// Each field must be member of the class type or one of its superclasses,
+/// superinterfaces, or implemented interfaces.
+///
+/// Access control is not enforced; for example, the values of private fields
+/// can be set.
+///
+/// Final fields cannot be set.
+///
+/// For primitive values, the value's type must match the field's type exactly.
+///
+/// For object values, there must exist a widening reference conversion from
+/// the value's type to the field's type and the field's type must be loaded.
+#[jdwp_command((), 3, 2)]
+#[derive(Debug, JdwpWritable)]
+pub struct SetValues {
+    /// The class type ID.
+    class_id: ClassID,
+    /// Fields to set and their values.
+    values: Vec<FieldValue>,
+}
+
+#[derive(Debug, Clone, JdwpWritable)]
+pub struct FieldValue {
+    /// Field to set.
+    pub field_id: FieldID,
+    /// Value to put in the field.
+    pub value: Untagged,
+}
+
+/// Invokes a static method.
+///
+/// The method must be member of the class type or one of its superclasses.
+/// Access control is not enforced; for example, private methods can be
+/// invoked.
+///
+/// The method invocation will occur in the specified thread. Method
+/// invocation can occur only if the specified thread has been suspended by an
+/// event. Method invocation is not supported when the target VM has been
+/// suspended by the front-end.
+///
+/// The specified method is invoked with the arguments in the specified
+/// argument list. The method invocation is synchronous; the reply packet is
+/// not sent until the invoked method returns in the target VM. The return
+/// value (possibly the void value) is included in the reply packet. If the
+/// invoked method throws an exception, the exception object ID is set in the
+/// reply packet; otherwise, the exception object ID is null.
+///
+/// For primitive arguments, the argument value's type must match the
+/// argument's type exactly. For object arguments, there must exist a widening
+/// reference conversion from the argument value's type to the argument's type
+/// and the argument's type must be loaded.
+///
+/// The threads are resumed during the invocation the same way as with the
+/// [InvokeMethod](super::object_reference::InvokeMethod) of an object.
+#[jdwp_command(InvokeMethodReply, 3, 3)]
+#[derive(Debug, JdwpWritable)]
+pub struct InvokeMethod {
+    /// The class type ID.
+    pub class_id: ClassID,
+    /// The thread in which to invoke.
+    pub thread: ThreadID,
+    /// The method to invoke.
+    pub method: MethodID,
+    /// The argument values.
+    pub arguments: Vec<Value>,
+    /// Invocation options
+    pub options: InvokeOptions,
+}
+
+/// Creates a new object of this type, invoking the specified constructor.
+///
+/// The constructor method ID must be a member of the class type.
+///
+/// Instance creation will occur in the specified thread, with the same
+/// restrictions and the same handling of the arguments and the other threads
+/// as with [InvokeMethod].
+///
+/// If the constructor throws an exception, the exception object ID is set in
+/// the reply packet and the new object is null.
+#[jdwp_command(3, 4)]
+#[derive(Debug, JdwpWritable)]
+pub struct NewInstance {
+    /// The class type ID.
+    pub class_id: ClassID,
+    /// The thread in which to invoke the constructor.
+    pub thread: ThreadID,
+    /// The constructor to invoke.
+    pub method: MethodID,
+    /// The argument values.
+    pub arguments: Vec<Value>,
+    /// Constructor invocation options
+    pub options: InvokeOptions,
+}
+
+/// The result of creating an instance, which either succeeded or the
+/// constructor threw.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NewInstanceReply {
+    /// The newly created object
+    Instance(TaggedObjectID),
+    /// The thrown exception
+    Exception(TaggedObjectID),
+}
+
+impl JdwpReadable for NewInstanceReply {
+    fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
+        let new_object = Option::<TaggedObjectID>::read(read)?;
+        let exception = Option::<TaggedObjectID>::read(read)?;
+        match (new_object, exception) {
+            (_, Some(exception)) => Ok(NewInstanceReply::Exception(exception)),
+            (Some(new_object), None) => Ok(NewInstanceReply::Instance(new_object)),
+            (None, None) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Neither the new object nor the exception were sent",
+            )),
+        }
+    }
+}
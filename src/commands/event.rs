@@ -22,6 +22,7 @@ use super::jdwp_command;
 /// This event is always generated by the target VM, even if not explicitly
 /// requested.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VmStart {
     /// Request that generated event (or 0 if this event is automatically
     /// generated)
@@ -34,6 +35,7 @@ pub struct VmStart {
 ///
 /// The step event is generated before the code at its location is executed.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SingleStep {
     /// Request that generated event
     pub request_id: i32,
@@ -48,6 +50,7 @@ pub struct SingleStep {
 /// The breakpoint event is generated before the code at its location is
 /// executed.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Breakpoint {
     /// Request that generated event
     pub request_id: i32,
@@ -67,6 +70,7 @@ pub struct Breakpoint {
 /// its thread start event occurs if methods are called as part of the thread's
 /// initialization.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MethodEntry {
     /// Request that generated event
     pub request_id: i32,
@@ -86,6 +90,7 @@ pub struct MethodEntry {
 /// Method exit events are not generated if the method terminates with a thrown
 /// exception.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MethodExit {
     /// Request that generated event
     pub request_id: i32,
@@ -107,6 +112,7 @@ pub struct MethodExit {
 ///
 /// Since JDWP version 1.6.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MethodExitWithReturnValue {
     /// Request that generated event
     pub request_id: i32,
@@ -126,6 +132,7 @@ pub struct MethodExitWithReturnValue {
 ///
 /// Since JDWP version 1.6.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MonitorContendedEnter {
     /// Request that generated event
     pub request_id: i32,
@@ -145,6 +152,7 @@ pub struct MonitorContendedEnter {
 ///
 /// Since JDWP version 1.6.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MonitorContendedEntered {
     /// Request that generated event
     pub request_id: i32,
@@ -163,6 +171,7 @@ pub struct MonitorContendedEntered {
 ///
 /// Since JDWP version 1.6.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MonitorWait {
     /// Request that generated event
     pub request_id: i32,
@@ -184,6 +193,7 @@ pub struct MonitorWait {
 ///
 /// Since JDWP version 1.6.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MonitorWaited {
     /// Request that generated event
     pub request_id: i32,
@@ -206,6 +216,7 @@ pub struct MonitorWaited {
 /// generated at the first non-native location reached after the exception is
 /// thrown.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Exception {
     /// Request that generated event
     pub request_id: i32,
@@ -273,6 +284,7 @@ pub struct Exception {
 /// object which may have happened much earlier, depending on the VM being
 /// debugged.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ThreadStart {
     /// Request that generated event
     pub request_id: i32,
@@ -294,6 +306,7 @@ pub struct ThreadStart {
 /// It may or may not be collected soon depending on what references exist in
 /// the target VM.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ThreadDeath {
     /// Request that generated event
     pub request_id: i32,
@@ -308,6 +321,7 @@ pub struct ThreadDeath {
 /// Class prepare events are not generated for primitive classes
 /// (for example, `java.lang.Integer.TYPE`).
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassPrepare {
     /// Request that generated event
     pub request_id: i32,
@@ -342,6 +356,7 @@ pub struct ClassPrepare {
 /// There are severe constraints on the debugger back-end during garbage
 /// collection, so unload information is greatly limited.
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassUnload {
     /// Request that generated event
     pub request_id: i32,
@@ -356,6 +371,7 @@ pub struct ClassUnload {
 /// Requires `can_watch_field_access` capability - see
 /// [CapabilitiesNew](super::virtual_machine::CapabilitiesNew).
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FieldAccess {
     /// Request that generated event
     pub request_id: i32,
@@ -372,6 +388,7 @@ pub struct FieldAccess {
 }
 
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FieldModification {
     /// Request that generated event
     pub request_id: RequestID,
@@ -390,19 +407,55 @@ pub struct FieldModification {
 }
 
 #[derive(Debug, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VmDeath {
     /// Request that generated event
     pub request_id: i32,
 }
 
+// most of the events have a plain i32 request id,
+// but the FieldModification one has a proper RequestID
+trait IntoRequestID {
+    fn into_request_id(self) -> RequestID;
+}
+
+impl IntoRequestID for i32 {
+    fn into_request_id(self) -> RequestID {
+        // SAFETY: the id was received from the host
+        unsafe { RequestID::new(self) }
+    }
+}
+
+impl IntoRequestID for RequestID {
+    fn into_request_id(self) -> RequestID {
+        self
+    }
+}
+
+// SAFETY: zero is what the host sends for the automatically generated events
+const AUTOMATIC: RequestID = unsafe { RequestID::new(0) };
+
 macro_rules! event_io {
     ($($events:ident),* $(,)?) => {
 
         #[derive(Debug)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         pub enum Event {
             $($events($events),)*
         }
 
+        impl Event {
+            /// The request that generated this event, see [RequestID], or
+            /// `None` if the event was generated automatically, e.g. a
+            /// [VmStart] or a [VmDeath] that nobody requested.
+            pub fn request_id(&self) -> Option<RequestID> {
+                let request_id = match self {
+                    $(Event::$events(e) => e.request_id.into_request_id(),)*
+                };
+                (request_id != AUTOMATIC).then_some(request_id)
+            }
+        }
+
         impl JdwpReadable for Event {
             fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
                 match EventKind::read(read)? {
@@ -449,9 +502,128 @@ event_io! {
     VmDeath,
 }
 
+impl Event {
+    /// The thread in which the event occurred, `None` for the
+    /// [ClassUnload] and [VmDeath] events that are not tied to any thread.
+    pub fn thread(&self) -> Option<ThreadID> {
+        Some(match self {
+            Event::VmStart(e) => e.thread_id,
+            Event::SingleStep(e) => e.thread,
+            Event::Breakpoint(e) => e.thread,
+            Event::MethodEntry(e) => e.thread,
+            Event::MethodExit(e) => e.thread,
+            Event::MethodExitWithReturnValue(e) => e.thread,
+            Event::MonitorContendedEnter(e) => e.thread,
+            Event::MonitorContendedEntered(e) => e.thread,
+            Event::MonitorWait(e) => e.thread,
+            Event::MonitorWaited(e) => e.thread,
+            Event::Exception(e) => e.thread,
+            Event::ThreadStart(e) => e.thread,
+            Event::ThreadDeath(e) => e.thread,
+            Event::ClassPrepare(e) => e.thread,
+            Event::FieldAccess(e) => e.thread,
+            Event::FieldModification(e) => e.thread,
+            Event::ClassUnload(_) | Event::VmDeath(_) => return None,
+        })
+    }
+}
+
 #[jdwp_command((), 64, 100)]
 #[derive(Debug, JdwpWritable, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Composite {
     pub suspend_policy: SuspendPolicy,
     pub events: Vec<Event>,
 }
+
+impl Composite {
+    /// Returns what has to be resumed once these events are handled, without
+    /// resuming anything.
+    ///
+    /// With the [EventThread](SuspendPolicy::EventThread) policy all the
+    /// events of a composite happen in the same thread, so it's taken from
+    /// the first event that has one.
+    pub fn resume_plan(&self) -> ResumePlan {
+        match self.suspend_policy {
+            SuspendPolicy::None => ResumePlan::Nothing,
+            SuspendPolicy::EventThread => self
+                .events
+                .iter()
+                .find_map(Event::thread)
+                .map_or(ResumePlan::Nothing, ResumePlan::Thread),
+            SuspendPolicy::All => ResumePlan::AllThreads,
+        }
+    }
+}
+
+/// What has to be resumed after handling the events, see
+/// [Composite::resume_plan].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResumePlan {
+    /// Nothing was suspended.
+    Nothing,
+    /// Only the given thread was suspended.
+    Thread(ThreadID),
+    /// The whole VM was suspended, see
+    /// [virtual_machine::Resume](super::virtual_machine::Resume).
+    AllThreads,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ClassID, MethodID, ObjectID};
+
+    #[test]
+    fn resume_plan() {
+        // SAFETY: it's never sent anywhere
+        let thread = unsafe { ThreadID::new(ObjectID::new(42)) };
+        let plan = |suspend_policy| {
+            let composite = Composite {
+                suspend_policy,
+                events: vec![
+                    Event::VmDeath(VmDeath { request_id: 0 }),
+                    Event::ThreadStart(ThreadStart {
+                        request_id: 1,
+                        thread,
+                    }),
+                ],
+            };
+            composite.resume_plan()
+        };
+
+        assert_eq!(plan(SuspendPolicy::None), ResumePlan::Nothing);
+        assert_eq!(plan(SuspendPolicy::EventThread), ResumePlan::Thread(thread));
+        assert_eq!(plan(SuspendPolicy::All), ResumePlan::AllThreads);
+    }
+
+    #[test]
+    fn request_id() {
+        // SAFETY: none of these are ever sent anywhere
+        let (thread, location, request_id) = unsafe {
+            let location = Location {
+                reference_id: TaggedReferenceTypeID::Class(ClassID::new(ReferenceTypeID::new(1))),
+                method_id: MethodID::new(2),
+                index: 0,
+            };
+            (
+                ThreadID::new(ObjectID::new(42)),
+                location,
+                RequestID::new(7),
+            )
+        };
+
+        let breakpoint = Event::Breakpoint(Breakpoint {
+            request_id: 7,
+            thread,
+            location,
+        });
+        assert_eq!(breakpoint.request_id(), Some(request_id));
+
+        let vm_start = Event::VmStart(VmStart {
+            request_id: 0,
+            thread_id: thread,
+        });
+        assert_eq!(vm_start.request_id(), None);
+    }
+}
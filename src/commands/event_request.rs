@@ -18,11 +18,11 @@ use crate::{
 /// which are automatically generated events - see
 /// [Composite](super::event::Composite) command for further details.
 #[jdwp_command(RequestID, 15, 1)]
-#[derive(Debug, JdwpWritable)]
+#[derive(Debug, Clone, JdwpWritable)]
 pub struct Set {
     /// Event kind to request. Some events may require a capability in order to
     /// be requested.
-    event_kind: EventKind,
+    pub(crate) event_kind: EventKind,
     /// What threads are suspended when this event occurs?
     ///
     /// Note that the order of events and command replies accurately reflects
@@ -31,7 +31,7 @@ pub struct Set {
     /// For example, if a VM-wide resume is processed before an event occurs
     /// which suspends the VM, the reply to the resume command will be written
     /// to the transport before the suspending event.
-    suspend_policy: SuspendPolicy,
+    pub(crate) suspend_policy: SuspendPolicy,
     /// Constraints used to control the number of generated events.
     ///
     /// Modifiers specify additional tests that an event must satisfy before it
@@ -45,7 +45,7 @@ pub struct Set {
     ///
     /// Filtering can improve debugger performance dramatically by reducing the
     /// amount of event traffic sent from the target VM to the debugger VM.
-    modifiers: Vec<Modifier>,
+    pub(crate) modifiers: Vec<Modifier>,
 }
 
 /// Clear an event request.
@@ -64,9 +64,9 @@ pub struct Set {
 #[derive(Debug, JdwpWritable)]
 pub struct Clear {
     /// Event kind to clear
-    event_kind: EventKind,
+    pub(crate) event_kind: EventKind,
     /// ID of request to clear
-    request_id: RequestID,
+    pub(crate) request_id: RequestID,
 }
 
 /// Removes all set breakpoints, a no-op if there are no breakpoints set.
@@ -11,10 +11,14 @@ pub mod array_reference;
 pub mod array_type;
 pub mod class_loader_reference;
 pub mod class_object_reference;
+pub mod class_type;
 pub mod event;
 pub mod event_request;
+pub mod method;
+pub mod module_reference;
 pub mod object_reference;
 pub mod reference_type;
+pub mod stack_frame;
 pub mod string_reference;
 pub mod thread_group_reference;
 pub mod thread_reference;
@@ -0,0 +1,25 @@
+use super::jdwp_command;
+use crate::{
+    codec::JdwpWritable,
+    types::{ClassLoaderID, ModuleID},
+};
+
+/// Returns the name of this module.
+///
+/// Since JDWP version 9.
+#[jdwp_command(String, 18, 1)]
+#[derive(Debug, JdwpWritable)]
+pub struct Name {
+    /// This module
+    module: ModuleID,
+}
+
+/// Returns the class loader of this module.
+///
+/// Since JDWP version 9.
+#[jdwp_command(Option<ClassLoaderID>, 18, 2)]
+#[derive(Debug, JdwpWritable)]
+pub struct ClassLoader {
+    /// This module
+    module: ModuleID,
+}
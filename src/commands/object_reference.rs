@@ -1,7 +1,13 @@
-use super::jdwp_command;
+use std::io::{self, Read};
+
+use super::{class_type::FieldValue, jdwp_command};
 use crate::{
-    codec::JdwpWritable,
-    types::{ObjectID, TaggedReferenceTypeID},
+    codec::{JdwpReadable, JdwpReader, JdwpWritable},
+    enums::InvokeOptions,
+    types::{
+        ClassID, FieldID, Limit, MethodID, ObjectID, TaggedObjectID, TaggedReferenceTypeID,
+        ThreadID, Value,
+    },
 };
 
 #[jdwp_command(TaggedReferenceTypeID, 9, 1)]
@@ -10,3 +16,148 @@ pub struct ReferenceType {
     /// The object ID
     object: ObjectID,
 }
+
+/// Returns the value of one or more instance fields.
+///
+/// Each field must be member of the object's type or one of its superclasses,
+/// superinterfaces, or implemented interfaces.
+///
+/// Access control is not enforced; for example, the values of private fields
+/// can be obtained.
+#[jdwp_command(Vec<Value>, 9, 2)]
+#[derive(Debug, JdwpWritable)]
+pub struct GetValues {
+    /// The object ID
+    pub object: ObjectID,
+    /// Fields to get
+    pub fields: Vec<FieldID>,
+}
+
+/// Sets the value of one or more instance fields.
+///
+/// Each field must be member of the object's type or one of its superclasses,
+/// superinterfaces, or implemented interfaces.
+///
+/// Access control is not enforced; for example, the values of private fields
+/// can be set.
+///
+/// For primitive values, the value's type must match the field's type exactly.
+///
+/// For object values, there must be a widening reference conversion from the
+/// value's type to the field's type and the field's type must be loaded.
+#[jdwp_command((), 9, 3)]
+#[derive(Debug, JdwpWritable)]
+pub struct SetValues {
+    /// The object ID
+    pub object: ObjectID,
+    /// Fields to set and their values
+    pub values: Vec<FieldValue>,
+}
+
+/// Invokes a instance method.
+///
+/// The method must be member of the object's type or one of its superclasses,
+/// superinterfaces, or implemented interfaces. Access control is not enforced;
+/// for example, private methods can be invoked.
+///
+/// The method invocation will occur in the specified thread. Method invocation
+/// can occur only if the specified thread has been suspended by an event.
+/// Method invocation is not supported when the target VM has been suspended by
+/// the front-end.
+///
+/// The specified method is invoked with the arguments in the specified
+/// argument list. The method invocation is synchronous; the reply packet is not
+/// sent until the invoked method returns in the target VM. The return value
+/// (possibly the void value) is included in the reply packet.
+///
+/// For primitive arguments, the argument value's type must match the
+/// argument's type exactly. For object arguments, there must be a widening
+/// reference conversion from the argument value's type to the argument's type
+/// and the argument's type must be loaded.
+///
+/// By default, all threads in the target VM are resumed while the method is
+/// being invoked if they were previously suspended by an event or by a command.
+/// This is done to prevent the deadlocks that will occur if any of the threads
+/// own monitors that will be needed by the invoked method. It is possible that
+/// breakpoints or other events might occur during the invocation. Note,
+/// however, that this implicit resume acts exactly like the ThreadReference
+/// resume command, so if the thread's suspend count is greater than 1, it will
+/// remain in a suspended state during the invocation. By default, when the
+/// invocation completes, all threads in the target VM are suspended,
+/// regardless their state before the invocation.
+///
+/// The resumption of other threads during the invoke can be prevented by
+/// specifying the [SINGLE_THREADED](InvokeOptions::SINGLE_THREADED) option.
+/// This option must be used with caution, as it will leave the target VM
+/// deadlocked if the invoked method tries to acquire a monitor that is held by
+/// another thread.
+///
+/// If the target VM is disconnected during the invoke (for example, through
+/// the VirtualMachine dispose command) the method invocation continues.
+///
+/// By default, the specified method is invoked using dynamic dispatch, same as
+/// with `invokevirtual`, unless the [NONVIRTUAL](InvokeOptions::NONVIRTUAL)
+/// option is given, in which case the exact method given is invoked, same as
+/// with `invokespecial`.
+#[jdwp_command(InvokeMethodReply, 9, 6)]
+#[derive(Debug, JdwpWritable)]
+pub struct InvokeMethod {
+    /// The object ID
+    pub object: ObjectID,
+    /// The thread in which to invoke
+    pub thread: ThreadID,
+    /// The class type
+    pub class: ClassID,
+    /// The method to invoke
+    pub method: MethodID,
+    /// The argument values
+    pub arguments: Vec<Value>,
+    /// Invocation options
+    pub options: InvokeOptions,
+}
+
+/// The result of a method invocation, which either returned normally or threw.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InvokeMethodReply {
+    /// The returned value, [Void](Value::Void) for void methods
+    Value(Value),
+    /// The thrown exception
+    Exception(TaggedObjectID),
+}
+
+impl JdwpReadable for InvokeMethodReply {
+    fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
+        let value = Value::read(read)?;
+        // the value is still sent (zeroed out) when the method threw
+        Ok(match Option::<TaggedObjectID>::read(read)? {
+            Some(exception) => InvokeMethodReply::Exception(exception),
+            None => InvokeMethodReply::Value(value),
+        })
+    }
+}
+
+/// Determines whether an object has been garbage collected in the target VM.
+#[jdwp_command(bool, 9, 9)]
+#[derive(Debug, JdwpWritable)]
+pub struct IsCollected {
+    /// The object ID
+    pub object: ObjectID,
+}
+
+/// Returns objects that directly reference this object. Only objects that are
+/// reachable for the purposes of garbage collection are returned. Note that an
+/// object can also be referenced in other ways, such as from a local variable
+/// in a stack frame, or from a JNI global reference. Such non-object referrers
+/// are not returned by this command.
+///
+/// Since JDWP version 1.6. Requires canGetInstanceInfo capability - see
+/// [CapabilitiesNew](crate::commands::virtual_machine::CapabilitiesNew).
+#[jdwp_command(Vec<TaggedObjectID>, 9, 10)]
+#[derive(Debug, JdwpWritable)]
+pub struct ReferringObjects {
+    /// The object ID
+    object: ObjectID,
+    /// Maximum number of referring objects to return.
+    max_referrers: Limit,
+}
@@ -6,7 +6,7 @@ use crate::{
     enums::ClassStatus,
     jvm::{FieldModifiers, MethodModifiers, TypeModifiers},
     types::{
-        ClassLoaderID, ClassObjectID, FieldID, InterfaceID, MethodID, ReferenceTypeID,
+        ClassLoaderID, ClassObjectID, FieldID, InterfaceID, Limit, MethodID, ReferenceTypeID,
         TaggedObjectID, TaggedReferenceTypeID, Value,
     },
 };
@@ -65,6 +65,7 @@ pub struct Fields {
 }
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Field {
     /// Field ID
     pub field_id: FieldID,
@@ -100,6 +101,7 @@ pub struct Methods {
 }
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Method {
     /// Method ID
     pub method_id: MethodID,
@@ -218,6 +220,7 @@ pub struct SignatureWithGeneric {
 }
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SignatureWithGenericReply {
     /// The JNI signature for the reference type.
     pub signature: String,
@@ -248,6 +251,7 @@ pub struct FieldsWithGeneric {
 }
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FieldWithGeneric {
     /// The field ID
     pub field_id: FieldID,
@@ -287,6 +291,7 @@ pub struct MethodsWithGeneric {
 }
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MethodWithGeneric {
     /// The method ID
     pub method_id: MethodID,
@@ -317,14 +322,13 @@ pub struct Instances {
     /// The reference type ID
     ref_type: ReferenceTypeID,
     /// Maximum number of instances to return.
-    ///
-    /// Must be non-negative.
-    ///
-    /// If zero, all instances are returned.
-    max_instances: u32,
+    max_instances: Limit,
 }
 
-/// Returns the class object corresponding to this type.
+/// Returns the class file major and minor version numbers, as defined in the
+/// class file format of the Java Virtual Machine specification.
+///
+/// Since JDWP version 1.6.
 #[jdwp_command(2, 17)]
 #[derive(Debug, JdwpWritable)]
 pub struct ClassFileVersion {
@@ -333,6 +337,7 @@ pub struct ClassFileVersion {
 }
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassFileVersionReply {
     /// Major version number
     pub major_version: i32,
@@ -354,6 +359,7 @@ pub struct ConstantPool {
 }
 
 #[derive(JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConstantPoolReply {
     /// Total number of constant pool entries plus one.
     ///
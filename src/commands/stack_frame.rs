@@ -0,0 +1,99 @@
+use super::jdwp_command;
+use crate::{
+    codec::JdwpWritable,
+    enums::Tag,
+    types::{FrameID, TaggedObjectID, ThreadID, Value},
+};
+
+/// Returns the value of one or more local variables in a given frame.
+///
+/// Each variable must be visible at the frame's code index.
+///
+/// Even if local variable information is not available, values can be
+/// retrieved if the front-end is able to determine the correct local variable
+/// index. (Typically, this index can be determined for method arguments from
+/// the method signature without access to the local variable table
+/// information.)
+#[jdwp_command(Vec<Value>, 16, 1)]
+#[derive(Debug, JdwpWritable)]
+pub struct GetValues {
+    /// The frame's thread.
+    pub thread_id: ThreadID,
+    /// The frame ID.
+    pub frame_id: FrameID,
+    /// Local variable indices and types to get.
+    pub slots: Vec<Slot>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JdwpWritable)]
+pub struct Slot {
+    /// The local variable's index in the frame.
+    pub slot: u32,
+    /// A tag identifying the type of the variable
+    pub sigbyte: Tag,
+}
+
+/// Sets the value of one or more local variables.
+///
+/// Each variable must be visible at the current frame code index. For
+/// primitive values, the value's type must match the variable's type exactly.
+/// For object values, there must be a widening reference conversion from the
+/// value's type to the variable's type and the variable's type must be
+/// loaded.
+///
+/// Even if local variable information is not available, values can be set,
+/// if the front-end is able to determine the correct local variable index.
+/// (Typically, this index can be determined for method arguments from the
+/// method signature without access to the local variable table information.)
+#[jdwp_command((), 16, 2)]
+#[derive(Debug, JdwpWritable)]
+pub struct SetValues {
+    /// The frame's thread.
+    thread_id: ThreadID,
+    /// The frame ID.
+    frame_id: FrameID,
+    /// Local variables to set and their values.
+    slot_values: Vec<SlotValue>,
+}
+
+#[derive(Debug, Clone, PartialEq, JdwpWritable)]
+pub struct SlotValue {
+    /// The local variable's index in the frame.
+    pub slot: u32,
+    /// The value to set.
+    pub value: Value,
+}
+
+/// Returns the value of the 'this' reference for this frame.
+///
+/// If the frame's method is static or native, the reply will contain the null
+/// object reference.
+#[jdwp_command(Option<TaggedObjectID>, 16, 3)]
+#[derive(Debug, JdwpWritable)]
+pub struct ThisObject {
+    /// The frame's thread.
+    thread_id: ThreadID,
+    /// The frame ID.
+    frame_id: FrameID,
+}
+
+/// Pop the top-most stack frames of the thread stack, up to, and including
+/// 'frame'.
+///
+/// The thread must be suspended to perform this command. The top-most stack
+/// frames are discarded and the stack frame previous to 'frame' becomes the
+/// current frame. The operand stack is restored -- the argument values are
+/// added back and if the invoke was not invokestatic, objectref is added back
+/// as well. The Java virtual machine program counter is restored to the
+/// opcode of the invoke instruction.
+///
+/// Since JDWP version 1.4. Requires canPopFrames capability - see
+/// [CapabilitiesNew](super::virtual_machine::CapabilitiesNew).
+#[jdwp_command((), 16, 4)]
+#[derive(Debug, JdwpWritable)]
+pub struct PopFrames {
+    /// The thread object ID.
+    thread_id: ThreadID,
+    /// The frame ID.
+    frame_id: FrameID,
+}
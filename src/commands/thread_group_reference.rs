@@ -36,6 +36,7 @@ pub struct Children {
 }
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChildrenReply {
     /// Live direct child threads
     pub child_threads: Vec<ThreadID>,
@@ -1,6 +1,10 @@
 use jdwp_macros::jdwp_command;
 
-use crate::{codec::JdwpWritable, types::ThreadID};
+use crate::{
+    codec::{JdwpReadable, JdwpWritable},
+    enums::{SuspendStatus, ThreadStatus},
+    types::{FrameID, Location, ThreadID, Value},
+};
 
 /// Returns the thread name.
 #[jdwp_command(String, 11, 1)]
@@ -35,3 +39,134 @@ pub struct Suspend {
     /// The thread object ID.
     pub thread: ThreadID,
 }
+
+/// Resumes the execution of a given thread.
+///
+/// If this thread was not previously suspended by the front-end, calling this
+/// command has no effect. Otherwise, the count of pending suspends on this
+/// thread is decremented. If it is decremented to 0, the thread will continue
+/// to execute.
+#[jdwp_command((), 11, 3)]
+#[derive(Debug, JdwpWritable)]
+pub struct Resume {
+    /// The thread object ID.
+    pub thread: ThreadID,
+}
+
+/// Returns the current status of a thread.
+///
+/// The thread status reply indicates the thread status the last time it was
+/// running. The suspend status provides information on the thread's
+/// suspension, if any.
+#[jdwp_command(11, 4)]
+#[derive(Debug, JdwpWritable)]
+pub struct Status {
+    /// The thread object ID.
+    pub thread: ThreadID,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatusReply {
+    /// One of the thread status codes.
+    pub thread_status: ThreadStatus,
+    /// One of the suspend status codes.
+    pub suspend_status: SuspendStatus,
+}
+
+/// Returns the current call stack of a suspended thread.
+///
+/// The sequence of frames starts with the currently executing frame, followed
+/// by its caller, and so on.
+///
+/// The thread must be suspended, and the returned frameID is valid only while
+/// the thread is suspended.
+#[jdwp_command(Vec<Frame>, 11, 6)]
+#[derive(Debug, JdwpWritable)]
+pub struct Frames {
+    /// The thread object ID.
+    pub thread: ThreadID,
+    /// The index of the first frame to retrieve.
+    pub start_frame: i32,
+    /// The count of frames to retrieve (-1 means all remaining).
+    pub length: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frame {
+    /// The ID of this frame.
+    pub frame_id: FrameID,
+    /// The current location of this frame
+    pub location: Location,
+}
+
+/// Returns the count of frames on this thread's stack.
+///
+/// The thread must be suspended, and the returned count is valid only while
+/// the thread is suspended.
+///
+/// Returns [ThreadNotSuspended](crate::enums::ErrorCode::ThreadNotSuspended)
+/// if not suspended.
+#[jdwp_command(i32, 11, 7)]
+#[derive(Debug, JdwpWritable)]
+pub struct FrameCount {
+    /// The thread object ID.
+    pub thread: ThreadID,
+}
+
+/// Get the suspend count for this thread.
+///
+/// The suspend count is the number of times the thread has been suspended
+/// through the thread-level or VM-level suspend commands without a
+/// corresponding resume
+#[jdwp_command(i32, 11, 12)]
+#[derive(Debug, JdwpWritable)]
+pub struct SuspendCount {
+    /// The thread object ID.
+    pub thread: ThreadID,
+}
+
+/// Force a method to return before it reaches a return statement.
+///
+/// The method which will return early is referred to as the called method.
+/// The called method is the current method (as defined by the Frames section
+/// in The Java Virtual Machine Specification) for the specified thread at the
+/// time this command is received.
+///
+/// The specified thread must be suspended. The return occurs when execution
+/// of Java programming language code is resumed on this thread. Between
+/// sending this command and resumption of thread execution, the state of the
+/// stack is undefined.
+///
+/// No further instructions are executed in the called method. Specifically,
+/// finally blocks are not executed. Note: this can cause inconsistent states
+/// in the application.
+///
+/// A lock acquired by calling the called method (if it is a synchronized
+/// method) and locks acquired by entering synchronized blocks within the
+/// called method are released. Note: this does not apply to JNI locks or
+/// java.util.concurrent.locks locks.
+///
+/// Events, such as MethodExit, are generated as they would be in a normal
+/// return.
+///
+/// The called method must be a non-native Java programming language method.
+/// Forcing return on a thread with only one frame on the stack causes the
+/// thread to exit when resumed.
+///
+/// For void methods, the value must be a void value. For methods that return
+/// primitive values, the value's type must match the return type exactly. For
+/// object values, there must be a widening reference conversion from the
+/// value's type to the return type type and the return type must be loaded.
+///
+/// Since JDWP version 1.6. Requires canForceEarlyReturn capability - see
+/// [CapabilitiesNew](super::virtual_machine::CapabilitiesNew).
+#[jdwp_command((), 11, 14)]
+#[derive(Debug, JdwpWritable)]
+pub struct ForceEarlyReturn {
+    /// The thread object ID.
+    pub thread: ThreadID,
+    /// The value to return.
+    pub value: Value,
+}
@@ -1,9 +1,16 @@
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    io::{self, Write},
+};
 
 use crate::{
-    codec::{JdwpReadable, JdwpWritable},
+    codec::{DataParts, JdwpReadable, JdwpWritable, JdwpWriter},
     enums::ClassStatus,
-    types::{ObjectID, ReferenceTypeID, StringID, TaggedReferenceTypeID, ThreadGroupID, ThreadID},
+    jvm::FieldType,
+    types::{
+        ModuleID, ObjectID, ReferenceTypeID, StringID, TaggedReferenceTypeID, ThreadGroupID,
+        ThreadID,
+    },
 };
 
 use super::jdwp_command;
@@ -16,6 +23,7 @@ use super::jdwp_command;
 pub struct Version;
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VersionReply {
     /// Text information on the VM version
     pub description: String,
@@ -45,6 +53,7 @@ pub struct ClassesBySignature {
 }
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnnamedClass {
     /// Matching loaded reference type
     pub type_id: TaggedReferenceTypeID,
@@ -58,6 +67,7 @@ pub struct UnnamedClass {
 pub struct AllClasses;
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Class {
     /// Matching loaded reference type
     pub type_id: TaggedReferenceTypeID,
@@ -115,7 +125,8 @@ pub struct Dispose;
 #[derive(Debug, JdwpWritable)]
 pub struct IDSizes;
 
-#[derive(Debug, Clone, JdwpReadable)]
+#[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IDSizeInfo {
     /// field_id size in bytes
     pub field_id_size: i32,
@@ -187,6 +198,7 @@ pub struct CreateString {
 pub struct Capabilities;
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CapabilitiesReply {
     /// Can the VM watch field modification, and therefore can it send the
     /// Modification Watchpoint Event?
@@ -218,6 +230,7 @@ pub struct CapabilitiesReply {
 pub struct ClassPaths;
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassPathsReply {
     /// Base directory used to resolve relative paths in either of the following
     /// lists.
@@ -230,7 +243,7 @@ pub struct ClassPathsReply {
 
 #[derive(Debug, JdwpWritable)]
 pub struct ObjectRef {
-    object: ObjectID,
+    pub(crate) object: ObjectID,
     ref_cnt: i32,
 }
 
@@ -273,7 +286,7 @@ impl ObjectRef {
 #[jdwp_command((), 1, 14)]
 #[derive(Debug, JdwpWritable)]
 pub struct DisposeObjects {
-    requests: Vec<ObjectRef>,
+    pub(crate) requests: Vec<ObjectRef>,
 }
 
 /// Tells the target VM to stop sending events. Events are not discarded; they
@@ -316,6 +329,7 @@ pub struct ReleaseEvents;
 pub struct CapabilitiesNew;
 
 #[derive(JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CapabilitiesNewReply {
     /// The prefix of [CapabilitiesNew] is identical to that of old
     /// [Capabilities]
@@ -411,10 +425,10 @@ impl Debug for CapabilitiesNewReply {
     }
 }
 
-#[derive(Debug, JdwpWritable)]
+#[derive(Debug)]
 pub struct RedefiningClass {
     /// The reference type.
-    ref_type: ReferenceTypeID,
+    pub(crate) ref_type: ReferenceTypeID,
     /// Bytes defining class in JVM class file format.
     bytes: Vec<u8>,
 }
@@ -425,6 +439,21 @@ impl RedefiningClass {
     }
 }
 
+// the class files can be huge, so they are borrowed when sending
+impl JdwpWritable for RedefiningClass {
+    fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
+        self.ref_type.write(write)?;
+        self.bytes.write(write)
+    }
+
+    fn write_parts<'a>(&'a self, parts: &mut DataParts<'a>) -> io::Result<()> {
+        self.ref_type.write(parts.writer())?;
+        (self.bytes.len() as u32).write(parts.writer())?;
+        parts.borrow(&self.bytes);
+        Ok(())
+    }
+}
+
 /// Installs new class definitions.
 ///
 /// If there are active stack frames in methods of the redefined classes in the
@@ -448,7 +477,7 @@ impl RedefiningClass {
 #[jdwp_command((), 1, 18)]
 #[derive(Debug, JdwpWritable)]
 pub struct RedefineClasses {
-    classes: Vec<RedefiningClass>,
+    pub(crate) classes: Vec<RedefiningClass>,
 }
 
 /// Set the default stratum. Requires `can_set_default_stratum` capability -
@@ -474,6 +503,7 @@ pub struct SetDefaultStratum {
 pub struct AllClassesWithGeneric;
 
 #[derive(Debug, JdwpReadable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GenericClass {
     /// Loaded reference type
     pub type_id: TaggedReferenceTypeID,
@@ -486,6 +516,17 @@ pub struct GenericClass {
     pub status: ClassStatus,
 }
 
+impl GenericClass {
+    /// The name of the type as written in the Java source code, e.g.
+    /// `java.util.List` for `Ljava/util/List;`, see [FieldType::java_name].
+    ///
+    /// `None` if the signature is malformed.
+    pub fn class_name(&self) -> Option<String> {
+        let field_type = self.signature.parse::<FieldType>().ok()?;
+        Some(field_type.java_name())
+    }
+}
+
 /// Returns the number of instances of each reference type in the input list.
 ///
 /// Only instances that are reachable for the purposes of garbage collection
@@ -502,3 +543,10 @@ pub struct InstanceCounts {
     /// A list of reference type IDs.
     ref_types: Vec<ReferenceTypeID>,
 }
+
+/// Returns all modules in the target VM.
+///
+/// Since JDWP version 9.
+#[jdwp_command(Vec<ModuleID>, 1, 22)]
+#[derive(Debug, JdwpWritable)]
+pub struct AllModules;
@@ -5,16 +5,17 @@ use std::{
 
 use bitflags::bitflags;
 
-use crate::codec::{JdwpReadable, JdwpReader, JdwpWritable, JdwpWriter};
+use crate::codec::{ByteTag, JdwpReadable, JdwpReader, JdwpWritable, JdwpWriter};
 
 macro_rules! readable_enum {
-    ($e:ident: $repr:ident, $($name:ident = $id:literal | $string:literal),* $(,)?) => {
+    ($e:ident: $repr:ident, $($name:ident = $id:literal $(| $string:literal)?),* $(,)?) => {
         #[repr($repr)]
         #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
         #[non_exhaustive]
         pub enum $e {
             $(
-                #[doc = $string]
+                $(#[doc = $string])?
                 $name = $id,
             )*
         }
@@ -43,9 +44,6 @@ macro_rules! readable_enum {
             }
         }
     };
-    ($e:ident: $repr:ident, $($name:ident = $id:literal),* $(,)?) => {
-        readable_enum!($e: $repr, $($name = $id | "",)*);
-    };
     ($e:ident: $repr:ident | Display, $($name:ident = $id:literal | $string:literal),* $(,)?) => {
         readable_enum!($e: $repr, $($name = $id | $string,)*);
 
@@ -159,18 +157,35 @@ readable_enum! {
     Wait = 4,
 }
 
-readable_enum! {
-    SuspendStatus: u32,
+bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    pub struct SuspendStatus: u32 {
+        const SUSPENDED = 1;
+    }
+}
 
-    Suspended = 1,
+impl JdwpReadable for SuspendStatus {
+    fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
+        Ok(Self::from_bits_retain(u32::read(read)?))
+    }
+}
+
+impl JdwpWritable for SuspendStatus {
+    fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
+        self.bits().write(write)
+    }
 }
 
 bitflags! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct ClassStatus: u32 {
         const VERIFIED = 1;
         const PREPARED = 2;
         const INITIALIZED = 4;
+        /// The static initialization of the type has failed, so any attempt
+        /// to use it throws a `NoClassDefFoundError`.
         const ERROR = 8;
 
         const OK = Self::VERIFIED.bits() | Self::PREPARED.bits() | Self::INITIALIZED.bits();
@@ -179,7 +194,8 @@ bitflags! {
 
 impl JdwpReadable for ClassStatus {
     fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
-        Self::from_bits(u32::read(read)?).ok_or_else(|| Error::from(ErrorKind::InvalidData))
+        // the VMs might set the reserved bits, they are just kept as is
+        Ok(Self::from_bits_retain(u32::read(read)?))
     }
 }
 
@@ -218,6 +234,51 @@ readable_enum! {
     ClassObject = 99 | "'c' - a class object object ([ObjectID](crate::types::ObjectID) size).",
 }
 
+impl TypeTag {
+    /// The character that the JNI signatures of the types of this kind start
+    /// with: `L` for the classes and interfaces alike, and `[` for the
+    /// arrays.
+    ///
+    /// There is no reverse of this, as an `L` could be either of the two.
+    pub fn signature_char(self) -> u8 {
+        match self {
+            TypeTag::Class | TypeTag::Interface => b'L',
+            TypeTag::Array => b'[',
+        }
+    }
+}
+
+impl Tag {
+    /// The signature character of this tag, which is also its JDWP value,
+    /// e.g. `I` for [Int](Tag::Int), `L` for [Object](Tag::Object) and `[`
+    /// for [Array](Tag::Array).
+    ///
+    /// The specific object tags such as [String](Tag::String) or
+    /// [Thread](Tag::Thread) have their own lowercase letters, which never
+    /// occur in the JNI signatures.
+    pub fn signature_char(self) -> u8 {
+        self as u8
+    }
+
+    /// The tag with the given signature character, see
+    /// [signature_char](Tag::signature_char).
+    pub fn from_signature_char(c: u8) -> Option<Self> {
+        Self::from(c)
+    }
+}
+
+impl ByteTag for TypeTag {
+    fn byte_tag(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl ByteTag for Tag {
+    fn byte_tag(&self) -> u8 {
+        *self as u8
+    }
+}
+
 readable_enum! {
     StepDepth: u32,
 
@@ -242,11 +303,17 @@ readable_enum! {
 }
 
 bitflags! {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct InvokeOptions: u32 {
-        /// otherwise, normal virtual invoke (instance methods only)
-        const VERIFIED = 1;
-        /// otherwise, all threads started
-        const PREPARED = 2;
+        /// Invoke the method without resuming the other threads suspended
+        /// in the target VM, otherwise all of them are resumed for the
+        /// duration of the invocation
+        const SINGLE_THREADED = 0x01;
+        /// Invoke the exact method given, the same as `invokespecial` does,
+        /// otherwise the method is looked up virtually (instance methods
+        /// only)
+        const NONVIRTUAL = 0x02;
     }
 }
 
@@ -276,5 +343,65 @@ readable_enum! {
     FieldOnly = 9 | "Restricts reported events to those that occur for a given field. This modifier can be used with field access and field modification event kinds only.",
     Step = 10 | "Restricts reported step events to those which satisfy depth and size constraints. This modifier can be used with step event kinds only.",
     InstanceOnly = 11 | "Restricts reported events to those whose active 'this' object is the given object. Match value is the null object for static methods. This modifier can be used with any event kind except class prepare, class unload, thread start, and thread end. Introduced in JDWP version 1.4.",
-    SourceNameMatch = 12 | "",
+    SourceNameMatch = 12 | "Restricts reported class prepare events to those for reference types which have a source name which matches the given restricted regular expression. The source names are determined by the reference type's SourceDebugExtension. This modifier can only be used with class prepare events. Since JDWP version 1.6.",
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::virtual_machine::IDSizeInfo;
+
+    #[test]
+    fn class_status_reserved_bits() -> io::Result<()> {
+        let id_sizes = IDSizeInfo {
+            field_id_size: 8,
+            method_id_size: 8,
+            object_id_size: 8,
+            reference_type_id_size: 8,
+            frame_id_size: 8,
+        };
+        let bytes: &[u8] = &[0, 0, 0x10, 0x07];
+        let status = ClassStatus::read(&mut JdwpReader::new(bytes, id_sizes))?;
+
+        assert!(status.contains(ClassStatus::OK));
+        assert_eq!(status.bits(), 0x1007);
+        Ok(())
+    }
+
+    #[test]
+    fn tag_signature_chars() {
+        let tags = [
+            (Tag::Array, b'['),
+            (Tag::Byte, b'B'),
+            (Tag::Char, b'C'),
+            (Tag::Object, b'L'),
+            (Tag::Float, b'F'),
+            (Tag::Double, b'D'),
+            (Tag::Int, b'I'),
+            (Tag::Long, b'J'),
+            (Tag::Short, b'S'),
+            (Tag::Void, b'V'),
+            (Tag::Boolean, b'Z'),
+            (Tag::String, b's'),
+            (Tag::Thread, b't'),
+            (Tag::ThreadGroup, b'g'),
+            (Tag::ClassLoader, b'l'),
+            (Tag::ClassObject, b'c'),
+        ];
+        for (tag, c) in tags {
+            assert_eq!(tag.signature_char(), c, "{tag:?}");
+            assert_eq!(Tag::from_signature_char(c), Some(tag));
+        }
+        assert_eq!(Tag::from_signature_char(b'X'), None);
+
+        assert_eq!(TypeTag::Class.signature_char(), b'L');
+        assert_eq!(TypeTag::Interface.signature_char(), b'L');
+        assert_eq!(TypeTag::Array.signature_char(), b'[');
+    }
+
+    #[test]
+    fn invoke_options_bits() {
+        assert_eq!(InvokeOptions::SINGLE_THREADED.bits(), 0x01);
+        assert_eq!(InvokeOptions::NONVIRTUAL.bits(), 0x02);
+    }
 }
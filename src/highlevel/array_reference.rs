@@ -0,0 +1,137 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, ErrorKind},
+    ops::Range,
+};
+
+use crate::{
+    client::ClientError,
+    commands::array_reference::{GetValues, Length, SetValues},
+    jvm::FieldType,
+    types::{ArrayID, ArrayRegion, Untagged, Value},
+};
+
+use super::{ObjectReference, SharedClient};
+
+/// An array in the target VM.
+///
+/// The indexes are checked against the [length](ArrayReference::length)
+/// before sending anything, failing with
+/// [IllegalArgument](ClientError::IllegalArgument) if they are out of bounds.
+#[derive(Clone)]
+pub struct ArrayReference {
+    client: SharedClient,
+    id: ArrayID,
+}
+
+impl ArrayReference {
+    pub fn new(client: SharedClient, id: ArrayID) -> Self {
+        Self { client, id }
+    }
+
+    pub fn id(&self) -> ArrayID {
+        self.id
+    }
+
+    pub fn client(&self) -> &SharedClient {
+        &self.client
+    }
+
+    /// Returns the number of components of this array, see [Length].
+    pub fn length(&self) -> Result<u32, ClientError> {
+        Ok(self.client.send(Length::new(self.id))? as u32)
+    }
+
+    /// Returns the components in the given range, see [GetValues].
+    pub fn get_region(&self, range: Range<u32>) -> Result<ArrayRegion, ClientError> {
+        let length = self.length()?;
+        if range.start > range.end || range.end > length {
+            return Err(ClientError::IllegalArgument(format!(
+                "{range:?} is out of bounds of an array of length {length}"
+            )));
+        }
+        if range.is_empty() {
+            return self.empty_region();
+        }
+        self.client.send(GetValues::new(
+            self.id,
+            range.start as i32,
+            range.len() as i32,
+        ))
+    }
+
+    /// Returns all the components of this array, see [GetValues].
+    pub fn get_all(&self) -> Result<ArrayRegion, ClientError> {
+        let length = self.length()?;
+        if length == 0 {
+            return self.empty_region();
+        }
+        self.client.send(GetValues::new(self.id, 0, length as i32))
+    }
+
+    // the target VM rejects any index into an empty array, even with a zero
+    // length, so the empty region of the component type is made up here
+    fn empty_region(&self) -> Result<ArrayRegion, ClientError> {
+        let signature = ObjectReference::new(self.client.clone(), *self.id)
+            .reference_type()?
+            .signature()?;
+        let component = match signature.parse() {
+            Ok(FieldType::Array(component)) => component,
+            _ => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Bad array signature: {signature}"),
+                )
+                .into())
+            }
+        };
+        Ok(match *component {
+            FieldType::Byte => ArrayRegion::Byte(Vec::new()),
+            FieldType::Boolean => ArrayRegion::Boolean(Vec::new()),
+            FieldType::Char => ArrayRegion::Char(Vec::new()),
+            FieldType::Short => ArrayRegion::Short(Vec::new()),
+            FieldType::Int => ArrayRegion::Int(Vec::new()),
+            FieldType::Long => ArrayRegion::Long(Vec::new()),
+            FieldType::Float => ArrayRegion::Float(Vec::new()),
+            FieldType::Double => ArrayRegion::Double(Vec::new()),
+            FieldType::Object(_) | FieldType::Array(_) => ArrayRegion::Object(Vec::new()),
+        })
+    }
+
+    /// Sets the components starting from the given index, see [SetValues].
+    ///
+    /// The primitive values must be exactly of the component type, which is
+    /// checked by the target VM.
+    pub fn set_values(&self, first_index: u32, values: &[Value]) -> Result<(), ClientError> {
+        if values.contains(&Value::Void) {
+            return Err(ClientError::IllegalArgument(
+                "an array cannot hold a void value".to_owned(),
+            ));
+        }
+        let length = self.length()?;
+        let end = first_index as u64 + values.len() as u64;
+        if end > length as u64 {
+            return Err(ClientError::IllegalArgument(format!(
+                "{}..{end} is out of bounds of an array of length {length}",
+                first_index
+            )));
+        }
+        let values = values.iter().copied().map(Untagged::new).collect();
+        self.client
+            .send(SetValues::new(self.id, first_index as i32, values))
+    }
+}
+
+impl Debug for ArrayReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ArrayReference").field(&self.id).finish()
+    }
+}
+
+impl PartialEq for ArrayReference {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for ArrayReference {}
@@ -0,0 +1,122 @@
+use crate::{
+    client::ClientError,
+    commands::virtual_machine::{self, CapabilitiesNew, CapabilitiesNewReply, CapabilitiesReply},
+    enums::ErrorCode,
+};
+
+use super::SharedClient;
+
+macro_rules! capabilities {
+    (
+        old { $($(#[$old_doc:meta])* $old:ident),* $(,)? }
+        new { $($(#[$new_doc:meta])* $new:ident),* $(,)? }
+    ) => {
+        /// All the capabilities of the target VM, see
+        /// [VM::capabilities](super::VM::capabilities).
+        ///
+        /// The commands associated with each capability fail with the
+        /// [NotImplemented](ErrorCode::NotImplemented) host error if the
+        /// capability is not available.
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        pub struct Capabilities {
+            $($old: bool,)*
+            $($new: bool,)*
+        }
+
+        impl Capabilities {
+            $(
+                $(#[$old_doc])*
+                pub fn $old(&self) -> bool {
+                    self.$old
+                }
+            )*
+            $(
+                $(#[$new_doc])*
+                pub fn $new(&self) -> bool {
+                    self.$new
+                }
+            )*
+
+            fn from_new(reply: CapabilitiesNewReply) -> Self {
+                Self {
+                    $($old: reply.capabilities.$old,)*
+                    $($new: reply.$new,)*
+                }
+            }
+
+            fn from_old(reply: CapabilitiesReply) -> Self {
+                Self {
+                    $($old: reply.$old,)*
+                    $($new: false,)*
+                }
+            }
+        }
+    };
+}
+
+capabilities! {
+    old {
+        /// Whether the field modification watchpoints can be set.
+        can_watch_field_modification,
+        /// Whether the field access watchpoints can be set.
+        can_watch_field_access,
+        /// Whether the bytecodes of the methods can be fetched.
+        can_get_bytecodes,
+        /// Whether it can be told that a field or a method is synthetic.
+        can_get_synthetic_attribute,
+        /// Whether the monitors owned by a thread can be fetched.
+        can_get_owned_monitor_info,
+        /// Whether the monitor a thread is waiting for can be fetched.
+        can_get_current_contended_monitor,
+        /// Whether the monitor information of an object can be fetched.
+        can_get_monitor_info,
+    }
+    new {
+        /// Whether classes can be redefined.
+        can_redefine_classes,
+        /// Whether methods can be added when redefining classes.
+        can_add_method,
+        /// Whether classes can be redefined in arbitrary ways.
+        can_unrestrictedly_redefine_classes,
+        /// Whether stack frames can be popped.
+        can_pop_frames,
+        /// Whether the events can be filtered by the object.
+        can_use_instance_filters,
+        /// Whether the source debug extension can be fetched.
+        can_get_source_debug_extension,
+        /// Whether the VM death events can be requested.
+        can_request_vmdeath_event,
+        /// Whether the default stratum can be set.
+        can_set_default_stratum,
+        /// Whether the instances, the instance counts and the referring
+        /// objects can be fetched.
+        can_get_instance_info,
+        /// Whether the monitor events can be requested.
+        can_request_monitor_events,
+        /// Whether the owned monitors can be fetched with their frame depths.
+        can_get_monitor_frame_info,
+        /// Whether the class prepare events can be filtered by the source
+        /// name.
+        can_use_source_name_filters,
+        /// Whether the constant pools can be fetched.
+        can_get_constant_pool,
+        /// Whether methods can be forced to return early.
+        can_force_early_return,
+    }
+}
+
+impl Capabilities {
+    /// Asks the target VM for its capabilities with [CapabilitiesNew],
+    /// falling back to the older [Capabilities](virtual_machine::Capabilities)
+    /// for the target VMs that predate it, in which case all the newer
+    /// capabilities are missing.
+    pub(crate) fn fetch(client: &SharedClient) -> Result<Self, ClientError> {
+        match client.send(CapabilitiesNew) {
+            Ok(reply) => Ok(Self::from_new(reply)),
+            Err(ClientError::HostError(ErrorCode::NotImplemented)) => {
+                Ok(Self::from_old(client.send(virtual_machine::Capabilities)?))
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
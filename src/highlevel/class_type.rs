@@ -0,0 +1,160 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    ops::Deref,
+};
+
+use crate::{
+    client::ClientError,
+    commands::{
+        class_type::{
+            FieldValue, InvokeMethod, NewInstance, NewInstanceReply, SetValues, Superclass,
+        },
+        object_reference::InvokeMethodReply,
+    },
+    enums::InvokeOptions,
+    jvm::{FieldModifiers, MethodModifiers},
+    types::{ClassID, FieldID, TaggedReferenceTypeID, Value},
+};
+
+use super::{Method, ReferenceType, SharedClient, ThreadReference};
+
+/// A class type in the target VM.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ClassType {
+    reference_type: ReferenceType,
+    id: ClassID,
+}
+
+impl ClassType {
+    pub fn new(client: SharedClient, id: ClassID) -> Self {
+        Self {
+            reference_type: ReferenceType::new(client, TaggedReferenceTypeID::Class(id)),
+            id,
+        }
+    }
+
+    pub fn id(&self) -> ClassID {
+        self.id
+    }
+
+    /// Returns the immediate superclass, or `None` if this is
+    /// `java.lang.Object`, see [Superclass].
+    pub fn superclass(&self) -> Result<Option<ClassType>, ClientError> {
+        let superclass = self.client().send(Superclass::new(self.id))?;
+        Ok(superclass.map(|id| ClassType::new(self.client().clone(), id)))
+    }
+
+    /// Sets the value of the static field with the given name declared
+    /// directly in this class, see [SetValues].
+    ///
+    /// Final fields cannot be set, so those are rejected with
+    /// [IllegalArgument](ClientError::IllegalArgument) instead of the
+    /// host error.
+    pub fn set_static(&self, name: &str, value: Value) -> Result<(), ClientError> {
+        let Some(field) = self.field_by_name(name)? else {
+            return Err(ClientError::IllegalArgument(format!(
+                "there is no field {name} in the class"
+            )));
+        };
+        if !field.is_static() {
+            return Err(ClientError::IllegalArgument(format!(
+                "the field {name} is not static"
+            )));
+        }
+        if field.modifiers().contains(FieldModifiers::FINAL) {
+            return Err(ClientError::IllegalArgument(format!(
+                "the field {name} is final"
+            )));
+        }
+        let value = FieldValue {
+            field_id: field.id(),
+            value: value.into(),
+        };
+        self.client().send(SetValues::new(self.id, vec![value]))
+    }
+
+    /// Sets the values of the given static fields at once, see [SetValues].
+    ///
+    /// Unlike with [set_static](Self::set_static), the fields are not checked
+    /// beforehand, so setting a final field fails with the host error.
+    pub fn set_static_values(&self, values: &[(FieldID, Value)]) -> Result<(), ClientError> {
+        let values = values
+            .iter()
+            .map(|&(field_id, value)| FieldValue {
+                field_id,
+                value: value.into(),
+            })
+            .collect();
+        self.client().send(SetValues::new(self.id, values))
+    }
+
+    /// Invokes the given static method of this class or of one of its
+    /// superclasses, see [InvokeMethod].
+    ///
+    /// The thread must be suspended by an event.
+    /// The arguments are [checked](Method::check_arguments) before sending.
+    pub fn invoke_static(
+        &self,
+        thread: &ThreadReference,
+        method: &Method,
+        args: &[Value],
+        options: InvokeOptions,
+    ) -> Result<InvokeMethodReply, ClientError> {
+        if !method.modifiers().contains(MethodModifiers::STATIC) {
+            return Err(ClientError::IllegalArgument(format!(
+                "{} is not static",
+                method.name()
+            )));
+        }
+        method.check_arguments(args)?;
+        self.client().send(InvokeMethod::new(
+            self.id,
+            thread.id(),
+            method.id(),
+            args.to_vec(),
+            options,
+        ))
+    }
+
+    /// Creates a new instance of this class with the given constructor, see
+    /// [NewInstance].
+    ///
+    /// The thread must be suspended by an event.
+    /// The arguments are [checked](Method::check_arguments) before sending.
+    pub fn new_instance(
+        &self,
+        thread: &ThreadReference,
+        constructor: &Method,
+        args: &[Value],
+        options: InvokeOptions,
+    ) -> Result<NewInstanceReply, ClientError> {
+        if constructor.name() != "<init>" {
+            return Err(ClientError::IllegalArgument(format!(
+                "{} is not a constructor",
+                constructor.name()
+            )));
+        }
+        constructor.check_arguments(args)?;
+        self.client().send(NewInstance::new(
+            self.id,
+            thread.id(),
+            constructor.id(),
+            args.to_vec(),
+            options,
+        ))
+    }
+}
+
+impl Deref for ClassType {
+    type Target = ReferenceType;
+
+    fn deref(&self) -> &Self::Target {
+        &self.reference_type
+    }
+}
+
+impl Debug for ClassType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ClassType").field(&self.id).finish()
+    }
+}
@@ -0,0 +1,71 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    ops::Deref,
+};
+
+use crate::{client::ClientError, commands::event::Exception, types::Location};
+
+use super::{SharedClient, SourcePosition, ThreadReference};
+
+/// An [Exception] event with some high-level helpers.
+pub struct ExceptionEvent {
+    client: SharedClient,
+    event: Exception,
+}
+
+impl ExceptionEvent {
+    pub fn new(client: SharedClient, event: Exception) -> Self {
+        Self { client, event }
+    }
+
+    /// The thread in which the exception was thrown.
+    pub fn thread(&self) -> ThreadReference {
+        ThreadReference::new(self.client.clone(), self.event.thread)
+    }
+
+    /// Whether the exception will be caught, i.e. if there is a catch
+    /// location.
+    ///
+    /// Note that this is not entirely reliable when there are native frames
+    /// on the stack: a native method might handle the exception even though
+    /// it's considered uncaught here, and a native frame between the throw
+    /// and the catch location might clear it before the catch location is
+    /// reached, see [Exception::catch_location].
+    ///
+    /// Also, compilers generate implicit try-catch blocks e.g. for
+    /// `synchronized` and `finally`, so an exception might be considered
+    /// caught even though it looks uncaught in the source code.
+    pub fn is_caught(&self) -> bool {
+        self.event.catch_location.is_some()
+    }
+
+    /// The location of the first instruction of the catch clause that will
+    /// handle the exception, with the same caveats as [is_caught].
+    ///
+    /// [is_caught]: ExceptionEvent::is_caught
+    pub fn catch_location(&self) -> Option<&Location> {
+        self.event.catch_location.as_ref()
+    }
+
+    /// Resolves the [catch location](ExceptionEvent::catch_location) to the
+    /// position in the source code, `None` if the exception is uncaught.
+    pub fn catch_position(&self) -> Result<Option<SourcePosition>, ClientError> {
+        self.catch_location()
+            .map(|location| SourcePosition::resolve(&self.client, location))
+            .transpose()
+    }
+}
+
+impl Deref for ExceptionEvent {
+    type Target = Exception;
+
+    fn deref(&self) -> &Self::Target {
+        &self.event
+    }
+}
+
+impl Debug for ExceptionEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.event.fmt(f)
+    }
+}
@@ -0,0 +1,224 @@
+use std::sync::mpsc::Sender;
+
+use crate::{
+    client::ClientError,
+    commands::{event::Event, event_request::Set},
+    enums::{EventKind, ModifierKind, StepDepth, StepSize, SuspendPolicy},
+    types::{
+        ClassExclude, ClassMatch, ClassOnly, Count, ExceptionOnly, FieldOnly, InstanceOnly,
+        Location, LocationOnly, Modifier, RequestID, SourceNameMatch, Step, ThreadOnly,
+    },
+};
+
+use super::{Field, ObjectReference, ReferenceType, SharedClient, ThreadReference};
+
+/// An event request that is currently set in the target VM, see
+/// [VM::active_requests](super::VM::active_requests).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveRequest {
+    pub event_kind: EventKind,
+    pub request_id: RequestID,
+    pub suspend_policy: SuspendPolicy,
+    /// The modifiers the request was set with, e.g. the breakpoint location
+    /// or the class pattern
+    pub modifiers: Vec<Modifier>,
+}
+
+impl ActiveRequest {
+    pub(crate) fn new(request_id: RequestID, request: Set) -> Self {
+        Self {
+            event_kind: request.event_kind,
+            request_id,
+            suspend_policy: request.suspend_policy,
+            modifiers: request.modifiers,
+        }
+    }
+}
+
+/// A builder for an event request, created by
+/// [VM::event_request](super::VM::event_request).
+///
+/// The suspend policy defaults to [All](SuspendPolicy::All), same as in JDI.
+///
+/// The modifiers are checked against the event kind before sending, as
+/// described in their docs, failing with
+/// [ModifierNotApplicable](ClientError::ModifierNotApplicable) for the first
+/// one that the target VM would reject.
+#[derive(Debug)]
+pub struct EventRequestBuilder {
+    client: SharedClient,
+    event_kind: EventKind,
+    suspend_policy: SuspendPolicy,
+    modifiers: Vec<Modifier>,
+}
+
+impl EventRequestBuilder {
+    pub(crate) fn new(client: SharedClient, event_kind: EventKind) -> Self {
+        Self {
+            client,
+            event_kind,
+            suspend_policy: SuspendPolicy::All,
+            modifiers: Vec::new(),
+        }
+    }
+
+    pub fn suspend_policy(mut self, suspend_policy: SuspendPolicy) -> Self {
+        self.suspend_policy = suspend_policy;
+        self
+    }
+
+    /// Adds a modifier, they are applied in the order they were added.
+    pub fn modifier(mut self, modifier: Modifier) -> Self {
+        self.modifiers.push(modifier);
+        self
+    }
+
+    /// Makes the request report only the first event, by adding a
+    /// [Count] modifier of one.
+    ///
+    /// In general, the count modifier skips the first `count - 1` events and
+    /// then lets the next one through to the modifiers after it, and no
+    /// events are ever reported for the request after that.
+    ///
+    /// Note that the request is not cleared by that, so it is still
+    /// [active](super::VM::active_requests) until cleared explicitly.
+    pub fn once(self) -> Self {
+        self.count(1)
+    }
+
+    /// Adds a [Count] modifier, see [once](EventRequestBuilder::once).
+    pub fn count(self, count: i32) -> Self {
+        self.modifier(Modifier::Count(Count { count }))
+    }
+
+    /// Adds a [ThreadOnly] modifier.
+    pub fn thread_only(self, thread: &ThreadReference) -> Self {
+        self.modifier(Modifier::ThreadOnly(ThreadOnly {
+            thread: thread.id(),
+        }))
+    }
+
+    /// Adds a [ClassOnly] modifier.
+    pub fn class_only(self, class: &ReferenceType) -> Self {
+        self.modifier(Modifier::ClassOnly(ClassOnly { class: *class.id() }))
+    }
+
+    /// Adds a [ClassMatch] modifier.
+    pub fn class_match(self, class_pattern: impl Into<String>) -> Self {
+        self.modifier(Modifier::ClassMatch(ClassMatch {
+            class_pattern: class_pattern.into(),
+        }))
+    }
+
+    /// Adds a [ClassExclude] modifier.
+    pub fn class_exclude(self, class_pattern: impl Into<String>) -> Self {
+        self.modifier(Modifier::ClassExclude(ClassExclude {
+            class_pattern: class_pattern.into(),
+        }))
+    }
+
+    /// Adds a [LocationOnly] modifier.
+    pub fn location_only(self, location: Location) -> Self {
+        self.modifier(Modifier::LocationOnly(LocationOnly { location }))
+    }
+
+    /// Adds an [ExceptionOnly] modifier, `None` meaning the exceptions of
+    /// any type.
+    pub fn exception_only(
+        self,
+        exception: Option<&ReferenceType>,
+        caught: bool,
+        uncaught: bool,
+    ) -> Self {
+        self.modifier(Modifier::ExceptionOnly(ExceptionOnly {
+            exception: exception.map(|e| *e.id()),
+            caught,
+            uncaught,
+        }))
+    }
+
+    /// Adds a [FieldOnly] modifier.
+    pub fn field_only(self, field: &Field) -> Self {
+        self.modifier(Modifier::FieldOnly(FieldOnly {
+            declaring: *field.declaring_type().id(),
+            field_id: field.id(),
+        }))
+    }
+
+    /// Adds a [Step] modifier.
+    pub fn step(self, thread: &ThreadReference, size: StepSize, depth: StepDepth) -> Self {
+        self.modifier(Modifier::Step(Step {
+            thread: thread.id(),
+            size,
+            depth,
+        }))
+    }
+
+    /// Adds an [InstanceOnly] modifier.
+    pub fn instance_only(self, instance: &ObjectReference) -> Self {
+        self.modifier(Modifier::InstanceOnly(InstanceOnly {
+            instance: instance.id(),
+        }))
+    }
+
+    /// Adds a [SourceNameMatch] modifier.
+    pub fn source_name_match(self, source_name_pattern: impl Into<String>) -> Self {
+        self.modifier(Modifier::SourceNameMatch(SourceNameMatch {
+            source_name_pattern: source_name_pattern.into(),
+        }))
+    }
+
+    /// Sets the request, its events end up in the
+    /// [host events](crate::client::JdwpClient::host_events).
+    pub fn send(self) -> Result<RequestID, ClientError> {
+        let (client, request) = self.build()?;
+        client.send(request)
+    }
+
+    /// Sets the request and routes its events to the given sender, see
+    /// [SharedClient::subscribe].
+    pub fn subscribe(self, events: Sender<Event>) -> Result<RequestID, ClientError> {
+        let (client, request) = self.build()?;
+        client.subscribe(request, events)
+    }
+
+    fn build(self) -> Result<(SharedClient, Set), ClientError> {
+        for modifier in &self.modifiers {
+            if !is_applicable(modifier.kind(), self.event_kind) {
+                return Err(ClientError::ModifierNotApplicable {
+                    modifier: modifier.kind(),
+                    event_kind: self.event_kind,
+                });
+            }
+        }
+        let request = Set::new(self.event_kind, self.suspend_policy, self.modifiers);
+        Ok((self.client, request))
+    }
+}
+
+/// Whether the modifier can be used with the event kind, as the JDWP spec
+/// describes for each of them.
+fn is_applicable(modifier: ModifierKind, event_kind: EventKind) -> bool {
+    use EventKind::*;
+
+    match modifier {
+        ModifierKind::Count | ModifierKind::Conditional => true,
+        ModifierKind::ThreadOnly => event_kind != ClassUnload,
+        ModifierKind::ClassOnly => !matches!(event_kind, ClassUnload | ThreadStart | ThreadDeath),
+        ModifierKind::ClassMatch | ModifierKind::ClassExclude => {
+            !matches!(event_kind, ThreadStart | ThreadDeath)
+        }
+        ModifierKind::LocationOnly => matches!(
+            event_kind,
+            Breakpoint | FieldAccess | FieldModification | SingleStep | Exception
+        ),
+        ModifierKind::ExceptionOnly => event_kind == Exception,
+        ModifierKind::FieldOnly => matches!(event_kind, FieldAccess | FieldModification),
+        ModifierKind::Step => event_kind == SingleStep,
+        ModifierKind::InstanceOnly => !matches!(
+            event_kind,
+            ClassPrepare | ClassUnload | ThreadStart | ThreadDeath
+        ),
+        ModifierKind::SourceNameMatch => event_kind == ClassPrepare,
+    }
+}
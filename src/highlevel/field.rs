@@ -0,0 +1,113 @@
+use std::fmt::{self, Debug, Formatter};
+
+use crate::{
+    client::ClientError,
+    commands::{object_reference, reference_type},
+    jvm::FieldModifiers,
+    types::{FieldID, TaggedReferenceTypeID, Value},
+};
+
+use super::{ObjectReference, ReferenceType, SharedClient};
+
+/// A field of some class or interface in the target VM.
+#[derive(Clone)]
+pub struct Field {
+    client: SharedClient,
+    declaring_type: TaggedReferenceTypeID,
+    id: FieldID,
+    name: String,
+    signature: String,
+    modifiers: FieldModifiers,
+}
+
+impl Field {
+    pub fn new(
+        client: SharedClient,
+        declaring_type: TaggedReferenceTypeID,
+        field: reference_type::Field,
+    ) -> Self {
+        Self {
+            client,
+            declaring_type,
+            id: field.field_id,
+            name: field.name,
+            signature: field.signature,
+            modifiers: field.mod_bits,
+        }
+    }
+
+    pub fn id(&self) -> FieldID {
+        self.id
+    }
+
+    pub fn client(&self) -> &SharedClient {
+        &self.client
+    }
+
+    pub fn declaring_type(&self) -> ReferenceType {
+        ReferenceType::new(self.client.clone(), self.declaring_type)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The JNI signature of the field.
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+
+    pub fn modifiers(&self) -> FieldModifiers {
+        self.modifiers
+    }
+
+    pub fn is_static(&self) -> bool {
+        self.modifiers.contains(FieldModifiers::STATIC)
+    }
+
+    /// Reads the value of this field.
+    ///
+    /// Static fields are read from the declaring type and the owner is
+    /// ignored, see [reference_type::GetValues]. That is also the case for
+    /// the inherited ones, e.g. from [all_fields](ReferenceType::all_fields)
+    /// of a subclass.
+    /// Instance fields are read from the owner, which then must be given, see
+    /// [object_reference::GetValues].
+    pub fn read(&self, owner: Option<&ObjectReference>) -> Result<Value, ClientError> {
+        let mut values = if self.is_static() {
+            self.client.send(reference_type::GetValues::new(
+                *self.declaring_type,
+                vec![self.id],
+            ))?
+        } else {
+            let Some(owner) = owner else {
+                return Err(ClientError::IllegalArgument(format!(
+                    "reading the instance field {} requires an object",
+                    self.name
+                )));
+            };
+            self.client
+                .send(object_reference::GetValues::new(owner.id(), vec![self.id]))?
+        };
+        // there is exactly one value for the one field we asked for
+        Ok(values.remove(0))
+    }
+}
+
+impl Debug for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Field")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("signature", &self.signature)
+            .finish()
+    }
+}
+
+impl PartialEq for Field {
+    fn eq(&self, other: &Self) -> bool {
+        self.declaring_type == other.declaring_type && self.id == other.id
+    }
+}
+
+impl Eq for Field {}
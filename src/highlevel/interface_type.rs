@@ -0,0 +1,42 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    ops::Deref,
+};
+
+use crate::types::{InterfaceID, TaggedReferenceTypeID};
+
+use super::{ReferenceType, SharedClient};
+
+/// An interface type in the target VM.
+#[derive(Clone, PartialEq, Eq)]
+pub struct InterfaceType {
+    reference_type: ReferenceType,
+    id: InterfaceID,
+}
+
+impl InterfaceType {
+    pub fn new(client: SharedClient, id: InterfaceID) -> Self {
+        Self {
+            reference_type: ReferenceType::new(client, TaggedReferenceTypeID::Interface(id)),
+            id,
+        }
+    }
+
+    pub fn id(&self) -> InterfaceID {
+        self.id
+    }
+}
+
+impl Deref for InterfaceType {
+    type Target = ReferenceType;
+
+    fn deref(&self) -> &Self::Target {
+        &self.reference_type
+    }
+}
+
+impl Debug for InterfaceType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("InterfaceType").field(&self.id).finish()
+    }
+}
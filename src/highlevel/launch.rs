@@ -0,0 +1,86 @@
+use std::{
+    io::{self, ErrorKind},
+    net::TcpListener,
+    path::PathBuf,
+    process::{Child, Command},
+    thread,
+    time::Duration,
+};
+
+use crate::client::{ClientError, JdwpClient};
+
+/// How to launch a target VM, see [VM::launch](super::VM::launch).
+#[derive(Debug, Clone)]
+pub struct LaunchConfig {
+    /// The java executable, just `java` from the `PATH` by default
+    pub java: PathBuf,
+    /// Options for the JVM itself, e.g. `-cp`, passed before the main class
+    pub vm_options: Vec<String>,
+    /// The class with the main method to run
+    pub main_class: String,
+    /// Arguments for the main method
+    pub args: Vec<String>,
+    /// Whether the target VM should stay suspended until it is resumed by the
+    /// debugger
+    pub suspend: bool,
+}
+
+impl LaunchConfig {
+    pub fn new(main_class: impl Into<String>) -> Self {
+        Self {
+            java: PathBuf::from("java"),
+            vm_options: Vec::new(),
+            main_class: main_class.into(),
+            args: Vec::new(),
+            suspend: false,
+        }
+    }
+
+    /// Spawns the JVM, making it connect back to us, and does the handshake.
+    pub(crate) fn launch(&self) -> Result<(JdwpClient, Child), ClientError> {
+        // the JVM connects to us, so that we don't have to guess a free port
+        let listener = TcpListener::bind(("localhost", 0))?;
+        let port = listener.local_addr()?.port();
+
+        let suspend = if self.suspend { "y" } else { "n" };
+        let mut process = Command::new(&self.java)
+            .arg(format!(
+                "-agentlib:jdwp=transport=dt_socket,server=n,suspend={suspend},address=localhost:{port}"
+            ))
+            .args(&self.vm_options)
+            .arg(&self.main_class)
+            .args(&self.args)
+            .spawn()?;
+
+        match accept(&listener, &mut process).and_then(JdwpClient::from_stream) {
+            Ok(client) => Ok((client, process)),
+            Err(e) => {
+                // don't leave it hanging if the handshake failed or something
+                let _ = process.kill();
+                process.wait()?;
+                Err(e)
+            }
+        }
+    }
+}
+
+fn accept(listener: &TcpListener, process: &mut Child) -> Result<std::net::TcpStream, ClientError> {
+    // poll so that we notice if the JVM dies instead of connecting
+    listener.set_nonblocking(true)?;
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                stream.set_nonblocking(false)?;
+                return Ok(stream);
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                if let Some(status) = process.try_wait()? {
+                    let message = format!("The JVM exited before connecting: {status}");
+                    return Err(io::Error::new(ErrorKind::Other, message).into());
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
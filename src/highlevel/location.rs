@@ -0,0 +1,90 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    client::ClientError, commands::reference_type::SourceFile, enums::ErrorCode, jvm::FieldType,
+    types::Location,
+};
+
+use super::SharedClient;
+
+/// The position in the source code that a [Location] corresponds to, as far
+/// as the target VM knows.
+///
+/// Displays the same way as the JVM stack traces do, e.g. `Basic.java:42`
+/// or `Unknown Source`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourcePosition {
+    /// The source file name, `None` if it's not known (e.g. the class was
+    /// compiled without debug information)
+    pub source_file: Option<String>,
+    /// The line number, `None` if the method has no line table (e.g. it's
+    /// native or was compiled without debug information)
+    pub line: Option<u32>,
+}
+
+impl SourcePosition {
+    pub(crate) fn resolve(client: &SharedClient, location: &Location) -> Result<Self, ClientError> {
+        let source_file = match client.send(SourceFile::new(*location.reference_id)) {
+            Ok(source_file) => Some(source_file),
+            Err(ClientError::HostError(ErrorCode::AbsentInformation)) => None,
+            Err(e) => return Err(e),
+        };
+
+        let line = match client.line_table(*location.reference_id, location.method_id) {
+            // the line table is ordered by the code index
+            Ok(line_table) => line_table
+                .lines
+                .iter()
+                .take_while(|line| line.line_code_index <= location.index)
+                .last()
+                .map(|line| line.line_number),
+            Err(ClientError::HostError(ErrorCode::AbsentInformation | ErrorCode::NativeMethod)) => {
+                None
+            }
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self { source_file, line })
+    }
+}
+
+impl Display for SourcePosition {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (&self.source_file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{file}:{line}"),
+            (Some(file), None) => f.write_str(file),
+            (None, _) => f.write_str("Unknown Source"),
+        }
+    }
+}
+
+/// A [Location] described in full, see
+/// [VM::describe_location](super::VM::describe_location).
+///
+/// Displays the same way as a frame of the JVM stack traces, e.g.
+/// `Basic.tick(Basic.java:13)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocationInfo {
+    /// The JNI signature of the declaring type of the method
+    pub class_signature: String,
+    /// The name of the method
+    pub method_name: String,
+    /// The source file name, `None` if it's not known
+    pub source_file: Option<String>,
+    /// The line number, `None` if the method has no line table
+    pub line: Option<u32>,
+}
+
+impl Display for LocationInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.class_signature.parse::<FieldType>() {
+            Ok(class) => f.write_str(&class.java_name())?,
+            Err(_) => f.write_str(&self.class_signature)?,
+        }
+        let position = SourcePosition {
+            source_file: self.source_file.clone(),
+            line: self.line,
+        };
+        write!(f, ".{}({position})", self.method_name)
+    }
+}
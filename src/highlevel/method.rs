@@ -0,0 +1,245 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, ErrorKind},
+    sync::Arc,
+};
+
+use crate::{
+    client::ClientError,
+    commands::{
+        method::{LineTableReply, VariableTableReply},
+        reference_type,
+    },
+    enums::ErrorCode,
+    jvm::{FieldType, InvalidDescriptor, MethodModifiers},
+    types::{Location, MethodID, TaggedReferenceTypeID, Value},
+};
+
+use super::{ReferenceType, SharedClient};
+
+/// A method of some class or interface in the target VM.
+#[derive(Clone)]
+pub struct Method {
+    client: SharedClient,
+    declaring_type: TaggedReferenceTypeID,
+    id: MethodID,
+    name: String,
+    signature: String,
+    modifiers: MethodModifiers,
+}
+
+impl Method {
+    pub fn new(
+        client: SharedClient,
+        declaring_type: TaggedReferenceTypeID,
+        method: reference_type::Method,
+    ) -> Self {
+        Self {
+            client,
+            declaring_type,
+            id: method.method_id,
+            name: method.name,
+            signature: method.signature,
+            modifiers: method.mod_bits,
+        }
+    }
+
+    pub fn id(&self) -> MethodID {
+        self.id
+    }
+
+    pub fn client(&self) -> &SharedClient {
+        &self.client
+    }
+
+    pub fn declaring_type(&self) -> ReferenceType {
+        ReferenceType::new(self.client.clone(), self.declaring_type)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The JNI signature of the method.
+    pub fn signature(&self) -> &str {
+        &self.signature
+    }
+
+    pub fn modifiers(&self) -> MethodModifiers {
+        self.modifiers
+    }
+
+    /// Whether this method was replaced by a non-equivalent one when its
+    /// declaring type was redefined, see
+    /// [IsObsolete](crate::commands::method::IsObsolete).
+    ///
+    /// Cached the same way as the [line table](Method::line_table).
+    pub fn is_obsolete(&self) -> Result<bool, ClientError> {
+        self.client.is_obsolete(*self.declaring_type, self.id)
+    }
+
+    /// Returns the local variable slots of the arguments of this method, in
+    /// order.
+    ///
+    /// The slots are computed from the [signature](Method::signature), so
+    /// this works even when the method has no variable table; slot 0 is taken
+    /// by `this` for the non-static methods, and longs and doubles take two
+    /// slots each, see [FieldType::slot_size].
+    pub fn argument_slots(&self) -> Result<Vec<u32>, InvalidDescriptor> {
+        let mut slot = if self.modifiers.contains(MethodModifiers::STATIC) {
+            0
+        } else {
+            1
+        };
+        Ok(FieldType::parse_arguments(&self.signature)?
+            .into_iter()
+            .map(|argument| {
+                let argument_slot = slot;
+                slot += argument.slot_size();
+                argument_slot
+            })
+            .collect())
+    }
+
+    /// Checks that the given arguments fit the parameters of this method
+    /// before invoking it, which the target VM would only reject with an
+    /// opaque [IllegalArgument](crate::enums::ErrorCode::IllegalArgument).
+    ///
+    /// The primitive arguments must be of the parameter types exactly, and
+    /// the objects must be passed for the reference types - whether an object
+    /// is actually assignable to its parameter is left for the target VM to
+    /// check.
+    pub fn check_arguments(&self, args: &[Value]) -> Result<(), ClientError> {
+        let params = FieldType::parse_arguments(&self.signature).map_err(|e| {
+            io::Error::new(ErrorKind::InvalidData, format!("Bad method signature: {e}"))
+        })?;
+        if params.len() != args.len() {
+            return Err(ClientError::IllegalArgument(format!(
+                "{} takes {} arguments, got {}",
+                self.name,
+                params.len(),
+                args.len()
+            )));
+        }
+        for (index, (param, arg)) in params.into_iter().zip(args).enumerate() {
+            let fits = match param {
+                FieldType::Object(_) | FieldType::Array(_) => matches!(arg, Value::Object(_)),
+                _ => param.tag() == arg.tag(),
+            };
+            if !fits {
+                return Err(ClientError::ArgumentTypeMismatch {
+                    index,
+                    expected: param,
+                    actual: arg.tag(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the line table of this method, see
+    /// [LineTable](crate::commands::method::LineTable).
+    ///
+    /// The line table is cached for the whole [VM](super::VM) until the
+    /// declaring type is redefined with
+    /// [RedefineClasses](crate::commands::virtual_machine::RedefineClasses).
+    pub fn line_table(&self) -> Result<Arc<LineTableReply>, ClientError> {
+        self.client.line_table(*self.declaring_type, self.id)
+    }
+
+    /// Returns the locations at which the code of the given line starts,
+    /// there might be several of those e.g. for loops.
+    ///
+    /// This is empty if the line is not in this method, or if the method
+    /// has no line information (for example, if it is native).
+    pub fn locations_of_line(&self, line: u32) -> Result<Vec<Location>, ClientError> {
+        let Some(line_table) = self.code_line_table()? else {
+            return Ok(vec![]);
+        };
+        Ok(line_table
+            .lines
+            .iter()
+            .filter(|l| l.line_number == line)
+            .map(|l| Location {
+                reference_id: self.declaring_type,
+                method_id: self.id,
+                index: l.line_code_index,
+            })
+            .collect())
+    }
+
+    /// Returns the line that contains the given code index, if there is one.
+    ///
+    /// This is `None` if the index is outside of the method, or if the
+    /// method has no line information (for example, if it is native).
+    pub fn line_for_index(&self, code_index: u64) -> Result<Option<u32>, ClientError> {
+        let Some(line_table) = self.code_line_table()? else {
+            return Ok(None);
+        };
+        if line_table.start < 0
+            || code_index < line_table.start as u64
+            || code_index > line_table.end as u64
+        {
+            return Ok(None);
+        }
+        // the lines are ordered by their code indices, so this is the first
+        // one that starts after the index
+        let after = line_table
+            .lines
+            .partition_point(|l| l.line_code_index <= code_index);
+        Ok(after
+            .checked_sub(1)
+            .map(|i| line_table.lines[i].line_number))
+    }
+
+    /// Returns the variable table of this method, see
+    /// [VariableTable](crate::commands::method::VariableTable).
+    ///
+    /// Cached the same way as the [line table](Method::line_table).
+    pub fn variable_table(&self) -> Result<Arc<VariableTableReply>, ClientError> {
+        self.client.variable_table(*self.declaring_type, self.id)
+    }
+
+    /// Returns the bytecodes of this method, see
+    /// [Bytecodes](crate::commands::method::Bytecodes).
+    ///
+    /// Cached the same way as the [line table](Method::line_table), and
+    /// fails with [IllegalState](ClientError::IllegalState) if the target VM
+    /// lacks the `can_get_bytecodes` capability.
+    pub fn bytecodes(&self) -> Result<Arc<Vec<u8>>, ClientError> {
+        if !self.client.capabilities()?.can_get_bytecodes() {
+            return Err(ClientError::IllegalState(
+                "the target VM cannot get the bytecodes".to_owned(),
+            ));
+        }
+        self.client.bytecodes(*self.declaring_type, self.id)
+    }
+
+    // HotSpot fails with NativeMethod for the native methods instead of
+    // giving out a line table with both ends at -1 as the spec says
+    fn code_line_table(&self) -> Result<Option<Arc<LineTableReply>>, ClientError> {
+        match self.line_table() {
+            Ok(line_table) => Ok(Some(line_table)),
+            Err(ClientError::HostError(ErrorCode::NativeMethod)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Debug for Method {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Method")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("signature", &self.signature)
+            .finish()
+    }
+}
+
+impl PartialEq for Method {
+    fn eq(&self, other: &Self) -> bool {
+        self.declaring_type == other.declaring_type && self.id == other.id
+    }
+}
+
+impl Eq for Method {}
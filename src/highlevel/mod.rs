@@ -0,0 +1,471 @@
+//! A higher-level API built on top of the [JdwpClient] and the raw commands.
+//!
+//! The types here hold a [SharedClient] along with the relevant IDs, so
+//! instead of building and sending commands one by one you can just call
+//! methods on the objects you got from the target VM.
+//!
+//! The raw [commands](crate::commands) are always still available through
+//! [SharedClient::send] for anything that is not covered here.
+
+use std::{
+    any::Any,
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    net::ToSocketAddrs,
+    process::Child,
+    sync::{mpsc::Sender, Arc, Mutex, MutexGuard},
+};
+
+use crate::{
+    client::{ClientError, JdwpClient},
+    commands::{
+        event::Event,
+        event_request::{Clear, ClearAllBreakpoints, Set},
+        method::{
+            Bytecodes, IsObsolete, LineTable, LineTableReply, VariableTable, VariableTableReply,
+        },
+        reference_type::{Method as MethodInfo, Methods},
+        thread_reference::Name,
+        virtual_machine::{DisposeObjects, RedefineClasses},
+        Command,
+    },
+    enums::EventKind,
+    types::{FieldID, MethodID, ObjectID, ReferenceTypeID, RequestID, ThreadID, Value},
+    CommandId,
+};
+
+mod array_reference;
+mod capabilities;
+mod class_type;
+mod event;
+mod event_request;
+mod field;
+mod interface_type;
+mod launch;
+mod location;
+mod method;
+mod module_reference;
+mod object_reference;
+mod reference_type;
+mod stack_frame;
+mod thread_group_reference;
+mod thread_reference;
+mod vm;
+
+pub use array_reference::ArrayReference;
+pub use capabilities::Capabilities;
+pub use class_type::ClassType;
+pub use event::ExceptionEvent;
+pub use event_request::{ActiveRequest, EventRequestBuilder};
+pub use field::Field;
+pub use interface_type::InterfaceType;
+pub use launch::LaunchConfig;
+pub use location::{LocationInfo, SourcePosition};
+pub use method::Method;
+pub use module_reference::ModuleReference;
+pub use object_reference::{ObjectInspection, ObjectReference};
+pub use reference_type::ReferenceType;
+pub use stack_frame::StackFrame;
+pub use thread_group_reference::{ThreadGroupReference, ThreadTreeNode};
+pub use thread_reference::{
+    AnnotatedFrame, ThreadEvent, ThreadInfo, ThreadReference, ThreadSubscription,
+};
+pub use vm::{ClassOverview, RedefineCapability, RedefineError, VmStartInfo, VM};
+
+/// A [JdwpClient] that is shared between all the high-level wrappers
+/// originating from a single [VM].
+///
+/// Cloning it is cheap and gives another handle to the same connection.
+#[derive(Debug, Clone)]
+pub struct SharedClient(Arc<Shared>);
+
+/// The per-method replies, invalidated when the declaring type is redefined.
+type MethodCache<T> = Mutex<HashMap<(ReferenceTypeID, MethodID), T>>;
+
+#[derive(Debug)]
+struct Shared {
+    client: Mutex<JdwpClient>,
+    observer: Mutex<Option<Observer>>,
+    line_tables: MethodCache<Arc<LineTableReply>>,
+    variable_tables: MethodCache<Arc<VariableTableReply>>,
+    bytecodes: MethodCache<Arc<Vec<u8>>>,
+    obsolete: MethodCache<bool>,
+    methods: Mutex<HashMap<ReferenceTypeID, Arc<Vec<MethodInfo>>>>,
+    instance_fields: Mutex<HashMap<ReferenceTypeID, Arc<HashMap<String, FieldID>>>>,
+    thread_names: Mutex<HashMap<ThreadID, String>>,
+    // object IDs are only reused after being disposed, which drops these
+    identity_hashes: Mutex<HashMap<ObjectID, i32>>,
+    // the capabilities do not change during a session
+    capabilities: Mutex<Option<Capabilities>>,
+    // JDWP has no way to list the event requests, so they are tracked here
+    requests: Mutex<Vec<ActiveRequest>>,
+    // set if the target VM was launched by us
+    process: Option<Mutex<Child>>,
+}
+
+impl Drop for Shared {
+    fn drop(&mut self) {
+        if let Some(process) = &mut self.process {
+            let process = process.get_mut().unwrap();
+            if let Err(e) = process.kill().and_then(|_| process.wait()) {
+                log::warn!("Failed to kill the launched JVM: {e}");
+            }
+        }
+    }
+}
+
+struct Observer(Box<dyn FnMut(CommandId) + Send>);
+
+impl Debug for Observer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
+
+impl SharedClient {
+    pub fn new(client: JdwpClient) -> Self {
+        Self::with_process(client, None)
+    }
+
+    fn with_process(client: JdwpClient, process: Option<Child>) -> Self {
+        Self(Arc::new(Shared {
+            client: Mutex::new(client),
+            observer: Mutex::new(None),
+            line_tables: Mutex::new(HashMap::new()),
+            variable_tables: Mutex::new(HashMap::new()),
+            bytecodes: Mutex::new(HashMap::new()),
+            obsolete: Mutex::new(HashMap::new()),
+            methods: Mutex::new(HashMap::new()),
+            instance_fields: Mutex::new(HashMap::new()),
+            thread_names: Mutex::new(HashMap::new()),
+            identity_hashes: Mutex::new(HashMap::new()),
+            capabilities: Mutex::new(None),
+            requests: Mutex::new(Vec::new()),
+            process: process.map(Mutex::new),
+        }))
+    }
+
+    pub fn attach<A: ToSocketAddrs>(addr: A) -> Result<Self, ClientError> {
+        JdwpClient::attach(addr).map(Self::new)
+    }
+
+    /// Sends a raw command, see [JdwpClient::send].
+    ///
+    /// The caches kept by the high-level wrappers are invalidated as needed,
+    /// e.g. when classes are redefined, and the event requests that are set
+    /// or cleared are tracked, see [VM::active_requests].
+    pub fn send<C>(&self, command: C) -> Result<C::Output, ClientError>
+    where
+        C: Command + 'static,
+        C::Output: 'static,
+    {
+        self.observe(C::ID);
+
+        let redefined = (&command as &dyn Any)
+            .downcast_ref::<RedefineClasses>()
+            .map(|redefine| {
+                redefine
+                    .classes
+                    .iter()
+                    .map(|class| class.ref_type)
+                    .collect::<Vec<_>>()
+            });
+        let disposed = (&command as &dyn Any)
+            .downcast_ref::<DisposeObjects>()
+            .map(|dispose| {
+                dispose
+                    .requests
+                    .iter()
+                    .map(|request| request.object)
+                    .collect::<Vec<_>>()
+            });
+        let set_request = (&command as &dyn Any).downcast_ref::<Set>().cloned();
+        let cleared_request = (&command as &dyn Any)
+            .downcast_ref::<Clear>()
+            .map(|clear| (clear.event_kind, clear.request_id));
+
+        let result = self.lock().send(command);
+
+        if let Some(redefined) = redefined {
+            let is_kept =
+                |(ref_type, _): &(ReferenceTypeID, MethodID)| !redefined.contains(ref_type);
+            self.0.line_tables.lock().unwrap().retain(|k, _| is_kept(k));
+            self.0
+                .variable_tables
+                .lock()
+                .unwrap()
+                .retain(|k, _| is_kept(k));
+            self.0.bytecodes.lock().unwrap().retain(|k, _| is_kept(k));
+            self.0.obsolete.lock().unwrap().retain(|k, _| is_kept(k));
+            self.0
+                .methods
+                .lock()
+                .unwrap()
+                .retain(|ref_type, _| !redefined.contains(ref_type));
+            // the fields of the superclasses are in there too
+            self.0.instance_fields.lock().unwrap().clear();
+        }
+
+        // the reference counts are unknown here, so even the IDs that might
+        // still be alive are dropped
+        if let Some(disposed) = disposed {
+            self.0
+                .identity_hashes
+                .lock()
+                .unwrap()
+                .retain(|object, _| !disposed.contains(object));
+        }
+
+        if let Ok(output) = &result {
+            let mut requests = self.0.requests.lock().unwrap();
+            if let Some(request_id) = (output as &dyn Any).downcast_ref::<RequestID>() {
+                if let Some(set_request) = set_request {
+                    requests.push(ActiveRequest::new(*request_id, set_request));
+                }
+            } else if let Some(cleared) = cleared_request {
+                requests.retain(|r| (r.event_kind, r.request_id) != cleared);
+            } else if C::ID == ClearAllBreakpoints::ID {
+                requests.retain(|r| r.event_kind != EventKind::Breakpoint);
+            }
+        }
+
+        result
+    }
+
+    /// Sends the commands back to back and only then waits for the replies,
+    /// see [JdwpClient::send_all].
+    ///
+    /// Unlike with [send](SharedClient::send), the caches and the tracked
+    /// event requests are not updated, so this is only meant for the commands
+    /// that don't change anything.
+    pub fn send_all<C: Command>(
+        &self,
+        commands: impl IntoIterator<Item = C>,
+    ) -> Result<Vec<Result<C::Output, ClientError>>, ClientError> {
+        let commands = commands.into_iter().collect::<Vec<_>>();
+        for _ in &commands {
+            self.observe(C::ID);
+        }
+        self.lock().send_all(commands)
+    }
+
+    /// Splits the items into chunks of the given size, sends a command for
+    /// each chunk with [send_all](SharedClient::send_all) and concatenates
+    /// the values from the replies in order.
+    ///
+    /// Used for the `get_values_chunked` methods, e.g.
+    /// [ReferenceType::get_values_chunked].
+    fn get_values_chunked<T, C>(
+        &self,
+        items: &[T],
+        chunk_size: usize,
+        command: impl FnMut(&[T]) -> C,
+    ) -> Result<Vec<Value>, ClientError>
+    where
+        C: Command<Output = Vec<Value>>,
+    {
+        if chunk_size == 0 {
+            return Err(ClientError::IllegalArgument(
+                "the chunk size must not be zero".to_owned(),
+            ));
+        }
+        let mut values = Vec::with_capacity(items.len());
+        for reply in self.send_all(items.chunks(chunk_size).map(command))? {
+            values.extend(reply?);
+        }
+        Ok(values)
+    }
+
+    /// Sets an event request and routes its events to the given sender, see
+    /// [JdwpClient::subscribe].
+    ///
+    /// Same as with [send](SharedClient::send), the request is tracked until
+    /// it is cleared.
+    pub fn subscribe(&self, request: Set, events: Sender<Event>) -> Result<RequestID, ClientError> {
+        self.observe(Set::ID);
+
+        let request_id = self.lock().subscribe(request.clone(), events)?;
+        self.0
+            .requests
+            .lock()
+            .unwrap()
+            .push(ActiveRequest::new(request_id, request));
+        Ok(request_id)
+    }
+
+    /// Gives exclusive access to the underlying client.
+    ///
+    /// Other handles calling [send](SharedClient::send) will block until the
+    /// returned guard is dropped.
+    pub fn lock(&self) -> MutexGuard<'_, JdwpClient> {
+        self.0.client.lock().unwrap()
+    }
+
+    /// Returns the line table of the given method, caching it until the type
+    /// is redefined, see [Method::line_table].
+    fn line_table(
+        &self,
+        ref_type: ReferenceTypeID,
+        method_id: MethodID,
+    ) -> Result<Arc<LineTableReply>, ClientError> {
+        self.method_cached(&self.0.line_tables, ref_type, method_id, || {
+            Ok(Arc::new(self.send(LineTable::new(ref_type, method_id))?))
+        })
+    }
+
+    /// Same as [line_table](SharedClient::line_table), see
+    /// [Method::variable_table].
+    fn variable_table(
+        &self,
+        ref_type: ReferenceTypeID,
+        method_id: MethodID,
+    ) -> Result<Arc<VariableTableReply>, ClientError> {
+        self.method_cached(&self.0.variable_tables, ref_type, method_id, || {
+            Ok(Arc::new(
+                self.send(VariableTable::new(ref_type, method_id))?,
+            ))
+        })
+    }
+
+    /// Same as [line_table](SharedClient::line_table), see
+    /// [Method::bytecodes].
+    fn bytecodes(
+        &self,
+        ref_type: ReferenceTypeID,
+        method_id: MethodID,
+    ) -> Result<Arc<Vec<u8>>, ClientError> {
+        self.method_cached(&self.0.bytecodes, ref_type, method_id, || {
+            Ok(Arc::new(self.send(Bytecodes::new(ref_type, method_id))?))
+        })
+    }
+
+    /// Same as [line_table](SharedClient::line_table), see
+    /// [Method::is_obsolete].
+    fn is_obsolete(
+        &self,
+        ref_type: ReferenceTypeID,
+        method_id: MethodID,
+    ) -> Result<bool, ClientError> {
+        self.method_cached(&self.0.obsolete, ref_type, method_id, || {
+            self.send(IsObsolete::new(ref_type, method_id))
+        })
+    }
+
+    fn method_cached<T: Clone>(
+        &self,
+        cache: &MethodCache<T>,
+        ref_type: ReferenceTypeID,
+        method_id: MethodID,
+        fetch: impl FnOnce() -> Result<T, ClientError>,
+    ) -> Result<T, ClientError> {
+        let key = (ref_type, method_id);
+        if let Some(value) = cache.lock().unwrap().get(&key) {
+            return Ok(value.clone());
+        }
+        let value = fetch()?;
+        cache.lock().unwrap().insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// Returns the methods declared in the given type, fetching them once per
+    /// type, see [VM::method_info].
+    fn methods(&self, ref_type: ReferenceTypeID) -> Result<Arc<Vec<MethodInfo>>, ClientError> {
+        if let Some(methods) = self.0.methods.lock().unwrap().get(&ref_type) {
+            return Ok(methods.clone());
+        }
+        let methods = Arc::new(self.send(Methods::new(ref_type))?);
+        self.0
+            .methods
+            .lock()
+            .unwrap()
+            .insert(ref_type, methods.clone());
+        Ok(methods)
+    }
+
+    /// Returns the instance fields of the given class by name, including the
+    /// inherited ones, fetching them once per class, see
+    /// [ObjectReference::get_field].
+    ///
+    /// When a field hides another one with the same name further up the
+    /// hierarchy, the most derived one is there.
+    fn instance_fields(
+        &self,
+        class: &ClassType,
+    ) -> Result<Arc<HashMap<String, FieldID>>, ClientError> {
+        let key = *class.id();
+        if let Some(fields) = self.0.instance_fields.lock().unwrap().get(&key) {
+            return Ok(fields.clone());
+        }
+        let mut fields = HashMap::new();
+        let mut current = Some(class.clone());
+        while let Some(class) = current {
+            for field in class.fields()? {
+                if !field.is_static() {
+                    fields.entry(field.name().to_owned()).or_insert(field.id());
+                }
+            }
+            current = class.superclass()?;
+        }
+        let fields = Arc::new(fields);
+        self.0
+            .instance_fields
+            .lock()
+            .unwrap()
+            .insert(key, fields.clone());
+        Ok(fields)
+    }
+
+    /// Returns the capabilities of the target VM, fetching them once, see
+    /// [VM::capabilities].
+    fn capabilities(&self) -> Result<Capabilities, ClientError> {
+        if let Some(capabilities) = *self.0.capabilities.lock().unwrap() {
+            return Ok(capabilities);
+        }
+        let capabilities = Capabilities::fetch(self)?;
+        *self.0.capabilities.lock().unwrap() = Some(capabilities);
+        Ok(capabilities)
+    }
+
+    /// Returns the name of the given thread, caching it until
+    /// [ThreadReference::refresh_name] is called, see [ThreadReference::name].
+    fn thread_name(&self, thread: ThreadID) -> Result<String, ClientError> {
+        if let Some(name) = self.0.thread_names.lock().unwrap().get(&thread) {
+            return Ok(name.clone());
+        }
+        self.refresh_thread_name(thread)
+    }
+
+    fn refresh_thread_name(&self, thread: ThreadID) -> Result<String, ClientError> {
+        let name = self.send(Name::new(thread))?;
+        self.0
+            .thread_names
+            .lock()
+            .unwrap()
+            .insert(thread, name.clone());
+        Ok(name)
+    }
+
+    fn identity_hash(&self, object: ObjectID) -> Option<i32> {
+        self.0.identity_hashes.lock().unwrap().get(&object).copied()
+    }
+
+    fn cache_identity_hash(&self, object: ObjectID, hash: i32) {
+        self.0.identity_hashes.lock().unwrap().insert(object, hash);
+    }
+
+    /// Sets a callback that is called with the ID of every command sent
+    /// through [send](SharedClient::send) or
+    /// [subscribe](SharedClient::subscribe), replacing the previous one.
+    ///
+    /// Useful to see what the high-level API does under the hood, e.g. to
+    /// check that something was cached.
+    pub fn set_observer(&self, observer: impl FnMut(CommandId) + Send + 'static) {
+        *self.0.observer.lock().unwrap() = Some(Observer(Box::new(observer)));
+    }
+
+    fn observe(&self, id: CommandId) {
+        if let Some(observer) = &mut *self.0.observer.lock().unwrap() {
+            (observer.0)(id);
+        }
+    }
+}
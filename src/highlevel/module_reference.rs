@@ -0,0 +1,58 @@
+use std::fmt::{self, Debug, Formatter};
+
+use crate::{
+    client::ClientError,
+    commands::module_reference::{ClassLoader, Name},
+    types::ModuleID,
+};
+
+use super::{ObjectReference, SharedClient};
+
+/// A module in the target VM, available since Java 9.
+#[derive(Clone)]
+pub struct ModuleReference {
+    client: SharedClient,
+    id: ModuleID,
+}
+
+impl ModuleReference {
+    pub fn new(client: SharedClient, id: ModuleID) -> Self {
+        Self { client, id }
+    }
+
+    pub fn id(&self) -> ModuleID {
+        self.id
+    }
+
+    pub fn client(&self) -> &SharedClient {
+        &self.client
+    }
+
+    /// Returns the module name, see [Name].
+    ///
+    /// The unnamed modules have an empty name.
+    pub fn name(&self) -> Result<String, ClientError> {
+        self.client.send(Name::new(self.id))
+    }
+
+    /// Returns the class loader of this module, or `None` for the bootstrap
+    /// class loader, see [ClassLoader].
+    pub fn class_loader(&self) -> Result<Option<ObjectReference>, ClientError> {
+        let class_loader = self.client.send(ClassLoader::new(self.id))?;
+        Ok(class_loader.map(|id| ObjectReference::new(self.client.clone(), *id)))
+    }
+}
+
+impl Debug for ModuleReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ModuleReference").field(&self.id).finish()
+    }
+}
+
+impl PartialEq for ModuleReference {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for ModuleReference {}
@@ -0,0 +1,433 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, ErrorKind},
+};
+
+use crate::{
+    client::ClientError,
+    commands::{
+        class_type::FieldValue,
+        object_reference::{
+            GetValues, InvokeMethod, InvokeMethodReply, IsCollected,
+            ReferenceType as GetReferenceType, SetValues,
+        },
+        virtual_machine::ClassesBySignature,
+    },
+    enums::InvokeOptions,
+    jvm::MethodModifiers,
+    types::{ArrayID, ArrayRegion, FieldID, ObjectID, TaggedReferenceTypeID, Value},
+};
+
+use super::{
+    ArrayReference, ClassType, Field, Method, ReferenceType, SharedClient, ThreadReference,
+};
+
+/// An object in the target VM.
+#[derive(Clone)]
+pub struct ObjectReference {
+    client: SharedClient,
+    id: ObjectID,
+}
+
+impl ObjectReference {
+    pub fn new(client: SharedClient, id: ObjectID) -> Self {
+        Self { client, id }
+    }
+
+    pub fn id(&self) -> ObjectID {
+        self.id
+    }
+
+    pub fn client(&self) -> &SharedClient {
+        &self.client
+    }
+
+    /// Returns the runtime type of this object, see
+    /// [ReferenceType](crate::commands::object_reference::ReferenceType).
+    pub fn reference_type(&self) -> Result<ReferenceType, ClientError> {
+        let id = self.client.send(GetReferenceType::new(self.id))?;
+        Ok(ReferenceType::new(self.client.clone(), id))
+    }
+
+    /// Returns the value of the instance field with the given name, see
+    /// [GetValues].
+    ///
+    /// The field is looked up in the runtime type of this object and its
+    /// superclasses, and the lookups are cached per type. When a field hides
+    /// another one with the same name, as in `class B extends A { int x; }`
+    /// with `A` also declaring `x`, the one declared in the most derived class
+    /// is used, same as `this.x` would in `B`.
+    pub fn get_field(&self, name: &str) -> Result<Value, ClientError> {
+        let field = self.field_id(name)?;
+        let mut values = self.client.send(GetValues::new(self.id, vec![field]))?;
+        // there is exactly one value for the one field we asked for
+        Ok(values.remove(0))
+    }
+
+    /// Returns the values of the given instance fields in chunks, see
+    /// [ReferenceType::get_values_chunked](super::ReferenceType::get_values_chunked).
+    pub fn get_values_chunked(
+        &self,
+        fields: &[FieldID],
+        chunk_size: usize,
+    ) -> Result<Vec<Value>, ClientError> {
+        self.client.get_values_chunked(fields, chunk_size, |chunk| {
+            GetValues::new(self.id, chunk.to_vec())
+        })
+    }
+
+    /// Sets the value of the instance field with the given name, see
+    /// [SetValues].
+    ///
+    /// The field is looked up the same way as with
+    /// [get_field](ObjectReference::get_field).
+    pub fn set_field(&self, name: &str, value: Value) -> Result<(), ClientError> {
+        if value == Value::Void {
+            return Err(ClientError::IllegalArgument(
+                "a field cannot hold a void value".to_owned(),
+            ));
+        }
+        let value = FieldValue {
+            field_id: self.field_id(name)?,
+            value: value.into(),
+        };
+        self.client.send(SetValues::new(self.id, vec![value]))
+    }
+
+    fn field_id(&self, name: &str) -> Result<FieldID, ClientError> {
+        let fields = self.client.instance_fields(&self.class()?)?;
+        fields.get(name).copied().ok_or_else(|| {
+            ClientError::IllegalArgument(format!("there is no instance field {name} in the object"))
+        })
+    }
+
+    /// Invokes the instance method with the given name and JNI signature on
+    /// this object, see [InvokeMethod].
+    ///
+    /// The method is looked up among the [methods](ReferenceType::all_methods)
+    /// of the runtime type of this object, including the inherited ones, and
+    /// it is dispatched virtually unless
+    /// [NONVIRTUAL](InvokeOptions::NONVIRTUAL) is given.
+    ///
+    /// The thread must be suspended by an event.
+    /// The arguments are [checked](Method::check_arguments) before sending.
+    pub fn invoke(
+        &self,
+        thread: &ThreadReference,
+        name: &str,
+        signature: &str,
+        args: &[Value],
+        options: InvokeOptions,
+    ) -> Result<InvokeMethodReply, ClientError> {
+        let class = self.class()?;
+        let Some(method) = class
+            .all_methods()?
+            .into_iter()
+            .find(|m| m.name() == name && m.signature() == signature)
+        else {
+            return Err(ClientError::IllegalArgument(format!(
+                "there is no method {name}{signature} in the object"
+            )));
+        };
+        if method.modifiers().contains(MethodModifiers::STATIC) {
+            return Err(ClientError::IllegalArgument(format!(
+                "{name} is static, invoke it through the class"
+            )));
+        }
+        method.check_arguments(args)?;
+        self.client.send(InvokeMethod::new(
+            self.id,
+            thread.id(),
+            class.id(),
+            method.id(),
+            args.to_vec(),
+            options,
+        ))
+    }
+
+    /// Whether this object has been garbage collected in the target VM, see
+    /// [IsCollected].
+    pub fn is_collected(&self) -> Result<bool, ClientError> {
+        self.client.send(IsCollected::new(self.id))
+    }
+
+    // the runtime type of anything with fields or methods to look up
+    fn class(&self) -> Result<ClassType, ClientError> {
+        match self.reference_type()?.id() {
+            TaggedReferenceTypeID::Class(id) => Ok(ClassType::new(self.client.clone(), id)),
+            // an object is never of an interface type
+            _ => Err(ClientError::IllegalArgument(
+                "arrays have no fields or methods of their own".to_owned(),
+            )),
+        }
+    }
+
+    /// Invokes the given method of the given class on this object without
+    /// the virtual dispatch, so an overridden method of a superclass is called
+    /// just like `super.method()` would, see [InvokeMethod].
+    ///
+    /// The thread must be suspended by an event.
+    /// The arguments are [checked](Method::check_arguments) before sending.
+    pub fn invoke_nonvirtual(
+        &self,
+        thread: &ThreadReference,
+        class: &ClassType,
+        method: &Method,
+        args: &[Value],
+    ) -> Result<InvokeMethodReply, ClientError> {
+        method.check_arguments(args)?;
+        self.client.send(InvokeMethod::new(
+            self.id,
+            thread.id(),
+            class.id(),
+            method.id(),
+            args.to_vec(),
+            InvokeOptions::NONVIRTUAL,
+        ))
+    }
+
+    /// Returns the identity hash code of this object, the one
+    /// `Object.hashCode` returns unless overridden, by invoking
+    /// `System.identityHashCode` in the given thread.
+    ///
+    /// It never changes during the lifetime of the object, so it is cached
+    /// per object ID and only the first call needs a thread suspended by an
+    /// event.
+    pub fn identity_hash(&self, thread: &ThreadReference) -> Result<i32, ClientError> {
+        if let Some(hash) = self.client.identity_hash(self.id) {
+            return Ok(hash);
+        }
+        let Some(system) = self
+            .client
+            .send(ClassesBySignature::new("Ljava/lang/System;"))?
+            .pop()
+        else {
+            return Err(bad_identity_hash());
+        };
+        let TaggedReferenceTypeID::Class(system) = system.type_id else {
+            return Err(bad_identity_hash());
+        };
+        let system = ClassType::new(self.client.clone(), system);
+        let Some(identity_hash_code) = system.method_by_name("identityHashCode")? else {
+            return Err(bad_identity_hash());
+        };
+        let reply = system.invoke_static(
+            thread,
+            &identity_hash_code,
+            &[Value::Object(self.id)],
+            InvokeOptions::empty(),
+        )?;
+        match reply {
+            InvokeMethodReply::Value(Value::Int(hash)) => {
+                self.client.cache_identity_hash(self.id, hash);
+                Ok(hash)
+            }
+            _ => Err(bad_identity_hash()),
+        }
+    }
+
+    /// Gathers everything an object inspector would show about this object:
+    /// its runtime type, the values of all of its instance fields including
+    /// the inherited ones, and its identity hash code.
+    ///
+    /// The identity hash code is obtained with
+    /// [identity_hash](ObjectReference::identity_hash), so it is only there if
+    /// a thread suspended by an event is given.
+    pub fn inspect(
+        &self,
+        thread: Option<&ThreadReference>,
+    ) -> Result<ObjectInspection, ClientError> {
+        let reference_type = self.reference_type()?;
+        let signature = reference_type.signature()?;
+
+        let mut fields = Vec::new();
+        let mut class = match reference_type.id() {
+            TaggedReferenceTypeID::Class(id) => Some(ClassType::new(self.client.clone(), id)),
+            // arrays have no fields and an object is never of an interface type
+            _ => None,
+        };
+        while let Some(current) = class {
+            fields.extend(current.fields()?.into_iter().filter(|f| !f.is_static()));
+            class = current.superclass()?;
+        }
+
+        let values = if fields.is_empty() {
+            Vec::new()
+        } else {
+            let ids = fields.iter().map(Field::id).collect();
+            self.client.send(GetValues::new(self.id, ids))?
+        };
+
+        let identity_hash = match thread {
+            Some(thread) => Some(self.identity_hash(thread)?),
+            None => None,
+        };
+
+        Ok(ObjectInspection {
+            signature,
+            fields: fields.into_iter().zip(values).collect(),
+            identity_hash,
+        })
+    }
+
+    /// Unboxes this object if it is an instance of one of the primitive
+    /// wrapper classes, such as `java.lang.Integer`, by invoking its accessor,
+    /// such as `intValue`, or returns `None` for any other object.
+    ///
+    /// The thread must be suspended by an event.
+    pub fn unbox(&self, thread: &ThreadReference) -> Result<Option<Value>, ClientError> {
+        let reference_type = self.reference_type()?;
+        let Some(accessor) = unboxing_accessor(&reference_type.signature()?) else {
+            return Ok(None);
+        };
+        let TaggedReferenceTypeID::Class(class) = reference_type.id() else {
+            return Ok(None);
+        };
+        let class = ClassType::new(self.client.clone(), class);
+        let Some(method) = class.method_by_name(accessor)? else {
+            return Ok(None);
+        };
+        let reply = self.client.send(InvokeMethod::new(
+            self.id,
+            thread.id(),
+            class.id(),
+            method.id(),
+            vec![],
+            InvokeOptions::empty(),
+        ))?;
+        match reply {
+            InvokeMethodReply::Value(value) => Ok(Some(value)),
+            InvokeMethodReply::Exception(_) => Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{accessor} of a boxed value threw an exception"),
+            )
+            .into()),
+        }
+    }
+
+    /// Reconstructs the contents of this `java.lang.String` from its backing
+    /// array, without the [Value](crate::commands::string_reference::Value)
+    /// command or an invocation.
+    ///
+    /// Both the `char[]` of Java 8 and the `byte[]` along with the `coder` of
+    /// the compact strings of Java 9+ are handled. The unpaired surrogates
+    /// are replaced with U+FFFD.
+    pub fn string_from_fields(&self) -> Result<String, ClientError> {
+        let string_type = self.reference_type()?;
+        let signature = string_type.signature()?;
+        if signature != "Ljava/lang/String;" {
+            return Err(ClientError::IllegalArgument(format!(
+                "{signature} is not a string"
+            )));
+        }
+        let field = |name: &str| -> Result<Value, ClientError> {
+            let field = string_type.field_by_name(name)?.ok_or_else(|| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("String has no {name} field"),
+                )
+            })?;
+            field.read(Some(self))
+        };
+
+        let Value::Object(array) = field("value")? else {
+            return Err(bad_string_field("value"));
+        };
+        // SAFETY: the backing field of a string is always an array
+        let array = ArrayReference::new(self.client.clone(), unsafe { ArrayID::new(array) });
+        match array.get_all()? {
+            ArrayRegion::Char(chars) => Ok(String::from_utf16_lossy(&chars)),
+            ArrayRegion::Byte(bytes) => match field("coder")? {
+                // LATIN1
+                Value::Byte(0) => Ok(bytes.into_iter().map(char::from).collect()),
+                // UTF16, in the byte order of the target VM
+                Value::Byte(1) => {
+                    let big_endian = self.is_utf16_big_endian()?;
+                    let chars = bytes
+                        .chunks_exact(2)
+                        .map(|pair| match big_endian {
+                            true => u16::from_be_bytes([pair[0], pair[1]]),
+                            false => u16::from_le_bytes([pair[0], pair[1]]),
+                        })
+                        .collect::<Vec<_>>();
+                    Ok(String::from_utf16_lossy(&chars))
+                }
+                _ => Err(bad_string_field("coder")),
+            },
+            _ => Err(bad_string_field("value")),
+        }
+    }
+
+    // StringUTF16 keeps the byte order in a constant, and it must be loaded
+    // if there is a UTF16 string to begin with
+    fn is_utf16_big_endian(&self) -> Result<bool, ClientError> {
+        let Some(utf16) = self
+            .client
+            .send(ClassesBySignature::new("Ljava/lang/StringUTF16;"))?
+            .pop()
+        else {
+            return Ok(false);
+        };
+        let utf16 = ReferenceType::new(self.client.clone(), utf16.type_id);
+        match utf16.field_by_name("HI_BYTE_SHIFT")? {
+            Some(field) => Ok(field.read(None)? == Value::Int(8)),
+            None => Ok(false),
+        }
+    }
+}
+
+fn bad_identity_hash() -> ClientError {
+    io::Error::new(ErrorKind::InvalidData, "Unexpected System.identityHashCode").into()
+}
+
+fn bad_string_field(name: &str) -> ClientError {
+    io::Error::new(
+        ErrorKind::InvalidData,
+        format!("Unexpected String.{name} field"),
+    )
+    .into()
+}
+
+/// The name of the method returning the primitive value of a wrapper class
+/// with the given JNI signature.
+fn unboxing_accessor(signature: &str) -> Option<&'static str> {
+    let accessor = match signature {
+        "Ljava/lang/Boolean;" => "booleanValue",
+        "Ljava/lang/Byte;" => "byteValue",
+        "Ljava/lang/Character;" => "charValue",
+        "Ljava/lang/Short;" => "shortValue",
+        "Ljava/lang/Integer;" => "intValue",
+        "Ljava/lang/Long;" => "longValue",
+        "Ljava/lang/Float;" => "floatValue",
+        "Ljava/lang/Double;" => "doubleValue",
+        _ => return None,
+    };
+    Some(accessor)
+}
+
+/// Everything about an object at some point in time, see
+/// [ObjectReference::inspect].
+#[derive(Debug, Clone)]
+pub struct ObjectInspection {
+    /// The JNI signature of the runtime type of the object
+    pub signature: String,
+    /// All of the instance fields and their values, starting with the ones
+    /// declared in the runtime type and followed by the inherited ones
+    pub fields: Vec<(Field, Value)>,
+    /// The identity hash code, if a thread to compute it in was given
+    pub identity_hash: Option<i32>,
+}
+
+impl Debug for ObjectReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ObjectReference").field(&self.id).finish()
+    }
+}
+
+impl PartialEq for ObjectReference {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for ObjectReference {}
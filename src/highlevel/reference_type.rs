@@ -0,0 +1,443 @@
+use std::{
+    collections::HashSet,
+    fmt::{self, Debug, Formatter},
+    io::{self, ErrorKind},
+};
+
+use crate::{
+    client::ClientError,
+    commands::reference_type::{
+        ClassFileVersion, ClassFileVersionReply, Fields, GetValues, Interfaces, Methods, Modifiers,
+        Signature, SignatureWithGeneric, Status,
+    },
+    enums::{ClassStatus, ErrorCode},
+    jvm::{FieldType, GenericClassSignature, TypeModifiers, TypeParameter},
+    types::{FieldID, TaggedReferenceTypeID, Value},
+};
+
+use super::{ClassType, Field, InterfaceType, Method, ObjectReference, SharedClient};
+
+/// A class, interface or array type in the target VM.
+#[derive(Clone)]
+pub struct ReferenceType {
+    client: SharedClient,
+    id: TaggedReferenceTypeID,
+}
+
+impl ReferenceType {
+    pub fn new(client: SharedClient, id: TaggedReferenceTypeID) -> Self {
+        Self { client, id }
+    }
+
+    pub fn id(&self) -> TaggedReferenceTypeID {
+        self.id
+    }
+
+    pub fn client(&self) -> &SharedClient {
+        &self.client
+    }
+
+    /// Returns the JNI signature of this type, see [Signature].
+    pub fn signature(&self) -> Result<String, ClientError> {
+        self.client.send(Signature::new(*self.id))
+    }
+
+    /// Returns the type parameters declared by this type, parsed from its
+    /// generic signature, see [SignatureWithGeneric].
+    ///
+    /// Empty for the non-generic types.
+    pub fn type_parameters(&self) -> Result<Vec<TypeParameter>, ClientError> {
+        let generic_signature = self
+            .client
+            .send(SignatureWithGeneric::new(*self.id))?
+            .generic_signature;
+        if generic_signature.is_empty() {
+            return Ok(Vec::new());
+        }
+        let signature = generic_signature
+            .parse::<GenericClassSignature>()
+            .map_err(|e| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Bad generic signature: {e}"),
+                )
+            })?;
+        Ok(signature.type_parameters)
+    }
+
+    /// Whether this is one of the primitive pseudo-types, like the one
+    /// represented by `java.lang.Integer.TYPE`.
+    ///
+    /// Their signature is a single primitive descriptor character, e.g. `I`.
+    pub fn is_primitive(&self) -> Result<bool, ClientError> {
+        let signature = self.signature()?;
+        Ok(matches!(
+            signature.as_str(),
+            "B" | "C" | "D" | "F" | "I" | "J" | "S" | "Z" | "V"
+        ))
+    }
+
+    /// Returns the modifiers of this type, see [Modifiers].
+    ///
+    /// These are undefined for the primitive types, so
+    /// [NotApplicableToPrimitive](ClientError::NotApplicableToPrimitive) is
+    /// returned for those.
+    pub fn modifiers(&self) -> Result<TypeModifiers, ClientError> {
+        self.ensure_not_primitive()?;
+        self.client.send(Modifiers::new(*self.id))
+    }
+
+    /// Returns the version of the class file this type was loaded from, see
+    /// [ClassFileVersion].
+    ///
+    /// The arrays and the primitive types have no class files, so
+    /// [IllegalArgument](ClientError::IllegalArgument) and
+    /// [NotApplicableToPrimitive](ClientError::NotApplicableToPrimitive) are
+    /// returned for those respectively. When the target VM does not have the
+    /// version or does not implement the command at all (it's there since
+    /// JDWP 1.6), this fails with
+    /// [ClassFileVersionUnavailable](ClientError::ClassFileVersionUnavailable).
+    pub fn class_file_version(&self) -> Result<ClassFileVersionReply, ClientError> {
+        if let TaggedReferenceTypeID::Array(_) = self.id {
+            return Err(ClientError::IllegalArgument(
+                "the class file version is not applicable for arrays".to_owned(),
+            ));
+        }
+        self.ensure_not_primitive()?;
+        match self.client.send(ClassFileVersion::new(*self.id)) {
+            Err(ClientError::HostError(
+                code @ (ErrorCode::AbsentInformation | ErrorCode::NotImplemented),
+            )) => Err(ClientError::ClassFileVersionUnavailable(code)),
+            result => result,
+        }
+    }
+
+    /// Returns the values of the given static fields, sending a [GetValues]
+    /// command for every `chunk_size` of them, so that a huge list does not
+    /// end up in a single packet.
+    ///
+    /// The commands are sent back to back, see
+    /// [send_all](SharedClient::send_all), and the values are in the order
+    /// of the fields. Fails with
+    /// [IllegalArgument](ClientError::IllegalArgument) if the chunk size is
+    /// zero.
+    pub fn get_values_chunked(
+        &self,
+        fields: &[FieldID],
+        chunk_size: usize,
+    ) -> Result<Vec<Value>, ClientError> {
+        self.client.get_values_chunked(fields, chunk_size, |chunk| {
+            GetValues::new(*self.id, chunk.to_vec())
+        })
+    }
+
+    /// Whether this type was generated by the compiler or at runtime rather
+    /// than written in the source code, see [TypeModifiers::SYNTHETIC].
+    ///
+    /// Same as with [modifiers](ReferenceType::modifiers), this is undefined
+    /// for the primitive types.
+    pub fn is_synthetic(&self) -> Result<bool, ClientError> {
+        Ok(self.modifiers()?.contains(TypeModifiers::SYNTHETIC))
+    }
+
+    /// Whether this is one of the classes spun up at runtime for the lambdas
+    /// and method references, e.g. `Basic$$Lambda$14/0x0000000800c03000`.
+    ///
+    /// This is a heuristic, JDWP has no way to tell those apart: they are
+    /// synthetic classes with a `$$Lambda` in their name.
+    pub fn is_lambda(&self) -> Result<bool, ClientError> {
+        // checking the name first also avoids failing for the primitives
+        Ok(is_lambda_signature(&self.signature()?) && self.is_synthetic()?)
+    }
+
+    /// Returns the status of this type, see [Status].
+    ///
+    /// Same as with [modifiers](ReferenceType::modifiers), this is undefined
+    /// for the primitive types.
+    pub fn status(&self) -> Result<ClassStatus, ClientError> {
+        self.ensure_not_primitive()?;
+        self.client.send(Status::new(*self.id))
+    }
+
+    /// Whether the static initialization of this type has failed, see
+    /// [ClassStatus::ERROR].
+    ///
+    /// Such a type cannot be used anymore, anything that would use it
+    /// throws a `NoClassDefFoundError` in the target VM.
+    pub fn initialization_error(&self) -> Result<bool, ClientError> {
+        Ok(self.status()?.contains(ClassStatus::ERROR))
+    }
+
+    /// Returns the interfaces directly implemented by this class or extended
+    /// by this interface, see [Interfaces].
+    pub fn interfaces(&self) -> Result<Vec<InterfaceType>, ClientError> {
+        let interfaces = self.client.send(Interfaces::new(*self.id))?;
+        Ok(interfaces
+            .into_iter()
+            .map(|id| InterfaceType::new(self.client.clone(), id))
+            .collect())
+    }
+
+    /// Returns all the interfaces implemented by this type, including the
+    /// ones implemented by its superclasses and the ones extended by other
+    /// interfaces, without duplicates.
+    pub fn all_interfaces(&self) -> Result<Vec<InterfaceType>, ClientError> {
+        let mut all = Vec::new();
+
+        let mut pending = self.interfaces()?;
+        let mut superclass = match self.id {
+            TaggedReferenceTypeID::Class(id) => {
+                ClassType::new(self.client.clone(), id).superclass()?
+            }
+            _ => None,
+        };
+        while let Some(class) = superclass {
+            pending.extend(class.interfaces()?);
+            superclass = class.superclass()?;
+        }
+
+        while let Some(interface) = pending.pop() {
+            if !all.contains(&interface) {
+                pending.extend(interface.interfaces()?);
+                all.push(interface);
+            }
+        }
+        Ok(all)
+    }
+
+    /// Returns the fields declared directly in this type, see [Fields].
+    pub fn fields(&self) -> Result<Vec<Field>, ClientError> {
+        let fields = self.client.send(Fields::new(*self.id))?;
+        Ok(fields
+            .into_iter()
+            .map(|field| Field::new(self.client.clone(), self.id, field))
+            .collect())
+    }
+
+    /// Returns the field declared directly in this type that has the given
+    /// name.
+    pub fn field_by_name(&self, name: &str) -> Result<Option<Field>, ClientError> {
+        Ok(self.fields()?.into_iter().find(|f| f.name() == name))
+    }
+
+    /// Returns the fields declared in this type and the inherited ones: the
+    /// ones of the superclasses and of all the interfaces, each with its own
+    /// [declaring type](Field::declaring_type).
+    ///
+    /// Unlike with the methods, the hidden fields are included, so there can
+    /// be several fields with the same name, the most derived one coming
+    /// first. Reading an inherited static field
+    /// [goes](Field::read) to the type that declares it.
+    pub fn all_fields(&self) -> Result<Vec<Field>, ClientError> {
+        let mut all = self.fields()?;
+        if let TaggedReferenceTypeID::Class(id) = self.id {
+            let mut superclass = ClassType::new(self.client.clone(), id).superclass()?;
+            while let Some(class) = superclass {
+                all.extend(class.fields()?);
+                superclass = class.superclass()?;
+            }
+        }
+        for interface in self.all_interfaces()? {
+            all.extend(interface.fields()?);
+        }
+        Ok(all)
+    }
+
+    /// Returns the methods declared directly in this type, see [Methods].
+    pub fn methods(&self) -> Result<Vec<Method>, ClientError> {
+        let methods = self.client.send(Methods::new(*self.id))?;
+        Ok(methods
+            .into_iter()
+            .map(|method| Method::new(self.client.clone(), self.id, method))
+            .collect())
+    }
+
+    /// Returns the methods declared in this type and the inherited ones: the
+    /// ones of the superclasses and of all the interfaces (for the default
+    /// methods), each with its own [declaring type](Method::declaring_type).
+    ///
+    /// A method shadows the methods with the same name and signature that are
+    /// further up the hierarchy, so the overridden ones are not included, and
+    /// neither are the constructors and static initializers of the supertypes.
+    pub fn all_methods(&self) -> Result<Vec<Method>, ClientError> {
+        let mut supertypes = Vec::new();
+        if let TaggedReferenceTypeID::Class(id) = self.id {
+            let mut superclass = ClassType::new(self.client.clone(), id).superclass()?;
+            while let Some(class) = superclass {
+                superclass = class.superclass()?;
+                supertypes.push((*class).clone());
+            }
+        }
+        supertypes.extend(self.all_interfaces()?.iter().map(|i| (**i).clone()));
+
+        let mut seen = HashSet::new();
+        let mut all = Vec::new();
+        for method in self.methods()? {
+            seen.insert((method.name().to_owned(), method.signature().to_owned()));
+            all.push(method);
+        }
+        for supertype in supertypes {
+            for method in supertype.methods()? {
+                if method.name().starts_with('<') {
+                    continue;
+                }
+                if seen.insert((method.name().to_owned(), method.signature().to_owned())) {
+                    all.push(method);
+                }
+            }
+        }
+        Ok(all)
+    }
+
+    /// Returns the first method declared directly in this type that has the
+    /// given name.
+    pub fn method_by_name(&self, name: &str) -> Result<Option<Method>, ClientError> {
+        Ok(self.methods()?.into_iter().find(|m| m.name() == name))
+    }
+
+    /// Picks the method declared directly in this type with the given name
+    /// that can be invoked with the given arguments, to tell the overloads
+    /// apart.
+    ///
+    /// The primitive arguments must match the parameter types exactly (there
+    /// are no widening conversions or boxing), and the objects must be
+    /// assignable to them, with null being assignable to any reference type.
+    /// If several overloads fit, the one with the most parameters that are
+    /// exactly of the runtime types of the arguments wins, which is a rough
+    /// approximation of the "most specific" rule of the Java compiler.
+    pub fn resolve_method(
+        &self,
+        name: &str,
+        args: &[Value],
+    ) -> Result<Option<Method>, ClientError> {
+        // the types that each non-null object argument can be assigned to
+        let arg_types = args
+            .iter()
+            .map(|arg| match *arg {
+                Value::Object(id) if !arg.is_null() => {
+                    ObjectReference::new(self.client.clone(), id)
+                        .reference_type()?
+                        .assignable_types()
+                        .map(Some)
+                }
+                _ => Ok(None),
+            })
+            .collect::<Result<Vec<_>, ClientError>>()?;
+
+        let mut best: Option<(usize, Method)> = None;
+        for method in self.methods()? {
+            if method.name() != name {
+                continue;
+            }
+            let Ok(params) = FieldType::parse_arguments(method.signature()) else {
+                continue;
+            };
+            if params.len() != args.len() {
+                continue;
+            }
+            let mut exact = 0;
+            let fits =
+                params
+                    .iter()
+                    .zip(args.iter().zip(&arg_types))
+                    .all(|(param, (arg, types))| match (param, types) {
+                        (FieldType::Object(_) | FieldType::Array(_), Some(types)) => {
+                            match types.iter().position(|t| t == param) {
+                                Some(0) => {
+                                    exact += 1;
+                                    true
+                                }
+                                Some(_) => true,
+                                None => false,
+                            }
+                        }
+                        (FieldType::Object(_) | FieldType::Array(_), None) => arg.is_null(),
+                        _ => param.tag() == arg.tag(),
+                    });
+            if fits && best.as_ref().map_or(true, |(e, _)| exact > *e) {
+                best = Some((exact, method));
+            }
+        }
+        Ok(best.map(|(_, method)| method))
+    }
+
+    /// Returns this type followed by all of the types its instances can be
+    /// assigned to.
+    fn assignable_types(&self) -> Result<Vec<FieldType>, ClientError> {
+        let mut signatures = vec![self.signature()?];
+        match self.id {
+            TaggedReferenceTypeID::Class(id) => {
+                let mut superclass = ClassType::new(self.client.clone(), id).superclass()?;
+                while let Some(class) = superclass {
+                    signatures.push(class.signature()?);
+                    superclass = class.superclass()?;
+                }
+            }
+            TaggedReferenceTypeID::Interface(_) => signatures.push("Ljava/lang/Object;".into()),
+            TaggedReferenceTypeID::Array(_) => signatures.extend(
+                [
+                    "Ljava/lang/Object;",
+                    "Ljava/lang/Cloneable;",
+                    "Ljava/io/Serializable;",
+                ]
+                .map(String::from),
+            ),
+        }
+        for interface in self.all_interfaces()? {
+            signatures.push(interface.signature()?);
+        }
+        signatures
+            .iter()
+            .map(|signature| {
+                signature.parse().map_err(|e| {
+                    io::Error::new(ErrorKind::InvalidData, format!("Bad type signature: {e}"))
+                        .into()
+                })
+            })
+            .collect()
+    }
+
+    fn ensure_not_primitive(&self) -> Result<(), ClientError> {
+        if self.is_primitive()? {
+            return Err(ClientError::NotApplicableToPrimitive);
+        }
+        Ok(())
+    }
+}
+
+/// The lambda classes are named `Outer$$Lambda$N/0x...` by the older JVMs
+/// and `Outer$$Lambda.0x...` or `Outer$$Lambda/0x...` by the newer ones.
+fn is_lambda_signature(signature: &str) -> bool {
+    signature.starts_with('L') && signature.contains("$$Lambda")
+}
+
+impl Debug for ReferenceType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReferenceType").field(&self.id).finish()
+    }
+}
+
+impl PartialEq for ReferenceType {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for ReferenceType {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lambda_signature() {
+        assert!(is_lambda_signature("LBasic$$Lambda$14.0x0000000800c03000;"));
+        assert!(is_lambda_signature("LBasic$$Lambda$1/1831932724;"));
+        assert!(is_lambda_signature("LBasic$$Lambda/0x000071c1a4001000;"));
+
+        assert!(!is_lambda_signature("LBasic;"));
+        assert!(!is_lambda_signature("LBasic$1;"));
+        assert!(!is_lambda_signature("Ljava/lang/invoke/LambdaForm;"));
+        assert!(!is_lambda_signature("[LBasic$$Lambda$1/1831932724;"));
+    }
+}
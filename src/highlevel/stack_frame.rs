@@ -0,0 +1,175 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, ErrorKind},
+};
+
+use crate::{
+    client::ClientError,
+    commands::stack_frame::{GetValues, PopFrames, SetValues, Slot, SlotValue, ThisObject},
+    enums::Tag,
+    jvm::FieldType,
+    types::{FrameID, Location, ThreadID, Value},
+};
+
+use super::{ObjectReference, SharedClient, ThreadReference};
+
+/// A frame on the call stack of a suspended thread.
+///
+/// Frames are only valid while their thread stays suspended, otherwise the
+/// commands fail with either
+/// [ThreadNotSuspended](crate::enums::ErrorCode::ThreadNotSuspended) or
+/// [InvalidFrameid](crate::enums::ErrorCode::InvalidFrameid) host errors.
+#[derive(Clone)]
+pub struct StackFrame {
+    client: SharedClient,
+    thread: ThreadID,
+    id: FrameID,
+    location: Location,
+}
+
+impl StackFrame {
+    pub fn new(client: SharedClient, thread: ThreadID, id: FrameID, location: Location) -> Self {
+        Self {
+            client,
+            thread,
+            id,
+            location,
+        }
+    }
+
+    pub fn id(&self) -> FrameID {
+        self.id
+    }
+
+    pub fn client(&self) -> &SharedClient {
+        &self.client
+    }
+
+    pub fn thread(&self) -> ThreadReference {
+        ThreadReference::new(self.client.clone(), self.thread)
+    }
+
+    /// The location of the code this frame is currently executing.
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    /// Returns the values of the local variables in the given slots, with
+    /// the tags of their types, see [GetValues].
+    pub fn get_values(&self, slots: &[(u32, Tag)]) -> Result<Vec<Value>, ClientError> {
+        let slots = slots
+            .iter()
+            .map(|&(slot, sigbyte)| Slot { slot, sigbyte })
+            .collect();
+        self.client
+            .send(GetValues::new(self.thread, self.id, slots))
+    }
+
+    /// Returns the values of the local variables in the given slots in
+    /// chunks, see
+    /// [ReferenceType::get_values_chunked](super::ReferenceType::get_values_chunked).
+    pub fn get_values_chunked(
+        &self,
+        slots: &[(u32, Tag)],
+        chunk_size: usize,
+    ) -> Result<Vec<Value>, ClientError> {
+        self.client.get_values_chunked(slots, chunk_size, |chunk| {
+            let slots = chunk
+                .iter()
+                .map(|&(slot, sigbyte)| Slot { slot, sigbyte })
+                .collect();
+            GetValues::new(self.thread, self.id, slots)
+        })
+    }
+
+    /// Sets the values of the local variables in the given slots, see
+    /// [SetValues].
+    pub fn set_values(&self, values: &[(u32, Value)]) -> Result<(), ClientError> {
+        let values = values
+            .iter()
+            .map(|&(slot, value)| SlotValue { slot, value })
+            .collect();
+        self.client
+            .send(SetValues::new(self.thread, self.id, values))
+    }
+
+    /// Returns the `this` object of this frame, or `None` if its method is
+    /// static or native, see [ThisObject].
+    pub fn this_object(&self) -> Result<Option<ObjectReference>, ClientError> {
+        let this = self.client.send(ThisObject::new(self.thread, self.id))?;
+        Ok(this.map(|id| ObjectReference::new(self.client.clone(), *id)))
+    }
+
+    /// Pops this frame and all the frames above it off the stack, so that
+    /// the invoke instruction that called its method is executed again once
+    /// the thread resumes, see [PopFrames].
+    ///
+    /// All of the popped frames become invalid.
+    pub fn pop(&self) -> Result<(), ClientError> {
+        self.client.send(PopFrames::new(self.thread, self.id))
+    }
+
+    /// Returns the names and values of all the local variables visible at
+    /// the current location of this frame, ordered by their slots.
+    ///
+    /// The value tags are inferred from the
+    /// [variable table](super::Method::variable_table), so this fails
+    /// with [AbsentInformation](crate::enums::ErrorCode::AbsentInformation)
+    /// if the class was compiled without the local variable tables (`-g`).
+    pub fn all_locals(&self) -> Result<Vec<(String, Value)>, ClientError> {
+        let location = &self.location;
+        let table = self
+            .client
+            .variable_table(*location.reference_id, location.method_id)?;
+
+        let mut visible = table
+            .slots
+            .iter()
+            .filter(|v| {
+                v.code_index <= location.index && location.index < v.code_index + v.length as u64
+            })
+            .collect::<Vec<_>>();
+        visible.sort_by_key(|v| v.slot);
+
+        let slots = visible
+            .iter()
+            .map(|v| {
+                let field_type = v.signature.parse::<FieldType>().map_err(|e| {
+                    io::Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Bad variable signature: {e}"),
+                    )
+                })?;
+                Ok((v.slot, field_type.tag()))
+            })
+            .collect::<Result<Vec<_>, ClientError>>()?;
+        if slots.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let values = self.get_values(&slots)?;
+        Ok(visible
+            .into_iter()
+            .map(|v| v.name.clone())
+            .zip(values)
+            .collect())
+    }
+}
+
+impl Debug for StackFrame {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StackFrame")
+            .field("id", &self.id)
+            .field("thread", &self.thread)
+            .field("location", &self.location)
+            .finish()
+    }
+}
+
+impl PartialEq for StackFrame {
+    fn eq(&self, other: &Self) -> bool {
+        self.thread == other.thread && self.id == other.id
+    }
+}
+
+impl Eq for StackFrame {}
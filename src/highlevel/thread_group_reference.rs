@@ -0,0 +1,123 @@
+use std::fmt::{self, Debug, Formatter};
+
+use crate::{
+    client::ClientError,
+    commands::thread_group_reference::{Children, Name, Parent},
+    enums::ErrorCode,
+    types::ThreadGroupID,
+};
+
+use super::{SharedClient, ThreadReference};
+
+/// A thread group in the target VM.
+///
+/// Thread groups can be destroyed at any time, after which the methods fail
+/// with the [InvalidThreadGroup](ErrorCode::InvalidThreadGroup) host error.
+#[derive(Clone)]
+pub struct ThreadGroupReference {
+    client: SharedClient,
+    id: ThreadGroupID,
+}
+
+impl ThreadGroupReference {
+    pub fn new(client: SharedClient, id: ThreadGroupID) -> Self {
+        Self { client, id }
+    }
+
+    pub fn id(&self) -> ThreadGroupID {
+        self.id
+    }
+
+    pub fn client(&self) -> &SharedClient {
+        &self.client
+    }
+
+    /// Returns the thread group name, see [Name].
+    pub fn name(&self) -> Result<String, ClientError> {
+        self.client.send(Name::new(self.id))
+    }
+
+    /// Returns the group containing this one, or `None` for the top-level
+    /// groups, see [Parent].
+    pub fn parent(&self) -> Result<Option<ThreadGroupReference>, ClientError> {
+        let parent = self.client.send(Parent::new(self.id))?;
+        Ok(parent.map(|id| ThreadGroupReference::new(self.client.clone(), id)))
+    }
+
+    /// Returns the live threads and the active thread groups directly
+    /// contained in this group, see [Children].
+    pub fn children(
+        &self,
+    ) -> Result<(Vec<ThreadReference>, Vec<ThreadGroupReference>), ClientError> {
+        let children = self.client.send(Children::new(self.id))?;
+        let threads = children
+            .child_threads
+            .into_iter()
+            .map(|id| ThreadReference::new(self.client.clone(), id))
+            .collect();
+        let groups = children
+            .child_groups
+            .into_iter()
+            .map(|id| ThreadGroupReference::new(self.client.clone(), id))
+            .collect();
+        Ok((threads, groups))
+    }
+
+    /// Visits this group and everything in it depth-first, calling `f` with
+    /// the depth of each node, 0 being this group.
+    ///
+    /// The threads of a group are visited before its subgroups. A subgroup
+    /// that gets destroyed during the walk is still visited but its children
+    /// are skipped, while this group being gone is an error.
+    pub fn walk(&self, mut f: impl FnMut(usize, &ThreadTreeNode)) -> Result<(), ClientError> {
+        f(0, &ThreadTreeNode::Group(self.clone()));
+        self.walk_children(1, &mut f)
+    }
+
+    fn walk_children(
+        &self,
+        depth: usize,
+        f: &mut dyn FnMut(usize, &ThreadTreeNode),
+    ) -> Result<(), ClientError> {
+        let (threads, groups) = match self.children() {
+            Ok(children) => children,
+            Err(ClientError::HostError(ErrorCode::InvalidThreadGroup)) if depth > 1 => {
+                return Ok(())
+            }
+            Err(e) => return Err(e),
+        };
+        for thread in threads {
+            f(depth, &ThreadTreeNode::Thread(thread));
+        }
+        for group in groups {
+            f(depth, &ThreadTreeNode::Group(group.clone()));
+            group.walk_children(depth + 1, f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for ThreadGroupReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ThreadGroupReference")
+            .field(&self.id)
+            .finish()
+    }
+}
+
+impl PartialEq for ThreadGroupReference {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for ThreadGroupReference {}
+
+/// A node of the thread group tree, see [ThreadGroupReference::walk].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThreadTreeNode {
+    /// A thread group, followed by its contents one level deeper.
+    Group(ThreadGroupReference),
+    /// A live thread, always a leaf.
+    Thread(ThreadReference),
+}
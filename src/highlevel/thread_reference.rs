@@ -0,0 +1,524 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Display, Formatter, Write},
+    io::{self, ErrorKind},
+    sync::mpsc::{self, Receiver, RecvError, RecvTimeoutError, TryRecvError},
+    time::Duration,
+};
+
+use crate::{
+    client::ClientError,
+    commands::{
+        event::Event,
+        event_request,
+        method::IsObsolete,
+        thread_reference::{
+            ForceEarlyReturn, FrameCount, Frames, Resume, Status, Suspend, SuspendCount,
+        },
+    },
+    enums::{
+        ErrorCode, EventKind, StepDepth, StepSize, SuspendPolicy, SuspendStatus, ThreadStatus,
+    },
+    jvm::{FieldType, MethodModifiers, MethodSignature},
+    types::{Location, RequestID, ThreadID, Value},
+};
+
+use super::{EventRequestBuilder, Method, ReferenceType, SharedClient, SourcePosition, StackFrame};
+
+/// A thread in the target VM.
+#[derive(Clone)]
+pub struct ThreadReference {
+    client: SharedClient,
+    id: ThreadID,
+}
+
+impl ThreadReference {
+    pub fn new(client: SharedClient, id: ThreadID) -> Self {
+        Self { client, id }
+    }
+
+    pub fn id(&self) -> ThreadID {
+        self.id
+    }
+
+    pub fn client(&self) -> &SharedClient {
+        &self.client
+    }
+
+    /// Returns the thread name, see
+    /// [Name](crate::commands::thread_reference::Name).
+    ///
+    /// The name is fetched once and then cached for all the references to
+    /// this thread from the same [SharedClient], as JDWP has no event for a
+    /// thread being renamed. So if the target VM renames the thread, this
+    /// keeps returning the old name until [refresh_name](Self::refresh_name)
+    /// is called.
+    pub fn name(&self) -> Result<String, ClientError> {
+        self.client.thread_name(self.id)
+    }
+
+    /// Fetches the thread name again, updating the cached one, see
+    /// [name](Self::name).
+    pub fn refresh_name(&self) -> Result<String, ClientError> {
+        self.client.refresh_thread_name(self.id)
+    }
+
+    /// Returns the status of this thread the last time it was running, see
+    /// [Status].
+    pub fn status(&self) -> Result<ThreadStatus, ClientError> {
+        Ok(self.client.send(Status::new(self.id))?.thread_status)
+    }
+
+    /// Whether this thread is suspended, either by itself or along with the
+    /// whole VM, see [Status].
+    pub fn is_suspended(&self) -> Result<bool, ClientError> {
+        let status = self.client.send(Status::new(self.id))?;
+        Ok(status.suspend_status.contains(SuspendStatus::SUSPENDED))
+    }
+
+    /// Suspends this thread, see [Suspend].
+    ///
+    /// The suspends are counted, so it has to be [resumed](Self::resume) as
+    /// many times before it runs again.
+    pub fn suspend(&self) -> Result<(), ClientError> {
+        self.client.send(Suspend::new(self.id))
+    }
+
+    /// Resumes this thread once, see [Resume].
+    pub fn resume(&self) -> Result<(), ClientError> {
+        self.client.send(Resume::new(self.id))
+    }
+
+    /// Suspends this thread for the duration of the closure, so that the
+    /// frames can be inspected without it running off in between the
+    /// commands.
+    ///
+    /// The thread is resumed once afterwards, even if the closure fails or
+    /// panics, which balances out the suspend - if it was suspended already,
+    /// it stays that way.
+    pub fn with_suspended<R>(
+        &self,
+        f: impl FnOnce(&Self) -> Result<R, ClientError>,
+    ) -> Result<R, ClientError> {
+        self.suspend()?;
+        let _guard = ResumeGuard(self);
+        f(self)
+    }
+
+    /// Returns the number of frames on the stack of this thread, see
+    /// [FrameCount].
+    ///
+    /// The thread must be suspended.
+    pub fn frame_count(&self) -> Result<u32, ClientError> {
+        Ok(self.client.send(FrameCount::new(self.id))? as u32)
+    }
+
+    /// Resumes this suspended thread until it completes a single step of the
+    /// given size and depth, returning the location it has stepped to.
+    ///
+    /// The step request is set with the
+    /// [EventThread](SuspendPolicy::EventThread) policy, so the thread is
+    /// suspended again at that location, and the request is cleared
+    /// afterwards even if the step has failed. If the step does not
+    /// complete within the timeout, e.g. because the thread is blocked,
+    /// this fails with a [TimedOut](io::ErrorKind::TimedOut) error and the
+    /// thread is left running.
+    pub fn step(
+        &self,
+        size: StepSize,
+        depth: StepDepth,
+        timeout: Duration,
+    ) -> Result<Location, ClientError> {
+        if self.client.send(SuspendCount::new(self.id))? == 0 {
+            return Err(not_suspended());
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let request_id = EventRequestBuilder::new(self.client.clone(), EventKind::SingleStep)
+            .suspend_policy(SuspendPolicy::EventThread)
+            .step(self, size, depth)
+            .once()
+            .subscribe(tx)?;
+        let _guard = StepGuard {
+            client: &self.client,
+            request_id,
+        };
+
+        self.resume()?;
+        match rx.recv_timeout(timeout) {
+            Ok(Event::SingleStep(step)) => Ok(step.location),
+            Ok(e) => unreachable!("Only the step event is subscribed to, got {:?}", e),
+            Err(RecvTimeoutError::Timeout) => {
+                Err(io::Error::new(ErrorKind::TimedOut, "the step did not complete in time").into())
+            }
+            Err(RecvTimeoutError::Disconnected) => Err(ClientError::Disconnected),
+        }
+    }
+
+    /// Returns all the frames of this thread, from the topmost one, see
+    /// [Frames].
+    ///
+    /// The thread must be suspended.
+    pub fn frames(&self) -> Result<Vec<StackFrame>, ClientError> {
+        Ok(self
+            .client
+            .send(Frames::new(self.id, 0, -1))?
+            .into_iter()
+            .map(|frame| {
+                StackFrame::new(self.client.clone(), self.id, frame.frame_id, frame.location)
+            })
+            .collect())
+    }
+
+    /// Returns all the frames of this thread, from the topmost one, along with
+    /// what a stack view would show about them, see [AnnotatedFrame].
+    ///
+    /// The methods of each type and whether each method is obsolete are
+    /// cached by the client, see [Method::is_obsolete].
+    ///
+    /// The thread must be suspended.
+    pub fn annotated_stack(&self) -> Result<Vec<AnnotatedFrame>, ClientError> {
+        let mut annotated = Vec::new();
+        for frame in self.frames()? {
+            let location = frame.location().clone();
+            let methods = self.client.methods(*location.reference_id)?;
+            // obsolete methods are not listed
+            let method = methods.iter().find(|m| m.method_id == location.method_id);
+            let modifiers = method.map_or(MethodModifiers::empty(), |m| m.mod_bits);
+
+            let is_obsolete = self
+                .client
+                .is_obsolete(*location.reference_id, location.method_id)?;
+
+            annotated.push(AnnotatedFrame {
+                method_name: method.map(|m| m.name.clone()),
+                position: SourcePosition::resolve(&self.client, &location)?,
+                is_native: modifiers.contains(MethodModifiers::NATIVE),
+                is_obsolete,
+                // JDWP marks the synthetic methods with one of the top bits
+                // if the class file has no flag for that
+                is_synthetic: modifiers.contains(MethodModifiers::SYNTHETIC)
+                    || modifiers.bits() & 0xf000_0000 != 0,
+                frame,
+            });
+        }
+        Ok(annotated)
+    }
+
+    /// Returns the location of the topmost frame of this thread, or `None` if
+    /// it has no frames, see [Frames].
+    ///
+    /// The thread must be suspended.
+    pub fn current_location(&self) -> Result<Option<Location>, ClientError> {
+        let frames = self.client.send(Frames::new(self.id, 0, 1))?;
+        Ok(frames.into_iter().next().map(|frame| frame.location))
+    }
+
+    /// Renders the frames of this thread the way
+    /// [Throwable.printStackTrace](https://docs.oracle.com/en/java/javase/17/docs/api/java.base/java/lang/Throwable.html#printStackTrace())
+    /// does, one `\tat Basic.tick(Basic.java:42)` line per frame, with the
+    /// `Native Method` and `Unknown Source` fallbacks.
+    ///
+    /// The module names that newer JVMs prepend to the classes are omitted.
+    ///
+    /// The thread must be suspended.
+    pub fn printable_stack_trace(&self) -> Result<String, ClientError> {
+        // frames of the same class are common, so its methods are fetched once
+        let mut types = HashMap::<_, (String, Vec<Method>)>::new();
+        let mut trace = String::new();
+        for frame in self.client.send(Frames::new(self.id, 0, -1))? {
+            let location = frame.location;
+            let (class_name, methods) = match types.get(&location.reference_id) {
+                Some(entry) => entry,
+                None => {
+                    let ref_type = ReferenceType::new(self.client.clone(), location.reference_id);
+                    let signature = ref_type.signature()?;
+                    let class_name = match signature.parse::<FieldType>() {
+                        Ok(class) => class.java_name(),
+                        Err(_) => signature,
+                    };
+                    let methods = ref_type.methods()?;
+                    types
+                        .entry(location.reference_id)
+                        .or_insert((class_name, methods))
+                }
+            };
+            let method = methods.iter().find(|m| m.id() == location.method_id);
+            let method_name = method.map_or("<unknown>", |m| m.name());
+
+            write!(trace, "\tat {class_name}.{method_name}(").unwrap();
+            if method.map_or(false, |m| m.modifiers().contains(MethodModifiers::NATIVE)) {
+                trace.push_str("Native Method");
+            } else {
+                let position = SourcePosition::resolve(&self.client, &location)?;
+                write!(trace, "{position}").unwrap();
+            }
+            trace.push_str(")\n");
+        }
+        Ok(trace)
+    }
+
+    /// Counts the frames of this thread that execute the given method, e.g.
+    /// to see how deep a runaway recursion has gone.
+    ///
+    /// The thread must be suspended.
+    pub fn recursion_depth(&self, method: &Method) -> Result<u32, ClientError> {
+        let declaring_type = method.declaring_type().id();
+        let frames = self.client.send(Frames::new(self.id, 0, -1))?;
+        Ok(frames
+            .iter()
+            .filter(|frame| {
+                frame.location.reference_id == declaring_type
+                    && frame.location.method_id == method.id()
+            })
+            .count() as u32)
+    }
+
+    /// Makes the method of the topmost frame return the given value once
+    /// this thread resumes, without executing the rest of it, see
+    /// [ForceEarlyReturn].
+    ///
+    /// The preconditions are checked beforehand, failing with
+    /// [IllegalState](ClientError::IllegalState) if the target VM lacks the
+    /// `can_force_early_return` capability, if the thread is not suspended or
+    /// if the frame is opaque (e.g. native), and with
+    /// [IllegalArgument](ClientError::IllegalArgument) if the value does not
+    /// fit the return type of the method - use [Value::Void] for the void
+    /// methods.
+    pub fn force_early_return(&self, value: Value) -> Result<(), ClientError> {
+        if !self.client.capabilities()?.can_force_early_return() {
+            return Err(ClientError::IllegalState(
+                "the target VM cannot force early returns".to_owned(),
+            ));
+        }
+        if self.client.send(SuspendCount::new(self.id))? == 0 {
+            return Err(not_suspended());
+        }
+
+        let Some(location) = self.current_location()? else {
+            return Err(ClientError::IllegalState(
+                "the thread has no frames".to_owned(),
+            ));
+        };
+        let methods = ReferenceType::new(self.client.clone(), location.reference_id).methods()?;
+        if let Some(method) = methods.iter().find(|m| m.id() == location.method_id) {
+            let signature = method.signature().parse::<MethodSignature>().map_err(|e| {
+                io::Error::new(ErrorKind::InvalidData, format!("Bad method signature: {e}"))
+            })?;
+            let fits = match &signature.ret {
+                None => value == Value::Void,
+                Some(FieldType::Object(_) | FieldType::Array(_)) => {
+                    matches!(value, Value::Object(_))
+                }
+                Some(ret) => ret.tag() == value.tag(),
+            };
+            if !fits {
+                return Err(ClientError::IllegalArgument(format!(
+                    "{} cannot return {:?}",
+                    method.name(),
+                    value
+                )));
+            }
+        }
+
+        match self.client.send(ForceEarlyReturn::new(self.id, value)) {
+            Err(ClientError::HostError(ErrorCode::OpaqueFrame)) => Err(ClientError::IllegalState(
+                "the topmost frame is native or otherwise opaque".to_owned(),
+            )),
+            // it might have been resumed since the check
+            Err(ClientError::HostError(ErrorCode::ThreadNotSuspended)) => Err(not_suspended()),
+            result => result,
+        }
+    }
+
+    /// Whether any of the frames of this thread is executing an obsolete
+    /// method, see [IsObsolete].
+    ///
+    /// After classes are redefined, such frames keep running the old code,
+    /// so debuggers usually offer to pop them.
+    ///
+    /// The thread must be suspended.
+    pub fn has_obsolete_frames(&self) -> Result<bool, ClientError> {
+        for frame in self.client.send(Frames::new(self.id, 0, -1))? {
+            let location = frame.location;
+            let is_obsolete = IsObsolete::new(*location.reference_id, location.method_id);
+            if self.client.send(is_obsolete)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// Resumes the thread once when dropped, see
+/// [with_suspended](ThreadReference::with_suspended).
+struct ResumeGuard<'a>(&'a ThreadReference);
+
+impl Drop for ResumeGuard<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.resume() {
+            log::warn!("Failed to resume the thread {:?}: {e}", self.0.id);
+        }
+    }
+}
+
+/// Clears the step request when dropped, see [step](ThreadReference::step).
+struct StepGuard<'a> {
+    client: &'a SharedClient,
+    request_id: RequestID,
+}
+
+impl Drop for StepGuard<'_> {
+    fn drop(&mut self) {
+        self.client.lock().unsubscribe(self.request_id);
+        let clear = event_request::Clear::new(EventKind::SingleStep, self.request_id);
+        if let Err(e) = self.client.send(clear) {
+            log::warn!("Failed to clear the step request: {e}");
+        }
+    }
+}
+
+fn not_suspended() -> ClientError {
+    ClientError::IllegalState("the thread is not suspended".to_owned())
+}
+
+impl Debug for ThreadReference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ThreadReference").field(&self.id).finish()
+    }
+}
+
+impl PartialEq for ThreadReference {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for ThreadReference {}
+
+/// A frame along with what a stack view would show about it, see
+/// [ThreadReference::annotated_stack].
+#[derive(Debug, Clone)]
+pub struct AnnotatedFrame {
+    pub frame: StackFrame,
+    /// The name of the method, `None` if it's obsolete and so is not listed
+    /// among the methods of its type anymore
+    pub method_name: Option<String>,
+    pub position: SourcePosition,
+    /// Whether the method is native, which also means it has no line table
+    pub is_native: bool,
+    /// Whether the method was replaced by a redefinition of its type while
+    /// this frame kept running the old code
+    pub is_obsolete: bool,
+    /// Whether the method was generated by the compiler, e.g. a bridge method
+    /// or the body of a lambda
+    pub is_synthetic: bool,
+}
+
+/// A snapshot of a single thread, collected by
+/// [VM::thread_dump](super::VM::thread_dump).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadInfo {
+    pub thread: ThreadReference,
+    pub name: String,
+    /// The status of the thread the last time it was running.
+    pub status: ThreadStatus,
+    /// The frames of the thread, rendered by
+    /// [printable_stack_trace](ThreadReference::printable_stack_trace).
+    pub stack_trace: String,
+}
+
+impl ThreadInfo {
+    /// The thread must be suspended.
+    pub(crate) fn collect(thread: ThreadReference) -> Result<Self, ClientError> {
+        Ok(Self {
+            name: thread.name()?,
+            status: thread.status()?,
+            stack_trace: thread.printable_stack_trace()?,
+            thread,
+        })
+    }
+}
+
+/// Renders the thread the way `jstack` does, the quoted name and the status
+/// followed by the stack trace.
+impl Display for ThreadInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "\"{}\" {:?}", self.name, self.status)?;
+        f.write_str(&self.stack_trace)
+    }
+}
+
+/// A change in the set of the running threads, see [ThreadSubscription].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThreadEvent {
+    /// The thread was started, see
+    /// [ThreadStart](crate::commands::event::ThreadStart).
+    Started(ThreadReference),
+    /// The thread has died, see
+    /// [ThreadDeath](crate::commands::event::ThreadDeath).
+    Died(ThreadReference),
+}
+
+/// A subscription to the thread start and death events, created by
+/// [VM::subscribe_threads](super::VM::subscribe_threads).
+///
+/// Both event requests are cleared when this is dropped.
+#[derive(Debug)]
+pub struct ThreadSubscription {
+    client: SharedClient,
+    requests: Vec<(EventKind, RequestID)>,
+    events: Receiver<Event>,
+}
+
+impl ThreadSubscription {
+    pub(crate) fn subscribe(client: SharedClient) -> Result<Self, ClientError> {
+        let (tx, rx) = mpsc::channel();
+        let mut subscription = Self {
+            client,
+            requests: Vec::with_capacity(2),
+            events: rx,
+        };
+        for event_kind in [EventKind::ThreadStart, EventKind::ThreadDeath] {
+            let request = event_request::Set::new(event_kind, SuspendPolicy::None, vec![]);
+            // if this fails, the requests that were already set are cleared on drop
+            let request_id = subscription.client.subscribe(request, tx.clone())?;
+            subscription.requests.push((event_kind, request_id));
+        }
+        Ok(subscription)
+    }
+
+    /// Blocks until the next thread is started or dies.
+    pub fn recv(&self) -> Result<ThreadEvent, RecvError> {
+        self.events.recv().map(|e| self.convert(e))
+    }
+
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<ThreadEvent, RecvTimeoutError> {
+        self.events.recv_timeout(timeout).map(|e| self.convert(e))
+    }
+
+    pub fn try_recv(&self) -> Result<ThreadEvent, TryRecvError> {
+        self.events.try_recv().map(|e| self.convert(e))
+    }
+
+    fn convert(&self, event: Event) -> ThreadEvent {
+        let client = self.client.clone();
+        match event {
+            Event::ThreadStart(e) => ThreadEvent::Started(ThreadReference::new(client, e.thread)),
+            Event::ThreadDeath(e) => ThreadEvent::Died(ThreadReference::new(client, e.thread)),
+            e => unreachable!("Only thread events are subscribed to, got {:?}", e),
+        }
+    }
+}
+
+impl Drop for ThreadSubscription {
+    fn drop(&mut self) {
+        for &(event_kind, request_id) in &self.requests {
+            self.client.lock().unsubscribe(request_id);
+            let clear = event_request::Clear::new(event_kind, request_id);
+            if let Err(e) = self.client.send(clear) {
+                log::warn!("Failed to clear the {event_kind:?} request: {e}");
+            }
+        }
+    }
+}
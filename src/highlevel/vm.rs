@@ -0,0 +1,738 @@
+use std::{
+    io::{self, ErrorKind},
+    net::ToSocketAddrs,
+    sync::mpsc::RecvTimeoutError,
+    time::{Duration, Instant},
+};
+
+use thiserror::Error;
+
+use crate::{
+    client::{ClientError, JdwpClient},
+    commands::{
+        array_reference,
+        event::{Composite, Event},
+        object_reference::{InvokeMethod, InvokeMethodReply},
+        reference_type::{Signature, SourceFile, Status},
+        string_reference,
+        virtual_machine::{
+            AllClasses, AllClassesWithGeneric, AllModules, AllThreads, ClassesBySignature,
+            GenericClass, InstanceCounts, RedefineClasses, RedefiningClass, Resume, Suspend,
+            TopLevelThreadGroups, Version, VersionReply,
+        },
+    },
+    enums::{ClassStatus, ErrorCode, EventKind, InvokeOptions, SuspendPolicy},
+    jvm::FieldType,
+    types::{ArrayID, Location, ReferenceTypeID, TaggedObjectID, TaggedReferenceTypeID, Value},
+};
+
+use super::{
+    ActiveRequest, Capabilities, ClassType, EventRequestBuilder, LaunchConfig, LocationInfo,
+    ModuleReference, ObjectReference, ReferenceType, SharedClient, SourcePosition,
+    ThreadGroupReference, ThreadInfo, ThreadReference, ThreadSubscription,
+};
+
+/// The target VM, the entry point of the high-level API.
+#[derive(Debug, Clone)]
+pub struct VM {
+    client: SharedClient,
+}
+
+impl VM {
+    pub fn new(client: JdwpClient) -> Self {
+        Self {
+            client: SharedClient::new(client),
+        }
+    }
+
+    pub fn attach<A: ToSocketAddrs>(addr: A) -> Result<Self, ClientError> {
+        SharedClient::attach(addr).map(|client| Self { client })
+    }
+
+    /// Launches a new JVM as configured and attaches to it.
+    ///
+    /// The launched JVM is killed once the last handle to it (this [VM] or
+    /// any of the wrappers that came from it) is dropped.
+    pub fn launch(config: &LaunchConfig) -> Result<Self, ClientError> {
+        let (client, process) = config.launch()?;
+        Ok(Self {
+            client: SharedClient::with_process(client, Some(process)),
+        })
+    }
+
+    /// Launches a new suspended JVM as configured, regardless of
+    /// [LaunchConfig::suspend], and waits for the automatic
+    /// [VmStart](crate::commands::event::VmStart) event, see
+    /// [await_start](VM::await_start).
+    ///
+    /// The whole VM stays suspended at that point, which is the time to set up
+    /// any breakpoints or other event requests before resuming it and letting
+    /// the main method run.
+    pub fn launch_and_wait_start(
+        config: &LaunchConfig,
+        timeout: Duration,
+    ) -> Result<(Self, VmStartInfo), ClientError> {
+        let vm = Self::launch(&LaunchConfig {
+            suspend: true,
+            ..config.clone()
+        })?;
+
+        let start = vm.await_start(timeout)?;
+        Ok((vm, start))
+    }
+
+    /// Waits for the automatic [VmStart](crate::commands::event::VmStart)
+    /// event and tells whether it left the target VM suspended.
+    ///
+    /// The suspend policy of that event is up to the target VM, in practice
+    /// everything is suspended if it was launched with `suspend=y` and
+    /// nothing otherwise. With the VM suspended this is a deterministic point
+    /// to set up the initial breakpoints before resuming it.
+    ///
+    /// The event is only sent once, so this must be called before anything
+    /// else takes it from the [host events](JdwpClient::host_events). The
+    /// other events taken from there while waiting are returned in
+    /// [VmStartInfo::other_events].
+    ///
+    /// The client is not kept locked while waiting, and if the event does not
+    /// arrive in time this fails with a [TimedOut](ErrorKind::TimedOut) IO
+    /// error.
+    pub fn await_start(&self, timeout: Duration) -> Result<VmStartInfo, ClientError> {
+        // how long the client stays locked at a time
+        const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+        let deadline = Instant::now() + timeout;
+        let mut other_events = Vec::new();
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let received = self
+                .client
+                .lock()
+                .host_events()
+                .recv_timeout(remaining.min(POLL_INTERVAL));
+            let mut composite = match received {
+                Ok(composite) => composite,
+                Err(RecvTimeoutError::Timeout) if remaining > POLL_INTERVAL => continue,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(io::Error::new(
+                        ErrorKind::TimedOut,
+                        "the VM start event did not arrive in time",
+                    )
+                    .into())
+                }
+                Err(RecvTimeoutError::Disconnected) => return Err(ClientError::Disconnected),
+            };
+            let vm_start = composite
+                .events
+                .iter()
+                .position(|event| matches!(event, Event::VmStart(_)));
+            let Some(vm_start) = vm_start else {
+                other_events.push(composite);
+                continue;
+            };
+            let Event::VmStart(vm_start) = composite.events.remove(vm_start) else {
+                unreachable!()
+            };
+            let suspend_policy = composite.suspend_policy;
+            if !composite.events.is_empty() {
+                other_events.push(composite);
+            }
+            return Ok(VmStartInfo {
+                thread: ThreadReference::new(self.client.clone(), vm_start.thread_id),
+                suspend_policy,
+                vm_suspended: suspend_policy == SuspendPolicy::All,
+                other_events,
+            });
+        }
+    }
+
+    pub fn client(&self) -> &SharedClient {
+        &self.client
+    }
+
+    /// Returns the JDWP version and the VM name/version descriptions, see
+    /// [Version].
+    pub fn version(&self) -> Result<VersionReply, ClientError> {
+        self.client.send(Version)
+    }
+
+    /// Returns the Java feature version of the target VM, e.g. `8` for
+    /// `1.8.0_292` or `17` for `17.0.1`.
+    ///
+    /// This is parsed from the `java.version` property reported in the
+    /// [VersionReply::vm_version], and is `None` if it could not be parsed.
+    pub fn java_version(&self) -> Result<Option<u32>, ClientError> {
+        Ok(parse_java_version(&self.version()?.vm_version))
+    }
+
+    /// Returns all the loaded types that match the given JNI signature, see
+    /// [ClassesBySignature].
+    pub fn classes_by_signature(&self, signature: &str) -> Result<Vec<ReferenceType>, ClientError> {
+        let classes = self.client.send(ClassesBySignature::new(signature))?;
+        Ok(classes
+            .into_iter()
+            .map(|class| ReferenceType::new(self.client.clone(), class.type_id))
+            .collect())
+    }
+
+    /// Returns all the loaded types with their generic signatures, see
+    /// [AllClassesWithGeneric].
+    pub fn classes_with_generic(&self) -> Result<Vec<GenericClass>, ClientError> {
+        self.client.send(AllClassesWithGeneric)
+    }
+
+    /// Returns the loaded types that match the predicate, e.g. the ones from
+    /// a package using [GenericClass::class_name].
+    pub fn find_classes(
+        &self,
+        predicate: impl Fn(&GenericClass) -> bool,
+    ) -> Result<Vec<GenericClass>, ClientError> {
+        let mut classes = self.classes_with_generic()?;
+        classes.retain(predicate);
+        Ok(classes)
+    }
+
+    /// Returns the signatures of the loaded types with the most instances and
+    /// their instance counts, at most `top_n` of them, with the counts of all
+    /// the types fetched with a single [InstanceCounts] command.
+    ///
+    /// Requires the `can_get_instance_info` capability, see
+    /// [Capabilities::can_get_instance_info].
+    pub fn heap_histogram(&self, top_n: usize) -> Result<Vec<(String, u64)>, ClientError> {
+        let classes = self.client.send(AllClasses)?;
+        let ids = classes.iter().map(|class| *class.type_id).collect();
+        let counts = self.client.send(InstanceCounts::new(ids))?;
+
+        let mut histogram: Vec<_> = classes
+            .into_iter()
+            .map(|class| class.signature)
+            .zip(counts)
+            .collect();
+        histogram.sort_by(|(a_sig, a), (b_sig, b)| b.cmp(a).then_with(|| a_sig.cmp(b_sig)));
+        histogram.truncate(top_n);
+        Ok(histogram)
+    }
+
+    /// Fetches the signature, the status and the source file of each of the
+    /// given types, in the same order, for e.g. a class browser.
+    ///
+    /// Instead of three round-trips per type, each kind of command is sent
+    /// for all of the types at once with
+    /// [send_all](SharedClient::send_all).
+    pub fn class_overview(
+        &self,
+        types: &[ReferenceTypeID],
+    ) -> Result<Vec<ClassOverview>, ClientError> {
+        let signatures = self
+            .client
+            .send_all(types.iter().map(|&id| Signature::new(id)))?;
+        let statuses = self
+            .client
+            .send_all(types.iter().map(|&id| Status::new(id)))?;
+        let source_files = self
+            .client
+            .send_all(types.iter().map(|&id| SourceFile::new(id)))?;
+
+        signatures
+            .into_iter()
+            .zip(statuses)
+            .zip(source_files)
+            .map(|((signature, status), source_file)| {
+                Ok(ClassOverview {
+                    signature: signature?,
+                    status: status?,
+                    source_file: match source_file {
+                        Ok(source_file) => Some(source_file),
+                        Err(ClientError::HostError(ErrorCode::AbsentInformation)) => None,
+                        Err(e) => return Err(e),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    /// Renders the given value for display: the contents of the strings,
+    /// and the [Display](std::fmt::Display) form for everything else.
+    pub fn value_to_string(&self, value: &Value) -> Result<String, ClientError> {
+        let Value::Object(id) = *value else {
+            return Ok(value.to_string());
+        };
+        if value.is_null() {
+            return Ok(value.to_string());
+        }
+        let object = ObjectReference::new(self.client.clone(), id);
+        if object.reference_type()?.signature()? == "Ljava/lang/String;" {
+            self.client.send(string_reference::Value::new(id))
+        } else {
+            Ok(value.to_string())
+        }
+    }
+
+    /// Describes the given exception the way `Throwable.toString` does, e.g.
+    /// `java.lang.NumberFormatException: For input string: "x"`, or just the
+    /// class name if it has no message.
+    ///
+    /// The message is obtained by invoking `getMessage`, so the thread must be
+    /// suspended by an event, e.g. the thread of the invocation that threw.
+    pub fn describe_exception(
+        &self,
+        exception: TaggedObjectID,
+        thread: &ThreadReference,
+    ) -> Result<String, ClientError> {
+        let object = ObjectReference::new(self.client.clone(), *exception);
+        let signature = object.reference_type()?.signature()?;
+        let class_name = match signature.parse::<FieldType>() {
+            Ok(class) => class.java_name(),
+            Err(_) => signature,
+        };
+
+        let throwable = self
+            .client
+            .send(ClassesBySignature::new("Ljava/lang/Throwable;"))?
+            .pop();
+        let Some(TaggedReferenceTypeID::Class(throwable)) = throwable.map(|t| t.type_id) else {
+            return Err(bad_exception("Throwable is not loaded"));
+        };
+        let Some(get_message) =
+            ClassType::new(self.client.clone(), throwable).method_by_name("getMessage")?
+        else {
+            return Err(bad_exception("Throwable has no getMessage"));
+        };
+
+        // not nonvirtual, the subclasses might override it
+        let reply = self.client.send(InvokeMethod::new(
+            object.id(),
+            thread.id(),
+            throwable,
+            get_message.id(),
+            vec![],
+            InvokeOptions::empty(),
+        ))?;
+        match reply {
+            InvokeMethodReply::Value(message) if message.is_null() => Ok(class_name),
+            InvokeMethodReply::Value(Value::Object(message)) => {
+                let message = self.client.send(string_reference::Value::new(message))?;
+                Ok(format!("{class_name}: {message}"))
+            }
+            _ => Err(bad_exception("getMessage of the exception threw")),
+        }
+    }
+
+    /// Returns the length of the array the given value refers to, or `None`
+    /// if it's not an array (or is null), see
+    /// [Length](array_reference::Length).
+    pub fn value_array_length(&self, value: &Value) -> Result<Option<u32>, ClientError> {
+        let Value::Object(id) = *value else {
+            return Ok(None);
+        };
+        if value.is_null() {
+            return Ok(None);
+        }
+        let object = ObjectReference::new(self.client.clone(), id);
+        let TaggedReferenceTypeID::Array(_) = object.reference_type()?.id() else {
+            return Ok(None);
+        };
+        // SAFETY: the runtime type of the object was just checked to be an array
+        let array = unsafe { ArrayID::new(id) };
+        let length = self.client.send(array_reference::Length::new(array))?;
+        Ok(Some(length as u32))
+    }
+
+    /// Returns all the threads that are currently running, see [AllThreads].
+    pub fn all_threads(&self) -> Result<Vec<ThreadReference>, ClientError> {
+        let threads = self.client.send(AllThreads)?;
+        Ok(threads
+            .into_iter()
+            .map(|id| ThreadReference::new(self.client.clone(), id))
+            .collect())
+    }
+
+    /// Collects the name, status and stack trace of every thread, akin to
+    /// `jstack`.
+    ///
+    /// The whole VM is suspended while doing so, so that all the threads are
+    /// captured at the same moment, and then resumed - if it was suspended
+    /// already, it stays that way. The threads that die in the meantime are
+    /// left out.
+    pub fn thread_dump(&self) -> Result<Vec<ThreadInfo>, ClientError> {
+        self.client.send(Suspend)?;
+        let dump = self.collect_thread_dump();
+        // resume even if collecting has failed, its error takes precedence
+        let resumed = self.client.send(Resume);
+        let dump = dump?;
+        resumed?;
+        Ok(dump)
+    }
+
+    fn collect_thread_dump(&self) -> Result<Vec<ThreadInfo>, ClientError> {
+        let mut dump = Vec::new();
+        for thread in self.all_threads()? {
+            match ThreadInfo::collect(thread) {
+                Ok(info) => dump.push(info),
+                Err(ClientError::HostError(ErrorCode::InvalidThread)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(dump)
+    }
+
+    /// Returns the name and the signature of the method that the location is
+    /// in, e.g. to render a stack frame.
+    ///
+    /// The methods of each type are fetched once and then cached, until the
+    /// type is redefined. Fails with the
+    /// [InvalidMethodid](ErrorCode::InvalidMethodid) host error if the type
+    /// has no such method.
+    pub fn method_info(&self, location: &Location) -> Result<(String, String), ClientError> {
+        let methods = self.client.methods(*location.reference_id)?;
+        methods
+            .iter()
+            .find(|method| method.method_id == location.method_id)
+            .map(|method| (method.name.clone(), method.signature.clone()))
+            .ok_or(ClientError::HostError(ErrorCode::InvalidMethodid))
+    }
+
+    /// Describes the given location for display: the declaring type, the
+    /// method, and the source file and line if the target VM knows them, see
+    /// [SourcePosition].
+    ///
+    /// Missing debug information just leaves the source file or the line
+    /// out, as opposed to the unknown method, see
+    /// [method_info](VM::method_info).
+    pub fn describe_location(&self, location: &Location) -> Result<LocationInfo, ClientError> {
+        let class_signature = self.client.send(Signature::new(*location.reference_id))?;
+        let (method_name, _) = self.method_info(location)?;
+        let SourcePosition { source_file, line } = SourcePosition::resolve(&self.client, location)?;
+        Ok(LocationInfo {
+            class_signature,
+            method_name,
+            source_file,
+            line,
+        })
+    }
+
+    /// Returns all the modules in the target VM, see [AllModules].
+    ///
+    /// The target VM must be Java 9 or newer.
+    pub fn modules(&self) -> Result<Vec<ModuleReference>, ClientError> {
+        let modules = self.client.send(AllModules)?;
+        Ok(modules
+            .into_iter()
+            .map(|id| ModuleReference::new(self.client.clone(), id))
+            .collect())
+    }
+
+    /// Returns the thread groups that have no parent, the roots of the thread
+    /// group tree, see [TopLevelThreadGroups].
+    pub fn top_level_thread_groups(&self) -> Result<Vec<ThreadGroupReference>, ClientError> {
+        let groups = self.client.send(TopLevelThreadGroups)?;
+        Ok(groups
+            .into_iter()
+            .map(|id| ThreadGroupReference::new(self.client.clone(), id))
+            .collect())
+    }
+
+    /// Returns the first running thread with the given name.
+    ///
+    /// Thread names are not unique, so if there are several threads with that
+    /// name this is just the first one in the [AllThreads] order, see
+    /// [threads_by_name](VM::threads_by_name) to get all of them.
+    pub fn thread_by_name(&self, name: &str) -> Result<Option<ThreadReference>, ClientError> {
+        Ok(self.threads_by_name(name)?.into_iter().next())
+    }
+
+    /// Returns all the running threads with the given name.
+    pub fn threads_by_name(&self, name: &str) -> Result<Vec<ThreadReference>, ClientError> {
+        let mut threads = Vec::new();
+        for thread in self.all_threads()? {
+            match thread.name() {
+                Ok(thread_name) if thread_name == name => threads.push(thread),
+                Ok(_) => {}
+                // the thread might have died since the list was fetched
+                Err(ClientError::HostError(
+                    ErrorCode::InvalidThread | ErrorCode::InvalidObject,
+                )) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(threads)
+    }
+
+    /// Sets the [ThreadStart](crate::enums::EventKind::ThreadStart) and
+    /// [ThreadDeath](crate::enums::EventKind::ThreadDeath) event requests and
+    /// returns a subscription that yields the started and dead threads.
+    ///
+    /// The events do not suspend anything, so a thread might already be gone
+    /// by the time its start is received.
+    pub fn subscribe_threads(&self) -> Result<ThreadSubscription, ClientError> {
+        ThreadSubscription::subscribe(self.client.clone())
+    }
+
+    /// Starts building an event request of the given kind.
+    pub fn event_request(&self, event_kind: EventKind) -> EventRequestBuilder {
+        EventRequestBuilder::new(self.client.clone(), event_kind)
+    }
+
+    /// Returns the event requests that are currently set, in the order they
+    /// were set.
+    ///
+    /// JDWP has no command for this, so only the requests that were set and
+    /// cleared through this [VM]'s [SharedClient] are known - the ones sent
+    /// through the [locked](SharedClient::lock) raw client are not tracked.
+    pub fn active_requests(&self) -> Vec<ActiveRequest> {
+        self.client.0.requests.lock().unwrap().clone()
+    }
+
+    /// Returns the capabilities of the target VM, see [Capabilities].
+    ///
+    /// They do not change while connected, so they are only fetched once.
+    pub fn capabilities(&self) -> Result<Capabilities, ClientError> {
+        self.client.capabilities()
+    }
+
+    /// Installs new definitions of the given classes or interfaces, see
+    /// [RedefineClasses].
+    ///
+    /// Fails with [IllegalState](ClientError::IllegalState) if the target VM
+    /// cannot redefine classes at all, and with
+    /// [IllegalArgument](ClientError::IllegalArgument) for the array or
+    /// primitive types or if some bytes are obviously not a class file,
+    /// before sending anything.
+    /// The class files that the target VM rejects come back as the matching
+    /// [RedefineError] instead of the raw [ErrorCode].
+    pub fn redefine_classes(
+        &self,
+        classes: &[(ReferenceType, &[u8])],
+    ) -> Result<(), RedefineError> {
+        if self.redefine_capability()? == RedefineCapability::None {
+            return Err(ClientError::IllegalState(
+                "the target VM cannot redefine classes".to_owned(),
+            )
+            .into());
+        }
+        let mut redefining = Vec::with_capacity(classes.len());
+        for (ref_type, bytes) in classes {
+            if !matches!(
+                ref_type.id(),
+                TaggedReferenceTypeID::Class(_) | TaggedReferenceTypeID::Interface(_)
+            ) {
+                return Err(ClientError::IllegalArgument(format!(
+                    "{} is not a class or an interface",
+                    ref_type.signature()?
+                ))
+                .into());
+            }
+            if !bytes.starts_with(&CLASS_FILE_MAGIC) {
+                return Err(ClientError::IllegalArgument(format!(
+                    "the new definition of {} is not a class file",
+                    ref_type.signature()?
+                ))
+                .into());
+            }
+            redefining.push(RedefiningClass::new(*ref_type.id(), bytes.to_vec()));
+        }
+        match self.client.send(RedefineClasses::new(redefining)) {
+            Err(ClientError::HostError(code)) => {
+                Err(RedefineError::from_error_code(code)
+                    .unwrap_or(ClientError::HostError(code).into()))
+            }
+            result => Ok(result?),
+        }
+    }
+
+    /// Returns how much the target VM allows to change when redefining
+    /// classes, see [RedefineCapability].
+    pub fn redefine_capability(&self) -> Result<RedefineCapability, ClientError> {
+        let capabilities = self.capabilities()?;
+        Ok(RedefineCapability::from_flags(
+            capabilities.can_redefine_classes(),
+            capabilities.can_add_method(),
+            capabilities.can_unrestrictedly_redefine_classes(),
+        ))
+    }
+}
+
+/// The basic information about a type, see [VM::class_overview].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassOverview {
+    /// The JNI signature of the type
+    pub signature: String,
+    /// The current status of the type
+    pub status: ClassStatus,
+    /// The source file name, `None` if it's not known (e.g. for arrays or the
+    /// classes compiled without debug information)
+    pub source_file: Option<String>,
+}
+
+/// What kind of changes the target VM supports when redefining classes,
+/// combined from the three related capabilities.
+///
+/// The variants are ordered, so e.g. `capability >=
+/// RedefineCapability::AddMethod` can be used to check that methods can be
+/// added.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RedefineCapability {
+    /// Classes cannot be redefined at all.
+    None,
+    /// Only the method bodies can be changed, the schema (fields, methods,
+    /// hierarchy etc.) must stay the same.
+    Restricted,
+    /// Same as [Restricted](RedefineCapability::Restricted), but methods can
+    /// also be added.
+    AddMethod,
+    /// Classes can be redefined in arbitrary ways.
+    Unrestricted,
+}
+
+impl RedefineCapability {
+    fn from_flags(
+        can_redefine_classes: bool,
+        can_add_method: bool,
+        can_unrestrictedly_redefine_classes: bool,
+    ) -> Self {
+        // the other two are meaningless without the base capability
+        if !can_redefine_classes {
+            Self::None
+        } else if can_unrestrictedly_redefine_classes {
+            Self::Unrestricted
+        } else if can_add_method {
+            Self::AddMethod
+        } else {
+            Self::Restricted
+        }
+    }
+}
+
+/// Every class file starts with `0xCAFEBABE`.
+const CLASS_FILE_MAGIC: [u8; 4] = [0xCA, 0xFE, 0xBA, 0xBE];
+
+/// Why the target VM rejected the new class definitions, see
+/// [VM::redefine_classes].
+#[derive(Debug, Error)]
+pub enum RedefineError {
+    #[error("{}", ErrorCode::InvalidClassFormat)]
+    InvalidClassFormat,
+    #[error("{}", ErrorCode::CircularClassDefinition)]
+    CircularClassDefinition,
+    #[error("{}", ErrorCode::FailsVerification)]
+    FailsVerification,
+    #[error("{}", ErrorCode::UnsupportedVersion)]
+    UnsupportedVersion,
+    #[error("{}", ErrorCode::NamesDontMatch)]
+    NamesDontMatch,
+    /// The changes need the [AddMethod](RedefineCapability::AddMethod)
+    /// capability
+    #[error("{}", ErrorCode::AddMethodNotImplemented)]
+    AddMethodNotImplemented,
+    /// This and the ones below need the
+    /// [Unrestricted](RedefineCapability::Unrestricted) capability
+    #[error("{}", ErrorCode::SchemaChangeNotImplemented)]
+    SchemaChangeNotImplemented,
+    #[error("{}", ErrorCode::HierarchyChangeNotImplemented)]
+    HierarchyChangeNotImplemented,
+    #[error("{}", ErrorCode::DeleteMethodNotImplemented)]
+    DeleteMethodNotImplemented,
+    #[error("{}", ErrorCode::ClassModifiersChangeNotImplemented)]
+    ClassModifiersChangeNotImplemented,
+    #[error("{}", ErrorCode::MethodModifiersChangeNotImplemented)]
+    MethodModifiersChangeNotImplemented,
+    /// Anything else, including the other host errors
+    #[error(transparent)]
+    Client(#[from] ClientError),
+}
+
+impl RedefineError {
+    fn from_error_code(code: ErrorCode) -> Option<Self> {
+        Some(match code {
+            ErrorCode::InvalidClassFormat => Self::InvalidClassFormat,
+            ErrorCode::CircularClassDefinition => Self::CircularClassDefinition,
+            ErrorCode::FailsVerification => Self::FailsVerification,
+            ErrorCode::UnsupportedVersion => Self::UnsupportedVersion,
+            ErrorCode::NamesDontMatch => Self::NamesDontMatch,
+            ErrorCode::AddMethodNotImplemented => Self::AddMethodNotImplemented,
+            ErrorCode::SchemaChangeNotImplemented => Self::SchemaChangeNotImplemented,
+            ErrorCode::HierarchyChangeNotImplemented => Self::HierarchyChangeNotImplemented,
+            ErrorCode::DeleteMethodNotImplemented => Self::DeleteMethodNotImplemented,
+            ErrorCode::ClassModifiersChangeNotImplemented => {
+                Self::ClassModifiersChangeNotImplemented
+            }
+            ErrorCode::MethodModifiersChangeNotImplemented => {
+                Self::MethodModifiersChangeNotImplemented
+            }
+            _ => return None,
+        })
+    }
+}
+
+/// The start of the target VM, see [VM::await_start].
+#[derive(Debug)]
+pub struct VmStartInfo {
+    /// The initial thread, the one that runs the main method
+    pub thread: ThreadReference,
+    /// The suspend policy the target VM chose for the
+    /// [VmStart](crate::commands::event::VmStart) event
+    pub suspend_policy: SuspendPolicy,
+    /// Whether all the threads were suspended by the event, so that the
+    /// target VM has to be resumed to let the main method run
+    pub vm_suspended: bool,
+    /// The events that arrived before the
+    /// [VmStart](crate::commands::event::VmStart) event or together with it,
+    /// in order
+    pub other_events: Vec<Composite>,
+}
+
+/// Parses the feature version out of the `java.version` property, handling
+/// both the legacy `1.x` and the modern versioning schemes.
+fn parse_java_version(version: &str) -> Option<u32> {
+    let mut parts = version.split(|ch: char| !ch.is_ascii_digit());
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        v => Some(v),
+    }
+}
+
+fn bad_exception(message: &str) -> ClientError {
+    io::Error::new(ErrorKind::InvalidData, message).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn java_version() {
+        assert_eq!(parse_java_version("1.8.0_292"), Some(8));
+        assert_eq!(parse_java_version("1.7.0"), Some(7));
+        assert_eq!(parse_java_version("11.0.2"), Some(11));
+        assert_eq!(parse_java_version("17"), Some(17));
+        assert_eq!(parse_java_version("17-ea"), Some(17));
+        assert_eq!(parse_java_version("21.0.1+12-LTS"), Some(21));
+
+        assert_eq!(parse_java_version(""), None);
+        assert_eq!(parse_java_version("1"), None);
+        assert_eq!(parse_java_version("unknown"), None);
+    }
+
+    #[test]
+    fn redefine_capability() {
+        use RedefineCapability::*;
+
+        assert_eq!(RedefineCapability::from_flags(false, false, false), None);
+        assert_eq!(RedefineCapability::from_flags(false, true, true), None);
+        assert_eq!(
+            RedefineCapability::from_flags(true, false, false),
+            Restricted
+        );
+        assert_eq!(RedefineCapability::from_flags(true, true, false), AddMethod);
+        assert_eq!(
+            RedefineCapability::from_flags(true, false, true),
+            Unrestricted
+        );
+        assert_eq!(
+            RedefineCapability::from_flags(true, true, true),
+            Unrestricted
+        );
+
+        assert!(Unrestricted > AddMethod && AddMethod > Restricted && Restricted > None);
+    }
+}
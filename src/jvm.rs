@@ -1,8 +1,9 @@
-use crate::{JdwpReadable, JdwpReader, JdwpWritable, JdwpWriter};
+use crate::{enums::Tag, JdwpReadable, JdwpReader, JdwpWritable, JdwpWriter};
 use bitflags::bitflags;
 use std::{
     io::{self, Read, Write},
     rc::Rc,
+    str::FromStr,
 };
 use thiserror::Error;
 
@@ -11,6 +12,7 @@ use byteorder::{BigEndian, ReadBytesExt};
 /// This represents an item in the constant pool table.
 #[repr(u8)]
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConstantPoolItem {
     /// Used as a placeholder taking the second slot for longs and doubles, as
     /// well as the 0th slot in the vec of constant pool items for indexing to
@@ -185,22 +187,69 @@ impl ConstantPoolItem {
     ///
     /// A stub is also added after each long and double, so that the resulting
     /// vector completely mimics the constant pool indexing.
-    pub fn read_all(
-        count: u32,
-        mut read: impl Read,
-    ) -> Result<Vec<Self>, ConstantPoolParsingError> {
-        let mut result = Vec::with_capacity(count as usize);
-        result.push(ConstantPoolItem::Stub);
-
-        while (result.len() as u32) < count {
-            match Self::read(result.len() as u32, &mut read)? {
-                item @ (ConstantPoolItem::Long(_) | ConstantPoolItem::Double(_)) => {
-                    result.extend([item, ConstantPoolItem::Stub])
-                }
-                item => result.push(item),
+    pub fn read_all(count: u32, read: impl Read) -> Result<Vec<Self>, ConstantPoolParsingError> {
+        Self::iter(count, read).collect()
+    }
+
+    /// Reads the constant pool items one by one, without collecting them,
+    /// e.g. to scan a large constant pool once.
+    ///
+    /// The items are the same as the ones returned by
+    /// [read_all](ConstantPoolItem::read_all), stubs included, so the
+    /// position of an item is still its constant pool index.
+    /// The iteration stops after the first error.
+    pub fn iter<R: Read>(count: u32, read: R) -> ConstantPoolIter<R> {
+        ConstantPoolIter {
+            read,
+            count,
+            index: 0,
+            stub_next: true,
+        }
+    }
+}
+
+/// A lazy reader of the constant pool items, see [ConstantPoolItem::iter].
+#[derive(Debug)]
+pub struct ConstantPoolIter<R> {
+    read: R,
+    count: u32,
+    index: u32,
+    // set for the 0th index and after the longs and doubles
+    stub_next: bool,
+}
+
+impl<R: Read> Iterator for ConstantPoolIter<R> {
+    type Item = Result<ConstantPoolItem, ConstantPoolParsingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.count {
+            return None;
+        }
+        let index = self.index;
+        self.index += 1;
+
+        if self.stub_next {
+            self.stub_next = false;
+            return Some(Ok(ConstantPoolItem::Stub));
+        }
+        match ConstantPoolItem::read(index, &mut self.read) {
+            Ok(item) => {
+                self.stub_next = matches!(
+                    item,
+                    ConstantPoolItem::Long(_) | ConstantPoolItem::Double(_)
+                );
+                Some(Ok(item))
+            }
+            Err(e) => {
+                // the rest cannot be read after a bad item
+                self.index = self.count;
+                Some(Err(e))
             }
         }
-        Ok(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some((self.count.saturating_sub(self.index)) as usize))
     }
 }
 
@@ -212,6 +261,7 @@ pub enum IndexableItem {
     NameAndType,
     Fieldref,
     Methodref,
+    MethodHandle,
 }
 
 /// Error that can occur when resolving a constant pool index.
@@ -223,12 +273,14 @@ pub struct ResolutionError {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NameAndType {
     pub name: Rc<str>,
     pub descriptor: Rc<str>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ref {
     pub class: Rc<str>,
     pub name: Rc<str>,
@@ -247,6 +299,7 @@ impl Ref {
 
 #[repr(u8)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReferenceKind {
     GetField = 1,
     GetStatic = 2,
@@ -261,6 +314,7 @@ pub enum ReferenceKind {
 
 /// This represents a constant pool structure with all the indexes resolved
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ConstantPoolValue {
     Utf8(Rc<str>),
     Integer(i32),
@@ -339,6 +393,49 @@ impl ConstantPoolValue {
         }) -> Ref;
     }
 
+    /// Resolves the method handle at the given index along with the field or
+    /// method it references, e.g. the handles that the `BootstrapMethods`
+    /// attribute points to.
+    // same as below, the macro doesn't do struct variants and the reference
+    // can be to any of the three ref kinds
+    pub fn resolve_method_handle(
+        pool: &mut [Option<ConstantPoolValue>],
+        items: &[ConstantPoolItem],
+        index: u16,
+    ) -> Result<ResolvedMethodHandle, ResolutionError> {
+        let resolved = pool.get(index as usize).and_then(Option::as_ref);
+        if let Some(handle) = resolved.and_then(Self::as_method_handle) {
+            return Ok(handle);
+        }
+        if let (
+            None,
+            Some(ConstantPoolItem::MethodHandle {
+                reference_kind,
+                reference_index,
+            }),
+        ) = (resolved, items.get(index as usize))
+        {
+            use ReferenceKind::*;
+
+            let reference = match reference_kind {
+                GetField | GetStatic | PutField | PutStatic => {
+                    Self::resolve_fieldref(pool, items, *reference_index)?
+                }
+                _ => Self::resolve_methodref(pool, items, *reference_index)?,
+            };
+            let handle = ResolvedMethodHandle::new(*reference_kind, reference.clone());
+            pool[index as usize] = Some(ConstantPoolValue::MethodHandle {
+                reference_kind: *reference_kind,
+                reference,
+            });
+            return Ok(handle);
+        }
+        Err(ResolutionError {
+            index,
+            item: IndexableItem::MethodHandle,
+        })
+    }
+
     // this is different from other resolve methods because we bunch in the
     // interface methodref
     fn resolve_methodref(
@@ -495,23 +592,8 @@ impl ConstantPoolValue {
                         descriptor: Self::resolve_string(&mut pool, items, *descriptor_index)?,
                     }));
                 }
-                Item::MethodHandle {
-                    reference_kind,
-                    reference_index,
-                } => {
-                    use ReferenceKind::*;
-
-                    let reference = match reference_kind {
-                        GetField | GetStatic | PutField | PutStatic => {
-                            Self::resolve_fieldref(&mut pool, items, *reference_index)?
-                        }
-                        _ => Self::resolve_methodref(&mut pool, items, *reference_index)?,
-                    };
-
-                    pool[index] = Some(Value::MethodHandle {
-                        reference_kind: *reference_kind,
-                        reference,
-                    });
+                Item::MethodHandle { .. } => {
+                    Self::resolve_method_handle(&mut pool, items, index as u16)?;
                 }
                 Item::MethodType(descriptor_index) => {
                     pool[index] = Some(Value::MethodType(Self::resolve_string(
@@ -559,6 +641,116 @@ impl ConstantPoolValue {
         // only stubs should be None, and flatten will remove them
         Ok(pool.into_iter().flatten().collect())
     }
+
+    /// Returns the [Dynamic](ConstantPoolValue::Dynamic) or
+    /// [InvokeDynamic](ConstantPoolValue::InvokeDynamic) entry this value is,
+    /// if any.
+    pub fn as_dynamic(&self) -> Option<DynamicEntry> {
+        let (kind, bootstrap_method_attr_index, name, descriptor) = match self {
+            ConstantPoolValue::Dynamic {
+                bootstrap_method_attr_index,
+                name,
+                descriptor,
+            } => (
+                DynamicKind::Dynamic,
+                bootstrap_method_attr_index,
+                name,
+                descriptor,
+            ),
+            ConstantPoolValue::InvokeDynamic {
+                bootstrap_method_attr_index,
+                name,
+                descriptor,
+            } => (
+                DynamicKind::InvokeDynamic,
+                bootstrap_method_attr_index,
+                name,
+                descriptor,
+            ),
+            _ => return None,
+        };
+        Some(DynamicEntry {
+            kind,
+            bootstrap_method_attr_index: *bootstrap_method_attr_index,
+            name: name.clone(),
+            descriptor: descriptor.clone(),
+        })
+    }
+
+    /// Returns the [MethodHandle](ConstantPoolValue::MethodHandle) this value
+    /// is, if any.
+    pub fn as_method_handle(&self) -> Option<ResolvedMethodHandle> {
+        match self {
+            ConstantPoolValue::MethodHandle {
+                reference_kind,
+                reference,
+            } => Some(ResolvedMethodHandle::new(
+                *reference_kind,
+                reference.clone(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Lists all the [Dynamic](ConstantPoolValue::Dynamic) and
+    /// [InvokeDynamic](ConstantPoolValue::InvokeDynamic) entries of a
+    /// resolved constant pool, e.g. to find the lambdas and the string
+    /// concatenations of a class.
+    pub fn dynamic_entries(pool: &[Self]) -> Vec<DynamicEntry> {
+        pool.iter().filter_map(Self::as_dynamic).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DynamicKind {
+    /// A dynamically-computed constant, `CONSTANT_Dynamic`.
+    Dynamic,
+    /// A call site of the `invokedynamic` instruction,
+    /// `CONSTANT_InvokeDynamic`.
+    InvokeDynamic,
+}
+
+/// A constant pool entry that is computed by a bootstrap method, see
+/// [ConstantPoolValue::dynamic_entries].
+///
+/// The bootstrap methods themselves are stored in the `BootstrapMethods`
+/// attribute of the class file, which JDWP does not expose, so resolving
+/// the [index](DynamicEntry::bootstrap_method_attr_index) into the actual
+/// bootstrap method and its arguments requires the class file from
+/// elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DynamicEntry {
+    pub kind: DynamicKind,
+    /// The index into the `bootstrap_methods` array of the `BootstrapMethods`
+    /// class attribute.
+    pub bootstrap_method_attr_index: u16,
+    pub name: Rc<str>,
+    pub descriptor: Rc<str>,
+}
+
+/// A constant pool method handle with the field or method it references
+/// resolved, see [ConstantPoolValue::resolve_method_handle].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ResolvedMethodHandle {
+    pub kind: ReferenceKind,
+    /// The internal name of the class that declares the field or method.
+    pub owner: Rc<str>,
+    pub name: Rc<str>,
+    pub descriptor: Rc<str>,
+}
+
+impl ResolvedMethodHandle {
+    fn new(kind: ReferenceKind, reference: Ref) -> Self {
+        Self {
+            kind,
+            owner: reference.class,
+            name: reference.name,
+            descriptor: reference.descriptor,
+        }
+    }
 }
 
 // Access flags are not specified in the JDWP protocol, so they are in the JVM
@@ -568,6 +760,7 @@ impl ConstantPoolValue {
 
 bitflags! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct TypeModifiers: u32 {
         /// Declared public; may be accessed from outside its package.
         const PUBLIC = 0x0001;
@@ -590,6 +783,7 @@ bitflags! {
 
 bitflags! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct FieldModifiers: u32 {
         /// Declared public; may be accessed from outside its package.
         const PUBLIC = 0x0001;
@@ -616,6 +810,7 @@ bitflags! {
 
 bitflags! {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct MethodModifiers: u32 {
         /// Declared public; may be accessed from outside its package.
         const PUBLIC = 0x0001;
@@ -663,3 +858,562 @@ macro_rules! jdwp_access_flags {
 }
 
 jdwp_access_flags![TypeModifiers, FieldModifiers, MethodModifiers];
+
+/// The type of a field, local variable or method argument, as described by a
+/// field descriptor (JVMS §4.3.2), e.g. `I` or `[Ljava/lang/String;`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FieldType {
+    Byte,
+    Char,
+    Double,
+    Float,
+    Int,
+    Long,
+    Short,
+    Boolean,
+    /// An instance of the class with the given binary name, e.g.
+    /// `java/lang/String`.
+    Object(String),
+    /// An array with the given component type.
+    Array(Box<FieldType>),
+}
+
+#[derive(Debug, Error)]
+#[error("Invalid descriptor: {0:?}")]
+pub struct InvalidDescriptor(pub String);
+
+impl FieldType {
+    /// The number of local variable slots a value of this type occupies:
+    /// longs and doubles take two slots and everything else takes one.
+    pub fn slot_size(&self) -> u32 {
+        match self {
+            FieldType::Long | FieldType::Double => 2,
+            _ => 1,
+        }
+    }
+
+    /// The tag of the values of this type.
+    pub fn tag(&self) -> Tag {
+        match self {
+            FieldType::Byte => Tag::Byte,
+            FieldType::Char => Tag::Char,
+            FieldType::Double => Tag::Double,
+            FieldType::Float => Tag::Float,
+            FieldType::Int => Tag::Int,
+            FieldType::Long => Tag::Long,
+            FieldType::Short => Tag::Short,
+            FieldType::Boolean => Tag::Boolean,
+            FieldType::Object(_) => Tag::Object,
+            FieldType::Array(_) => Tag::Array,
+        }
+    }
+
+    /// The name of this type as written in the Java source code, e.g. `int`,
+    /// `java.util.List` or `java.lang.String[]`.
+    ///
+    /// The nested classes keep their binary names, e.g. `java.util.Map$Entry`.
+    pub fn java_name(&self) -> String {
+        match self {
+            FieldType::Byte => "byte".to_owned(),
+            FieldType::Char => "char".to_owned(),
+            FieldType::Double => "double".to_owned(),
+            FieldType::Float => "float".to_owned(),
+            FieldType::Int => "int".to_owned(),
+            FieldType::Long => "long".to_owned(),
+            FieldType::Short => "short".to_owned(),
+            FieldType::Boolean => "boolean".to_owned(),
+            FieldType::Object(name) => name.replace('/', "."),
+            FieldType::Array(component) => format!("{}[]", component.java_name()),
+        }
+    }
+
+    /// Parses the argument types out of a method descriptor (JVMS §4.3.3),
+    /// e.g. `[Int, Long]` out of `(IJ)V`.
+    ///
+    /// Only the arguments are parsed, see [MethodSignature] for the whole
+    /// descriptor.
+    pub fn parse_arguments(method_descriptor: &str) -> Result<Vec<Self>, InvalidDescriptor> {
+        match Self::parse_argument_prefix(method_descriptor) {
+            Some((arguments, _)) => Ok(arguments),
+            None => Err(InvalidDescriptor(method_descriptor.to_owned())),
+        }
+    }
+
+    /// Parses the parenthesized arguments, returning the rest after the
+    /// closing parenthesis.
+    fn parse_argument_prefix(method_descriptor: &str) -> Option<(Vec<Self>, &str)> {
+        let mut rest = method_descriptor.strip_prefix('(')?;
+        let mut arguments = Vec::new();
+        while !rest.starts_with(')') {
+            let (argument, tail) = Self::parse_prefix(rest)?;
+            arguments.push(argument);
+            rest = tail;
+        }
+        Some((arguments, &rest[1..]))
+    }
+
+    fn parse_prefix(descriptor: &str) -> Option<(Self, &str)> {
+        let mut chars = descriptor.chars();
+        let field_type = match chars.next()? {
+            'B' => FieldType::Byte,
+            'C' => FieldType::Char,
+            'D' => FieldType::Double,
+            'F' => FieldType::Float,
+            'I' => FieldType::Int,
+            'J' => FieldType::Long,
+            'S' => FieldType::Short,
+            'Z' => FieldType::Boolean,
+            'L' => {
+                let (class_name, rest) = chars.as_str().split_once(';')?;
+                return Some((FieldType::Object(class_name.to_owned()), rest));
+            }
+            '[' => {
+                let (component, rest) = Self::parse_prefix(chars.as_str())?;
+                return Some((FieldType::Array(Box::new(component)), rest));
+            }
+            _ => return None,
+        };
+        Some((field_type, chars.as_str()))
+    }
+}
+
+impl FromStr for FieldType {
+    type Err = InvalidDescriptor;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::parse_prefix(s) {
+            Some((field_type, "")) => Ok(field_type),
+            _ => Err(InvalidDescriptor(s.to_owned())),
+        }
+    }
+}
+
+/// The parameter and return types of a method, as described by a method
+/// descriptor (JVMS §4.3.3), e.g. `(ILjava/lang/String;)V`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MethodSignature {
+    pub params: Vec<FieldType>,
+    /// The return type, `None` for void methods.
+    pub ret: Option<FieldType>,
+}
+
+impl MethodSignature {
+    /// The number of local variable slots the arguments occupy, not counting
+    /// `this` of the non-static methods, see [FieldType::slot_size].
+    pub fn argument_slots(&self) -> u32 {
+        self.params.iter().map(FieldType::slot_size).sum()
+    }
+}
+
+impl FromStr for MethodSignature {
+    type Err = InvalidDescriptor;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidDescriptor(s.to_owned());
+        let (params, ret) = FieldType::parse_argument_prefix(s).ok_or_else(invalid)?;
+        let ret = match ret {
+            "V" => None,
+            ret => Some(ret.parse().map_err(|_| invalid())?),
+        };
+        Ok(MethodSignature { params, ret })
+    }
+}
+
+/// A reference type as written in a generic signature (JVMS §4.7.9.1), e.g.
+/// `Ljava/util/List<TT;>;`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GenericType {
+    /// A primitive type, which can only be an array component here.
+    Primitive(FieldType),
+    /// A class type with its type arguments.
+    ///
+    /// The inner classes are named by their binary names, e.g.
+    /// `java/util/Map$Entry`, with only the type arguments of the innermost
+    /// class kept.
+    Class {
+        name: String,
+        type_arguments: Vec<TypeArgument>,
+    },
+    /// A type variable, e.g. `T`.
+    TypeVariable(String),
+    /// An array with the given component type.
+    Array(Box<GenericType>),
+}
+
+impl GenericType {
+    fn parse_prefix(signature: &str) -> Option<(Self, &str)> {
+        let mut chars = signature.chars();
+        match chars.next()? {
+            'L' => {
+                let mut rest = chars.as_str();
+                let mut name = String::new();
+                let mut type_arguments = Vec::new();
+                loop {
+                    let (identifier, tail) = parse_identifier(rest)?;
+                    name.push_str(identifier);
+                    rest = tail;
+                    if let Some(tail) = rest.strip_prefix('<') {
+                        (type_arguments, rest) = TypeArgument::parse_list(tail)?;
+                    }
+                    let mut chars = rest.chars();
+                    match chars.next()? {
+                        '/' => name.push('/'),
+                        '.' => name.push('$'),
+                        ';' => {
+                            let class = GenericType::Class {
+                                name,
+                                type_arguments,
+                            };
+                            return Some((class, chars.as_str()));
+                        }
+                        _ => return None,
+                    }
+                    rest = chars.as_str();
+                }
+            }
+            'T' => {
+                let (name, rest) = parse_identifier(chars.as_str())?;
+                let rest = rest.strip_prefix(';')?;
+                Some((GenericType::TypeVariable(name.to_owned()), rest))
+            }
+            '[' => {
+                let rest = chars.as_str();
+                let (component, rest) = match Self::parse_prefix(rest) {
+                    Some(component) => component,
+                    None => match FieldType::parse_prefix(rest)? {
+                        (FieldType::Object(_) | FieldType::Array(_), _) => return None,
+                        (primitive, rest) => (GenericType::Primitive(primitive), rest),
+                    },
+                };
+                Some((GenericType::Array(Box::new(component)), rest))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A type argument of a [GenericType::Class].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeArgument {
+    /// The unbounded wildcard, `?` in Java.
+    Wildcard,
+    /// The given type itself.
+    Exact(GenericType),
+    /// `? extends` the given type.
+    Extends(GenericType),
+    /// `? super` the given type.
+    Super(GenericType),
+}
+
+impl TypeArgument {
+    /// Parses the arguments after the opening angle bracket, returning the
+    /// rest after the closing one.
+    fn parse_list(signature: &str) -> Option<(Vec<Self>, &str)> {
+        let mut rest = signature;
+        let mut arguments = Vec::new();
+        loop {
+            if let Some(rest) = rest.strip_prefix('>') {
+                return (!arguments.is_empty()).then_some((arguments, rest));
+            }
+            let (argument, tail) = match rest.chars().next()? {
+                '*' => (TypeArgument::Wildcard, &rest[1..]),
+                '+' => {
+                    let (bound, tail) = GenericType::parse_prefix(&rest[1..])?;
+                    (TypeArgument::Extends(bound), tail)
+                }
+                '-' => {
+                    let (bound, tail) = GenericType::parse_prefix(&rest[1..])?;
+                    (TypeArgument::Super(bound), tail)
+                }
+                _ => {
+                    let (exact, tail) = GenericType::parse_prefix(rest)?;
+                    (TypeArgument::Exact(exact), tail)
+                }
+            };
+            arguments.push(argument);
+            rest = tail;
+        }
+    }
+}
+
+/// A type parameter declared by a generic class or method, e.g. `T` in
+/// `<T:Ljava/lang/Object;>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TypeParameter {
+    pub name: String,
+    /// The class bound, `None` if there are only interface bounds.
+    pub class_bound: Option<GenericType>,
+    pub interface_bounds: Vec<GenericType>,
+}
+
+impl TypeParameter {
+    /// Parses the parameters after the opening angle bracket, returning the
+    /// rest after the closing one.
+    fn parse_list(signature: &str) -> Option<(Vec<Self>, &str)> {
+        let mut rest = signature;
+        let mut parameters = Vec::new();
+        loop {
+            if let Some(rest) = rest.strip_prefix('>') {
+                return (!parameters.is_empty()).then_some((parameters, rest));
+            }
+            let (name, tail) = parse_identifier(rest)?;
+            rest = tail.strip_prefix(':')?;
+            let class_bound = if rest.starts_with(':') {
+                None
+            } else {
+                let (class_bound, tail) = GenericType::parse_prefix(rest)?;
+                rest = tail;
+                Some(class_bound)
+            };
+            let mut interface_bounds = Vec::new();
+            while let Some(tail) = rest.strip_prefix(':') {
+                let (bound, tail) = GenericType::parse_prefix(tail)?;
+                interface_bounds.push(bound);
+                rest = tail;
+            }
+            parameters.push(TypeParameter {
+                name: name.to_owned(),
+                class_bound,
+                interface_bounds,
+            });
+        }
+    }
+}
+
+/// The generic signature of a class or interface (JVMS §4.7.9.1), e.g.
+/// `<T:Ljava/lang/Object;>Ljava/lang/Object;Ljava/lang/Comparable<TT;>;`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GenericClassSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub superclass: GenericType,
+    pub interfaces: Vec<GenericType>,
+}
+
+impl FromStr for GenericClassSignature {
+    type Err = InvalidDescriptor;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = || {
+            let (type_parameters, rest) = match s.strip_prefix('<') {
+                Some(rest) => TypeParameter::parse_list(rest)?,
+                None => (Vec::new(), s),
+            };
+            // the supertypes can only be classes
+            let parse_class = |rest| match GenericType::parse_prefix(rest)? {
+                class @ (GenericType::Class { .. }, _) => Some(class),
+                _ => None,
+            };
+            let (superclass, mut rest) = parse_class(rest)?;
+            let mut interfaces = Vec::new();
+            while !rest.is_empty() {
+                let (interface, tail) = parse_class(rest)?;
+                interfaces.push(interface);
+                rest = tail;
+            }
+            Some(GenericClassSignature {
+                type_parameters,
+                superclass,
+                interfaces,
+            })
+        };
+        parse().ok_or_else(|| InvalidDescriptor(s.to_owned()))
+    }
+}
+
+/// Splits off a (non-empty) unqualified name as in JVMS §4.2.2, stopping at
+/// the characters that delimit the names in the signatures.
+fn parse_identifier(signature: &str) -> Option<(&str, &str)> {
+    let end = signature
+        .find(['.', ';', '[', '/', '<', '>', ':'])
+        .unwrap_or(signature.len());
+    (end != 0).then(|| signature.split_at(end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_pool_iter() {
+        let mut bytes = vec![5];
+        bytes.extend(5i64.to_be_bytes());
+        bytes.extend([1, 0, 2, b'h', b'i']);
+
+        let items = ConstantPoolItem::iter(4, bytes.as_slice())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(format!("{items:?}"), r#"[Stub, Long(5), Stub, Utf8("hi")]"#);
+
+        // stops at the first bad item
+        let mut iter = ConstantPoolItem::iter(4, [42, 1, 2, 3].as_slice());
+        assert!(matches!(iter.next(), Some(Ok(ConstantPoolItem::Stub))));
+        assert!(matches!(
+            iter.next(),
+            Some(Err(ConstantPoolParsingError::BadTag { tag: 42, index: 1 }))
+        ));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    fn field_type() {
+        assert_eq!("I".parse::<FieldType>().unwrap(), FieldType::Int);
+        assert_eq!(
+            "[[Ljava/lang/String;".parse::<FieldType>().unwrap(),
+            FieldType::Array(Box::new(FieldType::Array(Box::new(FieldType::Object(
+                "java/lang/String".into()
+            )))))
+        );
+
+        for invalid in ["", "V", "II", "Ljava/lang/String", "["] {
+            assert!(invalid.parse::<FieldType>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn java_name() {
+        let java_name = |descriptor: &str| descriptor.parse::<FieldType>().unwrap().java_name();
+
+        assert_eq!(java_name("Z"), "boolean");
+        assert_eq!(java_name("Ljava/util/List;"), "java.util.List");
+        assert_eq!(java_name("Ljava/util/Map$Entry;"), "java.util.Map$Entry");
+        assert_eq!(java_name("[[I"), "int[][]");
+        assert_eq!(java_name("[Ljava/lang/String;"), "java.lang.String[]");
+    }
+
+    #[test]
+    fn slot_size() {
+        let slot_size = |descriptor: &str| descriptor.parse::<FieldType>().unwrap().slot_size();
+
+        assert_eq!(slot_size("J"), 2);
+        assert_eq!(slot_size("D"), 2);
+        assert_eq!(slot_size("I"), 1);
+        assert_eq!(slot_size("Lx;"), 1);
+        assert_eq!(slot_size("[J"), 1);
+    }
+
+    #[test]
+    fn parse_arguments() {
+        use FieldType::*;
+
+        assert_eq!(FieldType::parse_arguments("()V").unwrap(), []);
+        assert_eq!(
+            FieldType::parse_arguments("(IJ[DLjava/lang/Object;)V").unwrap(),
+            [
+                Int,
+                Long,
+                Array(Box::new(Double)),
+                Object("java/lang/Object".into())
+            ]
+        );
+        assert!(FieldType::parse_arguments("IJ").is_err());
+        assert!(FieldType::parse_arguments("(IX)V").is_err());
+        assert!(FieldType::parse_arguments("(I").is_err());
+    }
+
+    #[test]
+    fn method_signature() {
+        use FieldType::*;
+
+        assert_eq!(
+            "()V".parse::<MethodSignature>().unwrap(),
+            MethodSignature {
+                params: vec![],
+                ret: None
+            }
+        );
+
+        let signature = "(J[[Ljava/lang/String;D)[[I"
+            .parse::<MethodSignature>()
+            .unwrap();
+        assert_eq!(
+            signature,
+            MethodSignature {
+                params: vec![
+                    Long,
+                    Array(Box::new(Array(Box::new(Object("java/lang/String".into()))))),
+                    Double
+                ],
+                ret: Some(Array(Box::new(Array(Box::new(Int))))),
+            }
+        );
+        assert_eq!(signature.argument_slots(), 5);
+
+        for invalid in ["", "()", "(I)VV", "(I)X", "(V)V", "I)V", "(I"] {
+            assert!(invalid.parse::<MethodSignature>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn generic_class_signature() {
+        use GenericType::*;
+
+        let object = || Class {
+            name: "java/lang/Object".into(),
+            type_arguments: vec![],
+        };
+        let variable = |name: &str| TypeArgument::Exact(TypeVariable(name.into()));
+
+        let signature = "<K:Ljava/lang/Object;V::Ljava/lang/Comparable<-TV;>;>Ljava/util/AbstractMap<TK;TV;>;Ljava/util/Map$Entry<[TK;*>;"
+            .parse::<GenericClassSignature>()
+            .unwrap();
+        assert_eq!(
+            signature,
+            GenericClassSignature {
+                type_parameters: vec![
+                    TypeParameter {
+                        name: "K".into(),
+                        class_bound: Some(object()),
+                        interface_bounds: vec![],
+                    },
+                    TypeParameter {
+                        name: "V".into(),
+                        class_bound: None,
+                        interface_bounds: vec![Class {
+                            name: "java/lang/Comparable".into(),
+                            type_arguments: vec![TypeArgument::Super(TypeVariable("V".into()))],
+                        }],
+                    },
+                ],
+                superclass: Class {
+                    name: "java/util/AbstractMap".into(),
+                    type_arguments: vec![variable("K"), variable("V")],
+                },
+                interfaces: vec![Class {
+                    name: "java/util/Map$Entry".into(),
+                    type_arguments: vec![
+                        TypeArgument::Exact(Array(Box::new(TypeVariable("K".into())))),
+                        TypeArgument::Wildcard
+                    ],
+                }],
+            }
+        );
+
+        let inner = "LOuter<TT;>.Inner<[I>;"
+            .parse::<GenericClassSignature>()
+            .unwrap();
+        assert_eq!(
+            inner.superclass,
+            Class {
+                name: "Outer$Inner".into(),
+                type_arguments: vec![TypeArgument::Exact(Array(Box::new(Primitive(
+                    FieldType::Int
+                ))))],
+            }
+        );
+
+        for invalid in [
+            "",
+            "<>Ljava/lang/Object;",
+            "<T>Ljava/lang/Object;",
+            "Ljava/lang/Object",
+            "LFoo<>;",
+            "[I",
+            "TT",
+        ] {
+            assert!(
+                invalid.parse::<GenericClassSignature>().is_err(),
+                "{invalid}"
+            );
+        }
+    }
+}
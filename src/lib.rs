@@ -14,11 +14,15 @@ use crate::{
     enums::ErrorCode,
 };
 
+#[cfg(feature = "tokio")]
+pub mod async_client;
 pub mod client;
 pub mod codec;
 pub mod commands;
 pub mod enums;
+pub mod highlevel;
 pub mod jvm;
+pub mod recording;
 pub mod types;
 
 mod xorshift;
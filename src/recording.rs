@@ -0,0 +1,115 @@
+//! Capturing the packets received from a target VM and replaying them later,
+//! e.g. to test against real-world traffic without a live JVM.
+//!
+//! The recorded format is the [ID sizes](crate::client::JdwpClient::id_sizes)
+//! of the target VM as five 4-byte integers in the order of [IDSizeInfo],
+//! followed by the JDWP packets one after another, exactly as they came over
+//! the wire.
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    io::{self, ErrorKind, Read, Write},
+};
+
+use crate::{
+    client::{decode_reply, ClientError, ID_SIZES},
+    codec::{JdwpReadable, JdwpReader, JdwpWritable, JdwpWriter},
+    commands::{event::Composite, virtual_machine::IDSizeInfo, Command},
+    PacketHeader, PacketMeta,
+};
+
+/// Writes every packet received by a [JdwpClient](crate::client::JdwpClient),
+/// see [set_recorder](crate::client::JdwpClient::set_recorder).
+pub struct EventRecorder {
+    write: Box<dyn Write + Send>,
+    started: bool,
+}
+
+impl EventRecorder {
+    pub fn new(write: impl Write + Send + 'static) -> Self {
+        Self {
+            write: Box::new(write),
+            started: false,
+        }
+    }
+
+    pub(crate) fn record(
+        &mut self,
+        id_sizes: &IDSizeInfo,
+        header: &PacketHeader,
+        data: &[u8],
+    ) -> io::Result<()> {
+        let mut writer = JdwpWriter::new(&mut self.write, id_sizes.clone());
+        if !self.started {
+            id_sizes.write(&mut writer)?;
+            self.started = true;
+        }
+        header.write(&mut writer)?;
+        self.write.write_all(data)?;
+        self.write.flush()
+    }
+}
+
+impl Debug for EventRecorder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("EventRecorder")
+    }
+}
+
+/// Reads the packets written by an [EventRecorder] and decodes the composite
+/// events among them, skipping the replies.
+#[derive(Debug)]
+pub struct EventReplayer<R: Read> {
+    reader: JdwpReader<R>,
+    started: bool,
+}
+
+impl<R: Read> EventReplayer<R> {
+    pub fn new(read: R) -> Self {
+        Self {
+            // replaced with the recorded ones before the first packet
+            reader: JdwpReader::new(read, ID_SIZES),
+            started: false,
+        }
+    }
+
+    fn next_packet(&mut self) -> io::Result<Option<(PacketHeader, Vec<u8>)>> {
+        if !self.started {
+            self.reader.id_sizes = match IDSizeInfo::read(&mut self.reader) {
+                Ok(id_sizes) => id_sizes,
+                // nothing was recorded
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            self.started = true;
+        }
+        let header = match PacketHeader::read(&mut self.reader) {
+            Ok(header) => header,
+            // the recording ended at the packet boundary
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut data = vec![0; header.length as usize - PacketHeader::JDWP_SIZE];
+        self.reader.read_exact(&mut data)?;
+        Ok(Some((header, data)))
+    }
+}
+
+impl<R: Read> Iterator for EventReplayer<R> {
+    type Item = Result<Composite, ClientError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.next_packet() {
+                Ok(Some((header, data))) => match header.meta {
+                    PacketMeta::Command(Composite::ID) => {
+                        return Some(decode_reply(data, &self.reader.id_sizes));
+                    }
+                    _ => continue,
+                },
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
@@ -1,13 +1,16 @@
 use crate::{
-    codec::{JdwpReadable, JdwpReader, JdwpWritable, JdwpWriter},
+    codec::{ByteTag, JdwpReadable, JdwpReader, JdwpWritable, JdwpWriter},
     enums::{Tag, TypeTag},
+    jvm::FieldType,
 };
 use std::{
-    fmt::{Debug, Formatter},
+    fmt::{self, Debug, Display, Formatter},
     io::{self, Read, Write},
     ops::Deref,
 };
 
+use thiserror::Error;
+
 use crate::enums::{ModifierKind, StepDepth, StepSize};
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
@@ -19,13 +22,17 @@ use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 /// object unless it has been explicitly disposed, regardless of whether the
 /// referenced object has been garbage collected.
 ///
+/// `Option<ObjectID>`, as well as an `Option` of any of the more specific
+/// object ids, is a nullable reference, where `None` is sent as the zero id.
+///
 /// Note that the existence of an object ID does not prevent the garbage
 /// collection of the object.
 /// Any attempt to access a a garbage collected object with its object ID will
 /// result in the INVALID_OBJECT error code.
 /// Garbage collection can be disabled with the DisableCollection command,
 /// but it is not usually necessary to do so.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ObjectID(u64);
 
 /// Uniquely identifies a method in some class in the target VM.
@@ -38,7 +45,8 @@ pub struct ObjectID(u64);
 ///
 /// The [ReferenceTypeID] can identify either the declaring type of the method
 /// or a subtype.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MethodID(u64);
 
 /// Uniquely identifies a field in some class in the target VM.
@@ -51,7 +59,8 @@ pub struct MethodID(u64);
 ///
 /// The [ReferenceTypeID] can identify either the declaring type of the field
 /// or a subtype.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FieldID(u64);
 
 /// Uniquely identifies a frame in the target VM.
@@ -60,7 +69,8 @@ pub struct FieldID(u64);
 /// only within a given thread).
 ///
 /// The [FrameID] need only be valid during the time its thread is suspended.
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrameID(u64);
 
 /// Uniquely identifies a reference type in the target VM.
@@ -72,7 +82,10 @@ pub struct FrameID(u64);
 /// commands and replies throughout its lifetime A [ReferenceTypeID] is not
 /// reused to identify a different reference type, regardless of whether the
 /// referenced class has been unloaded.
-#[derive(Copy, Clone, PartialEq, Eq)]
+///
+/// Same as with the [ObjectID], an `Option` of it is a nullable reference.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ReferenceTypeID(u64);
 
 macro_rules! ids {
@@ -111,6 +124,16 @@ macro_rules! ids {
                 }
             }
 
+        )*
+    };
+}
+
+// `Option` of an id means a nullable reference, where the null is sent as a
+// zero id - only the objects and the reference types can be null in JDWP, so
+// the method, field and frame ids don't get these, a zero there is just an id
+macro_rules! nullable_ids {
+    ($($tpe:ident),* $(,)?) => {
+        $(
             impl JdwpReadable for Option<$tpe> {
                 fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
                     let id = $tpe::read(read)?;
@@ -136,49 +159,66 @@ ids! {
     reference_type_id_size: ReferenceTypeID,
 }
 
+nullable_ids!(ObjectID, ReferenceTypeID);
+
 /// Uniquely identifies an object in the target VM that is known to be a thread.
-#[derive(Copy, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ThreadID(ObjectID);
 
 /// Uniquely identifies an object in the target VM that is known to be a thread
 /// group.
-#[derive(Copy, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ThreadGroupID(ObjectID);
 
 /// Uniquely identifies an object in the target VM that is known to be a string
 /// object.
 ///
 /// Note: this is very different from string, which is a value.
-#[derive(Copy, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StringID(ObjectID);
 
 /// Uniquely identifies an object in the target VM that is known to be a class
 /// loader object.
-#[derive(Copy, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassLoaderID(ObjectID);
 
 /// Uniquely identifies an object in the target VM that is known to be a class
 /// object.
-#[derive(Copy, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassObjectID(ObjectID);
 
 /// Uniquely identifies an object in the target VM that is known to be an array.
-#[derive(Copy, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArrayID(ObjectID);
 
+/// Uniquely identifies an object in the target VM that is known to be a
+/// module.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ModuleID(ObjectID);
+
 /// Uniquely identifies a reference type in the target VM that is known to be
 /// a class type.
-#[derive(Copy, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassID(ReferenceTypeID);
 
 /// Uniquely identifies a reference type in the target VM that is known to be
 /// an interface type.
-#[derive(Copy, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InterfaceID(ReferenceTypeID);
 
 /// Uniquely identifies a reference type in the target VM that is known to be
 /// an array type.
-#[derive(Copy, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArrayTypeID(ReferenceTypeID);
 
 macro_rules! wrapper_ids {
@@ -214,6 +254,7 @@ macro_rules! wrapper_ids {
                 }
             }
 
+            // all of these are objects or reference types, so nullable
             impl JdwpReadable for Option<$tpe> {
                 fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
                     let id = $tpe::read(read)?;
@@ -238,6 +279,7 @@ wrapper_ids! {
         ClassLoaderID,
         ClassObjectID,
         ArrayID,
+        ModuleID,
     }
     ReferenceTypeID {
         ClassID,
@@ -249,6 +291,7 @@ wrapper_ids! {
 /// A value retrieved from the target VM.
 /// This value can be an [ObjectID] or a primitive value (1 to 8 bytes).
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Value {
     /// a void value (no bytes)
     Void,
@@ -287,15 +330,216 @@ impl Value {
             Value::Object(_) => Tag::Object,
         }
     }
+
+    /// Returns a [Display] wrapper that renders this value as a Java
+    /// literal, e.g. `42L`, `'A'` or `3.14f`, for logging and the UI.
+    ///
+    /// The [Display] of the value itself is the Java string conversion used
+    /// by [VM::value_to_string](crate::highlevel::VM::value_to_string), so
+    /// it has no suffixes and quotes.
+    pub fn literal(self) -> ValueLiteral {
+        ValueLiteral(self)
+    }
+
+    /// Whether this is the null object.
+    pub fn is_null(self) -> bool {
+        matches!(self, Value::Object(id) if id.0 == 0)
+    }
+
+    /// Widens any of the integral values to a long, the way Java would: bytes
+    /// and shorts are sign-extended and chars are zero-extended.
+    ///
+    /// Returns `None` for everything else, including the booleans.
+    pub fn as_i64(self) -> Option<i64> {
+        match self {
+            Value::Byte(v) => Some(v as i8 as i64),
+            Value::Char(v) => Some(v as i64),
+            Value::Short(v) => Some(v as i64),
+            Value::Int(v) => Some(v as i64),
+            Value::Long(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Reads an `untagged-value` whose type is given by a field descriptor,
+    /// e.g. the declared type of the field or the local variable.
+    ///
+    /// This is the read counterpart of [Untagged], the `V` descriptor reads a
+    /// [Void](Value::Void) value without consuming anything.
+    pub fn from_descriptor_bytes<R: Read>(
+        descriptor: &str,
+        read: &mut JdwpReader<R>,
+    ) -> io::Result<Self> {
+        if descriptor == "V" {
+            return Ok(Value::Void);
+        }
+        let field_type = descriptor
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        match field_type {
+            FieldType::Byte => JdwpReadable::read(read).map(Value::Byte),
+            FieldType::Boolean => JdwpReadable::read(read).map(Value::Boolean),
+            FieldType::Char => JdwpReadable::read(read).map(Value::Char),
+            FieldType::Short => JdwpReadable::read(read).map(Value::Short),
+            FieldType::Int => JdwpReadable::read(read).map(Value::Int),
+            FieldType::Long => JdwpReadable::read(read).map(Value::Long),
+            FieldType::Float => JdwpReadable::read(read).map(Value::Float),
+            FieldType::Double => JdwpReadable::read(read).map(Value::Double),
+            FieldType::Object(_) | FieldType::Array(_) => {
+                JdwpReadable::read(read).map(Value::Object)
+            }
+        }
+    }
+}
+
+impl ByteTag for Value {
+    fn byte_tag(&self) -> u8 {
+        self.tag() as u8
+    }
+}
+
+/// Renders the primitives the way Java would, objects can only be shown as
+/// their IDs here.
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match *self {
+            Value::Void => f.write_str("void"),
+            Value::Byte(v) => write!(f, "{}", v as i8),
+            Value::Boolean(v) => write!(f, "{v}"),
+            Value::Char(v) => {
+                let c = char::decode_utf16([v]).next().and_then(Result::ok);
+                write!(f, "{}", c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            }
+            Value::Short(v) => write!(f, "{v}"),
+            Value::Int(v) => write!(f, "{v}"),
+            Value::Long(v) => write!(f, "{v}"),
+            Value::Float(v) => write!(f, "{v}"),
+            Value::Double(v) => write!(f, "{v}"),
+            Value::Object(_) if self.is_null() => f.write_str("null"),
+            Value::Object(v) => write!(f, "{v:?}"),
+        }
+    }
+}
+
+/// A [Value] rendered as a Java literal, see [Value::literal].
+///
+/// Java has no byte and short literals, so those are rendered as plain
+/// numbers, and objects are rendered as their IDs.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ValueLiteral(Value);
+
+impl Display for ValueLiteral {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Value::Void => f.write_str("void"),
+            Value::Byte(v) => write!(f, "{}", v as i8),
+            Value::Boolean(v) => write!(f, "{v}"),
+            Value::Char(v) => {
+                f.write_str("'")?;
+                match char::from_u32(v as u32) {
+                    Some('\'') => f.write_str("\\'")?,
+                    Some('\\') => f.write_str("\\\\")?,
+                    Some('\n') => f.write_str("\\n")?,
+                    Some('\r') => f.write_str("\\r")?,
+                    Some('\t') => f.write_str("\\t")?,
+                    Some('\u{8}') => f.write_str("\\b")?,
+                    Some('\u{c}') => f.write_str("\\f")?,
+                    Some(c) if !c.is_control() => write!(f, "{c}")?,
+                    // the other control characters and the lone surrogates
+                    _ => write!(f, "\\u{v:04X}")?,
+                }
+                f.write_str("'")
+            }
+            Value::Short(v) => write!(f, "{v}"),
+            Value::Int(v) => write!(f, "{v}"),
+            Value::Long(v) => write!(f, "{v}L"),
+            Value::Float(v) if v.is_nan() => f.write_str("Float.NaN"),
+            Value::Float(v) if v.is_infinite() => {
+                let sign = if v > 0.0 { "POSITIVE" } else { "NEGATIVE" };
+                write!(f, "Float.{sign}_INFINITY")
+            }
+            // debug keeps the fractional part of the whole numbers
+            Value::Float(v) => write!(f, "{v:?}f"),
+            Value::Double(v) if v.is_nan() => f.write_str("Double.NaN"),
+            Value::Double(v) if v.is_infinite() => {
+                let sign = if v > 0.0 { "POSITIVE" } else { "NEGATIVE" };
+                write!(f, "Double.{sign}_INFINITY")
+            }
+            Value::Double(v) => write!(f, "{v:?}"),
+            Value::Object(_) if self.0.is_null() => f.write_str("null"),
+            Value::Object(v) => write!(f, "{v:?}"),
+        }
+    }
+}
+
+/// The error of converting a [Value] into a Rust type with [TryFrom], when
+/// the value is not of the corresponding type.
+///
+/// The conversions only succeed for the values of exactly the corresponding
+/// type, there is no widening, see [Value::as_i64] for that.
+/// Java bytes are signed, so they convert to [i8] even though they are stored
+/// as [u8], and Java chars are UTF-16 code units, so they convert to [u16].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Error)]
+#[error("Expected a value of type {expected:?}, got {actual:?}")]
+pub struct ValueTypeMismatch {
+    pub expected: Tag,
+    pub actual: Tag,
+}
+
+macro_rules! value_conversions {
+    ($($tpe:ty: $variant:ident($v:ident) => $convert:expr),* $(,)?) => {
+        $(
+            impl TryFrom<Value> for $tpe {
+                type Error = ValueTypeMismatch;
+
+                fn try_from(value: Value) -> Result<Self, Self::Error> {
+                    match value {
+                        Value::$variant($v) => Ok($convert),
+                        _ => Err(ValueTypeMismatch {
+                            expected: Tag::$variant,
+                            actual: value.tag(),
+                        }),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+value_conversions! {
+    i8: Byte(v) => v as i8,
+    bool: Boolean(v) => v,
+    u16: Char(v) => v,
+    i16: Short(v) => v,
+    i32: Int(v) => v,
+    i64: Long(v) => v,
+    f32: Float(v) => v,
+    f64: Double(v) => v,
+    ObjectID: Object(v) => v,
 }
 
 /// A writable-only wrapper around [Value] that only writes the value itself
 /// without a tag.
 /// Used in places where JDWP specifies an `untagged-value` type and expects
 /// no tag since it should be derived from context.
+///
+/// Such values are read with [Value::from_descriptor_bytes], since the type
+/// has to be known from somewhere else.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct Untagged(Value);
 
+impl Untagged {
+    pub fn new(value: Value) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Value> for Untagged {
+    fn from(value: Value) -> Self {
+        Self(value)
+    }
+}
+
 impl Deref for Untagged {
     type Target = Value;
 
@@ -307,6 +551,7 @@ impl Deref for Untagged {
 impl JdwpWritable for Untagged {
     fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
         match self.0 {
+            // void values have no bytes, used with ForceEarlyReturn on void methods
             Value::Void => Ok(()),
             Value::Byte(v) => v.write(write),
             Value::Boolean(v) => v.write(write),
@@ -346,17 +591,42 @@ macro_rules! tagged_io {
     };
 }
 
-tagged_io! {
-    Value <-> Tag,
-    Byte, Boolean, Char, Int, Short, Long, Float, Double, Object
-    {
-        Tag::Void => Ok(Value::Void),
-        _ => Err(io::Error::from(io::ErrorKind::InvalidData))
+impl JdwpReadable for Value {
+    fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
+        match Tag::read(read)? {
+            Tag::Byte => JdwpReadable::read(read).map(Value::Byte),
+            Tag::Boolean => JdwpReadable::read(read).map(Value::Boolean),
+            Tag::Char => JdwpReadable::read(read).map(Value::Char),
+            Tag::Int => JdwpReadable::read(read).map(Value::Int),
+            Tag::Short => JdwpReadable::read(read).map(Value::Short),
+            Tag::Long => JdwpReadable::read(read).map(Value::Long),
+            Tag::Float => JdwpReadable::read(read).map(Value::Float),
+            Tag::Double => JdwpReadable::read(read).map(Value::Double),
+            // the host uses the more specific tags for the object values too
+            Tag::Object
+            | Tag::Array
+            | Tag::String
+            | Tag::Thread
+            | Tag::ThreadGroup
+            | Tag::ClassLoader
+            | Tag::ClassObject => JdwpReadable::read(read).map(Value::Object),
+            Tag::Void => Ok(Value::Void),
+        }
+    }
+}
+
+impl JdwpWritable for Value {
+    fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
+        if let Value::Void = self {
+            return Ok(());
+        }
+        self.tag().write(write)?;
+        Untagged(*self).write(write)
     }
-    { Self::Void => Ok(()) }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TaggedObjectID {
     /// an array object
     Array(ArrayID),
@@ -393,6 +663,12 @@ impl TaggedObjectID {
     }
 }
 
+impl ByteTag for TaggedObjectID {
+    fn byte_tag(&self) -> u8 {
+        self.tag() as u8
+    }
+}
+
 impl Deref for TaggedObjectID {
     type Target = ObjectID;
 
@@ -419,6 +695,7 @@ tagged_io! {
 
 /// A compact representation of values used with some array operations.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ArrayRegion {
     Byte(Vec<u8>),
     Boolean(Vec<bool>),
@@ -478,6 +755,12 @@ impl ArrayRegion {
     }
 }
 
+impl ByteTag for ArrayRegion {
+    fn byte_tag(&self) -> u8 {
+        self.tag() as u8
+    }
+}
+
 tagged_io! {
     ArrayRegion <-> Tag,
     Byte, Boolean, Char, Short, Int, Long, Float, Double, Object
@@ -491,7 +774,8 @@ tagged_io! {
 /// This construct is not separated into a separate value type in JDWP spec and
 /// exists only here in Rust, in JDWP it's usually represented by a pair of
 /// [TypeTag] and [ReferenceTypeID] values.
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TaggedReferenceTypeID {
     /// a class reference
     Class(ClassID),
@@ -516,6 +800,12 @@ impl TaggedReferenceTypeID {
     }
 }
 
+impl ByteTag for TaggedReferenceTypeID {
+    fn byte_tag(&self) -> u8 {
+        self.tag() as u8
+    }
+}
+
 impl Deref for TaggedReferenceTypeID {
     type Target = ReferenceTypeID;
 
@@ -558,44 +848,66 @@ tagged_io! {
 /// identifies a class or an interface. Almost all locations are within
 /// classes, but it is possible to have executable code in the static
 /// initializer of an interface.
-#[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Location {
-    reference_id: TaggedReferenceTypeID,
-    method_id: MethodID,
-    index: u64,
+    /// The class or interface the method is declared in
+    pub reference_id: TaggedReferenceTypeID,
+    /// The method
+    pub method_id: MethodID,
+    /// The code index within the method
+    pub index: u64,
 }
 
-macro_rules! optional_tag_impl {
-    ($($tpe:ident),* $(,)?) => {
-        $(
-            impl JdwpReadable for Option<$tpe> {
-                fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
-                    if read.peek_u8()? != 0 {
-                        JdwpReadable::read(read).map(Some)
-                    } else {
-                        read.read_u8()?; // consume it
-                        Ok(None)
-                    }
-                }
-            }
+// a null object is not just a zero tag, the host sends the object tag with a
+// zero id
+impl JdwpReadable for Option<TaggedObjectID> {
+    fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
+        let object = TaggedObjectID::read(read)?;
+        Ok(if object.0 == 0 { None } else { Some(object) })
+    }
+}
 
-            impl JdwpWritable for Option<$tpe> {
-                fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
-                    match self {
-                        Some(x) => x.write(write),
-                        None => write.write_u8(0),
-                    }
-                }
-            }
-        )*
-    };
+impl JdwpWritable for Option<TaggedObjectID> {
+    fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
+        match self {
+            Some(object) => object.write(write),
+            None => TaggedObjectID::Object(ObjectID(0)).write(write),
+        }
+    }
 }
 
-optional_tag_impl![Location, TaggedObjectID];
+// same as with the objects, a missing location is a location with the class
+// tag and all of the ids being zero
+impl JdwpReadable for Option<Location> {
+    fn read<R: Read>(read: &mut JdwpReader<R>) -> io::Result<Self> {
+        let location = Location::read(read)?;
+        Ok(if location.reference_id.0 == 0 {
+            None
+        } else {
+            Some(location)
+        })
+    }
+}
+
+impl JdwpWritable for Option<Location> {
+    fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
+        match self {
+            Some(location) => location.write(write),
+            None => Location {
+                reference_id: TaggedReferenceTypeID::Class(ClassID(ReferenceTypeID(0))),
+                method_id: MethodID(0),
+                index: 0,
+            }
+            .write(write),
+        }
+    }
+}
 
 /// An opaque type for the request id, which is represented in JDWP docs as just
 /// a raw integer and exists only here in Rust similar to all the other IDs.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RequestID(i32);
 
 impl RequestID {
@@ -609,6 +921,26 @@ impl RequestID {
     }
 }
 
+/// The maximum number of things to return, for the commands that treat zero
+/// as no limit at all.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Limit {
+    /// Return everything, written as zero.
+    All,
+    /// Return at most this many, should not be zero as that means
+    /// [All](Limit::All).
+    Exactly(u32),
+}
+
+impl JdwpWritable for Limit {
+    fn write<W: Write>(&self, write: &mut JdwpWriter<W>) -> io::Result<()> {
+        match *self {
+            Limit::All => 0u32.write(write),
+            Limit::Exactly(n) => n.write(write),
+        }
+    }
+}
+
 /// Limit the requested event to be reported at most once after a given number
 /// of occurrences.
 ///
@@ -628,6 +960,7 @@ impl RequestID {
 ///
 /// This modifier can be used with any event kind.
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Count {
     /// Count before event. One for one-off
     pub count: i32,
@@ -635,6 +968,7 @@ pub struct Count {
 
 /// Conditional on expression
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Conditional {
     /// For the future
     pub expr_id: i32,
@@ -643,6 +977,7 @@ pub struct Conditional {
 /// Restricts reported events to those in the given thread.
 /// This modifier can be used with any event kind except for class unload.
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ThreadOnly {
     /// Required thread
     pub thread: ThreadID,
@@ -664,6 +999,7 @@ pub struct ThreadOnly {
 /// This modifier can be used with any event kind except class unload, thread
 /// start, and thread end.
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassOnly {
     /// Required class
     pub class: ReferenceTypeID,
@@ -684,6 +1020,7 @@ pub struct ClassOnly {
 /// This modifier can be used with any event kind except thread start and
 /// thread end.
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassMatch {
     /// Required class pattern.
     ///
@@ -708,6 +1045,7 @@ pub struct ClassMatch {
 /// This modifier can be used with any event kind except thread start and
 /// thread end.
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ClassExclude {
     /// Disallowed class pattern.
     ///
@@ -722,6 +1060,7 @@ pub struct ClassExclude {
 /// This modifier can be used with breakpoint, field access, field
 /// modification, step, and exception event kinds.
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LocationOnly {
     /// Required location
     pub location: Location,
@@ -732,6 +1071,7 @@ pub struct LocationOnly {
 ///
 /// This modifier can be used with exception event kinds only.
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExceptionOnly {
     /// Exception to report. `None` means report exceptions of all types.
     ///
@@ -739,7 +1079,7 @@ pub struct ExceptionOnly {
     /// of the given type or any of its subtypes.
     pub exception: Option<ReferenceTypeID>,
     /// Report caught exceptions
-    pub uncaught: bool,
+    pub caught: bool,
     /// Report uncaught exceptions.
     ///
     /// Note that it is not always possible to determine whether an exception
@@ -747,7 +1087,7 @@ pub struct ExceptionOnly {
     ///
     /// See the exception event catch location under composite events for more
     /// information.
-    pub caught: bool,
+    pub uncaught: bool,
 }
 
 /// Restricts reported events to those that occur for a given field.
@@ -755,6 +1095,7 @@ pub struct ExceptionOnly {
 /// This modifier can be used with field access and field modification event
 /// kinds only.
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FieldOnly {
     /// Type in which field is declared
     pub declaring: ReferenceTypeID,
@@ -767,6 +1108,7 @@ pub struct FieldOnly {
 ///
 /// This modifier can be used with step event kinds only.
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Step {
     /// Thread in which to step
     pub thread: ThreadID,
@@ -786,6 +1128,7 @@ pub struct Step {
 ///
 /// Introduced in JDWP version 1.4.
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InstanceOnly {
     /// Required 'this' object
     pub instance: ObjectID,
@@ -804,6 +1147,7 @@ pub struct InstanceOnly {
 /// Requires the `can_use_source_name_filters` capability - see
 /// [CapabilitiesNew](crate::commands::virtual_machine::CapabilitiesNew).
 #[derive(Debug, Clone, PartialEq, Eq, JdwpReadable, JdwpWritable)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SourceNameMatch {
     /// Required source name pattern.
     /// Matches are limited to exact matches of the given pattern and matches
@@ -828,8 +1172,238 @@ pub enum Modifier {
     SourceNameMatch(SourceNameMatch),
 }
 
+impl Modifier {
+    /// The kind of this modifier, which is also its tag on the wire.
+    pub fn kind(&self) -> ModifierKind {
+        match self {
+            Self::Count(_) => ModifierKind::Count,
+            Self::Conditional(_) => ModifierKind::Conditional,
+            Self::ThreadOnly(_) => ModifierKind::ThreadOnly,
+            Self::ClassOnly(_) => ModifierKind::ClassOnly,
+            Self::ClassMatch(_) => ModifierKind::ClassMatch,
+            Self::ClassExclude(_) => ModifierKind::ClassExclude,
+            Self::LocationOnly(_) => ModifierKind::LocationOnly,
+            Self::ExceptionOnly(_) => ModifierKind::ExceptionOnly,
+            Self::FieldOnly(_) => ModifierKind::FieldOnly,
+            Self::Step(_) => ModifierKind::Step,
+            Self::InstanceOnly(_) => ModifierKind::InstanceOnly,
+            Self::SourceNameMatch(_) => ModifierKind::SourceNameMatch,
+        }
+    }
+}
+
 tagged_io! {
     Modifier <-> ModifierKind,
     Count, Conditional, ThreadOnly, ClassOnly, ClassMatch, ClassExclude, LocationOnly, ExceptionOnly, FieldOnly, Step, InstanceOnly, SourceNameMatch
     {} {}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::virtual_machine::IDSizeInfo;
+
+    const ID_SIZES: IDSizeInfo = IDSizeInfo {
+        field_id_size: 8,
+        method_id_size: 8,
+        object_id_size: 8,
+        reference_type_id_size: 8,
+        frame_id_size: 8,
+    };
+
+    fn write_bytes(value: impl JdwpWritable) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        value
+            .write(&mut JdwpWriter::new(&mut bytes, ID_SIZES))
+            .unwrap();
+        bytes
+    }
+
+    fn read_bytes<T: JdwpReadable>(bytes: &[u8]) -> T {
+        T::read(&mut JdwpReader::new(bytes, ID_SIZES)).unwrap()
+    }
+
+    #[test]
+    fn value_conversions() {
+        assert_eq!(i32::try_from(Value::Int(42)), Ok(42));
+        assert_eq!(i8::try_from(Value::Byte(0xFF)), Ok(-1));
+        assert_eq!(
+            i64::try_from(Value::Int(42)),
+            Err(ValueTypeMismatch {
+                expected: Tag::Long,
+                actual: Tag::Int
+            })
+        );
+        assert!(bool::try_from(Value::Object(ObjectID(0))).is_err());
+    }
+
+    #[test]
+    fn as_i64() {
+        assert_eq!(Value::Byte(0xFF).as_i64(), Some(-1));
+        assert_eq!(Value::Char(0xFFFF).as_i64(), Some(0xFFFF));
+        assert_eq!(Value::Short(-2).as_i64(), Some(-2));
+        assert_eq!(Value::Long(i64::MIN).as_i64(), Some(i64::MIN));
+        assert_eq!(Value::Boolean(true).as_i64(), None);
+        assert_eq!(Value::Double(1.0).as_i64(), None);
+    }
+
+    #[test]
+    fn untagged_void_is_empty() {
+        assert_eq!(write_bytes(Untagged::new(Value::Void)), []);
+    }
+
+    #[test]
+    fn untagged_has_no_tag() {
+        assert_eq!(write_bytes(Untagged::new(Value::Int(42))), [0, 0, 0, 42]);
+        assert_eq!(write_bytes(Value::Int(42)), [b'I', 0, 0, 0, 42]);
+    }
+
+    #[test]
+    fn mixed_value_list() {
+        let values = vec![Value::Int(42), Value::Object(ObjectID(7))];
+
+        let bytes = write_bytes(values.clone());
+        #[rustfmt::skip]
+        assert_eq!(bytes, [
+            0, 0, 0, 2,
+            b'I', 0, 0, 0, 42,
+            b'L', 0, 0, 0, 0, 0, 0, 0, 7,
+        ]);
+
+        assert_eq!(read_bytes::<Vec<Value>>(&bytes), values);
+    }
+
+    #[test]
+    fn array_region_round_trip() {
+        // one byte per boolean, after the tag and the u32 count
+        let booleans = ArrayRegion::Boolean(vec![true, false, true]);
+        let bytes = write_bytes(booleans.clone());
+        assert_eq!(bytes, [b'Z', 0, 0, 0, 3, 1, 0, 1]);
+        assert_eq!(read_bytes::<ArrayRegion>(&bytes), booleans);
+
+        let regions = [
+            ArrayRegion::Byte(vec![0, 0xFF]),
+            ArrayRegion::Boolean(vec![]),
+            ArrayRegion::Char(vec![b'x' as u16, 0xFFFF]),
+            ArrayRegion::Short(vec![-1, i16::MAX]),
+            ArrayRegion::Int(vec![42, i32::MIN]),
+            ArrayRegion::Long(vec![i64::MAX]),
+            ArrayRegion::Float(vec![1.5, -0.0]),
+            ArrayRegion::Double(vec![f64::MIN_POSITIVE]),
+            ArrayRegion::Object(vec![
+                TaggedObjectID::Object(ObjectID(7)),
+                TaggedObjectID::String(StringID(ObjectID(8))),
+            ]),
+        ];
+        for region in regions {
+            let bytes = write_bytes(region.clone());
+            assert_eq!(bytes[0], region.tag() as u8, "{region:?}");
+            assert_eq!(
+                bytes[1..5],
+                (region.len() as u32).to_be_bytes(),
+                "{region:?}"
+            );
+            assert_eq!(read_bytes::<ArrayRegion>(&bytes), region);
+        }
+    }
+
+    #[test]
+    fn limit() {
+        assert_eq!(write_bytes(Limit::All), [0, 0, 0, 0]);
+        assert_eq!(write_bytes(Limit::Exactly(5)), [0, 0, 0, 5]);
+    }
+
+    #[test]
+    fn from_descriptor_bytes() {
+        let read = |descriptor, bytes: &[u8]| {
+            let mut reader = JdwpReader::new(bytes, ID_SIZES);
+            let value = Value::from_descriptor_bytes(descriptor, &mut reader).unwrap();
+            assert!(reader.is_empty(), "{descriptor} left {reader:?}");
+            value
+        };
+
+        assert_eq!(read("I", &[0, 0, 0, 42]), Value::Int(42));
+        assert_eq!(read("J", &[0, 0, 0, 0, 0, 0, 0, 42]), Value::Long(42));
+        assert_eq!(read("Z", &[1]), Value::Boolean(true));
+        assert_eq!(
+            read("[Ljava/lang/String;", &[0, 0, 0, 0, 0, 0, 0, 42]),
+            Value::Object(ObjectID(42))
+        );
+        assert_eq!(read("V", &[]), Value::Void);
+
+        let mut reader = JdwpReader::new([0; 4].as_slice(), ID_SIZES);
+        assert!(Value::from_descriptor_bytes("X", &mut reader).is_err());
+    }
+
+    #[test]
+    fn null_ids() {
+        assert_eq!(read_bytes::<Option<ThreadID>>(&[0; 8]), None);
+        assert_eq!(write_bytes(None::<ThreadID>), [0; 8]);
+
+        // SAFETY: it is just written and read back
+        let thread = Some(unsafe { ThreadID::new(ObjectID(42)) });
+        assert_eq!(read_bytes::<Option<ThreadID>>(&write_bytes(thread)), thread);
+
+        assert_eq!(read_bytes::<Option<ReferenceTypeID>>(&[0; 8]), None);
+    }
+
+    #[test]
+    fn missing_location() {
+        let bytes = write_bytes(None::<Location>);
+        assert_eq!(bytes, [[1].as_slice(), &[0; 24]].concat());
+        assert_eq!(read_bytes::<Option<Location>>(&bytes), None);
+
+        // SAFETY: it is just written and read back
+        let class_id = unsafe { ClassID::new(ReferenceTypeID(1)) };
+        let location = Some(Location {
+            reference_id: TaggedReferenceTypeID::Class(class_id),
+            method_id: MethodID(2),
+            index: 3,
+        });
+        assert_eq!(
+            read_bytes::<Option<Location>>(&write_bytes(location.clone())),
+            location
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Value::Byte(0xFF).to_string(), "-1");
+        assert_eq!(Value::Char('ы' as u16).to_string(), "ы");
+        assert_eq!(Value::Double(2.5).to_string(), "2.5");
+        assert_eq!(Value::Boolean(true).to_string(), "true");
+        // SAFETY: it's never sent anywhere
+        let null = Value::Object(unsafe { ObjectID::new(0) });
+        assert_eq!(null.to_string(), "null");
+    }
+
+    #[test]
+    fn literal() {
+        let literal = |value: Value| value.literal().to_string();
+
+        assert_eq!(literal(Value::Void), "void");
+        assert_eq!(literal(Value::Byte(0xFF)), "-1");
+        assert_eq!(literal(Value::Boolean(false)), "false");
+        assert_eq!(literal(Value::Char('A' as u16)), "'A'");
+        assert_eq!(literal(Value::Char('ы' as u16)), "'ы'");
+        assert_eq!(literal(Value::Char('\'' as u16)), r"'\''");
+        assert_eq!(literal(Value::Char('\n' as u16)), r"'\n'");
+        assert_eq!(literal(Value::Char(0x1B)), r"'\u001B'");
+        assert_eq!(literal(Value::Char(0xD800)), r"'\uD800'");
+        assert_eq!(literal(Value::Short(-7)), "-7");
+        assert_eq!(literal(Value::Int(42)), "42");
+        assert_eq!(literal(Value::Long(42)), "42L");
+        assert_eq!(literal(Value::Float(1.25)), "1.25f");
+        assert_eq!(literal(Value::Float(2.0)), "2.0f");
+        assert_eq!(literal(Value::Float(f32::NAN)), "Float.NaN");
+        assert_eq!(literal(Value::Double(2.0)), "2.0");
+        assert_eq!(
+            literal(Value::Double(f64::NEG_INFINITY)),
+            "Double.NEGATIVE_INFINITY"
+        );
+        // SAFETY: it's never sent anywhere
+        let (null, object) = unsafe { (ObjectID::new(0), ObjectID::new(12)) };
+        assert_eq!(literal(Value::Object(null)), "null");
+        assert_eq!(literal(Value::Object(object)), "ObjectID(12)");
+    }
+}
@@ -0,0 +1,48 @@
+#![cfg(feature = "arrayvec")]
+
+use arrayvec::ArrayVec;
+use jdwp::{
+    codec::{JdwpReadable, JdwpReader, JdwpWritable, JdwpWriter},
+    commands::{
+        reference_type::{Fields, GetValues},
+        virtual_machine::ClassesBySignature,
+    },
+    enums::ErrorCode,
+    types::Value,
+};
+
+mod common;
+
+use common::Result;
+
+#[test]
+fn inline_get_values() -> Result {
+    let mut client = common::launch_and_attach("basic")?;
+
+    let id = *client.send(ClassesBySignature::new("Ljava/lang/Integer;"))?[0].type_id;
+    let fields = client.send(Fields::new(id))?;
+    let field = |name: &str| fields.iter().find(|f| f.name == name).unwrap().field_id;
+
+    let mut data = Vec::new();
+    GetValues::new(
+        id,
+        vec![field("MIN_VALUE"), field("MAX_VALUE"), field("SIZE")],
+    )
+    .write(&mut JdwpWriter::new(&mut data, client.id_sizes().clone()))?;
+    // ReferenceType.GetValues
+    let (error_code, reply) = client.send_raw(2, 6, &data)?;
+    assert_eq!(error_code, ErrorCode::None);
+
+    let id_sizes = client.id_sizes().clone();
+    let values = ArrayVec::<Value, 3>::read(&mut JdwpReader::new(&reply[..], id_sizes.clone()))?;
+    assert_eq!(
+        values.as_slice(),
+        [Value::Int(i32::MIN), Value::Int(i32::MAX), Value::Int(32)]
+    );
+
+    // not truncated, as that would leave the rest of the reply unread
+    let overflow = ArrayVec::<Value, 2>::read(&mut JdwpReader::new(&reply[..], id_sizes));
+    assert!(overflow.is_err());
+
+    Ok(())
+}
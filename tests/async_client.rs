@@ -0,0 +1,31 @@
+#![cfg(feature = "tokio")]
+
+use jdwp::{async_client::AsyncJdwpClient, commands::virtual_machine::Version};
+use tokio::runtime::{Builder, Runtime};
+
+mod common;
+
+use common::Result;
+
+fn runtime() -> Result<Runtime> {
+    Ok(Builder::new_current_thread().enable_io().build()?)
+}
+
+#[test]
+fn version() -> Result {
+    let runtime = runtime()?;
+    let client = common::launch_and_attach_with("basic", |port| {
+        let client = runtime.block_on(AsyncJdwpClient::attach(("localhost", port)))?;
+        Ok(client)
+    })?;
+
+    let reply = runtime.block_on(client.send(Version))?;
+
+    let version = match common::java_version() {
+        8 => (1, 8),
+        v => (v, 0),
+    };
+    assert_eq!((reply.version_major, reply.version_minor), version);
+
+    Ok(())
+}
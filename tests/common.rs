@@ -1,3 +1,6 @@
+// not every test uses every helper from here
+#![allow(dead_code)]
+
 use std::{
     error::Error,
     format,
@@ -5,35 +8,46 @@ use std::{
     net::TcpListener,
     ops::{Deref, DerefMut},
     process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    time::Duration,
 };
 
-use jdwp::client::JdwpClient;
+use jdwp::{
+    client::JdwpClient,
+    commands::event::Event,
+    enums::{EventKind, SuspendPolicy},
+    highlevel::{ThreadReference, VM},
+    CommandId,
+};
 use lazy_static::lazy_static;
 
 pub type Result<T = ()> = std::result::Result<T, Box<dyn Error>>;
 
 #[derive(Debug)]
-pub struct JvmHandle {
-    jdwp_client: JdwpClient,
+pub struct JvmHandle<C = JdwpClient> {
+    jdwp_client: C,
     pub jvm_process: Child,
     port: u16,
 }
 
-impl Deref for JvmHandle {
-    type Target = JdwpClient;
+impl<C> Deref for JvmHandle<C> {
+    type Target = C;
 
     fn deref(&self) -> &Self::Target {
         &self.jdwp_client
     }
 }
 
-impl DerefMut for JvmHandle {
+impl<C> DerefMut for JvmHandle<C> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.jdwp_client
     }
 }
 
-impl Drop for JvmHandle {
+impl<C> Drop for JvmHandle<C> {
     fn drop(&mut self) {
         match self.jvm_process.kill() {
             Ok(_) => {}
@@ -60,7 +74,10 @@ impl Drop for JvmHandle {
     }
 }
 
-fn ensure_fixture_is_compiled(fixture: &str) -> Result<(String, String)> {
+/// Fixtures that are compiled with the local variable tables.
+const DEBUG_INFO_FIXTURES: &[&str] = &["locals"];
+
+pub fn ensure_fixture_is_compiled(fixture: &str) -> Result<(String, String)> {
     let java_version = java_version();
 
     // omg wtf is this, Rust, no capitalize?
@@ -93,16 +110,99 @@ fn ensure_fixture_is_compiled(fixture: &str) -> Result<(String, String)> {
 
     log::info!("Compiling the java fixture: {fixture}");
 
-    Command::new("javac")
-        .args([&format!("tests/fixtures/{capitalized}.java"), "-d", &dir])
-        .stderr(Stdio::null())
-        .spawn()?
-        .wait()?;
+    let mut javac = Command::new("javac");
+    javac.args([&format!("tests/fixtures/{capitalized}.java"), "-d", &dir]);
+    // not for every fixture, as that changes e.g. the constant pool snapshot
+    if DEBUG_INFO_FIXTURES.contains(&fixture) {
+        javac.arg("-g");
+    }
+    javac.stderr(Stdio::null()).spawn()?.wait()?;
 
     Ok((dir, capitalized))
 }
 
 pub fn launch_and_attach(fixture: &str) -> Result<JvmHandle> {
+    let (jvm_process, port) = launch(fixture)?;
+
+    let jdwp_client = JdwpClient::attach(("localhost", port)).expect("Can't connect to the JVM");
+
+    Ok(JvmHandle {
+        jdwp_client,
+        jvm_process,
+        port,
+    })
+}
+
+/// Launches the fixture and attaches to it with a custom client, given the
+/// JDWP port.
+pub fn launch_and_attach_with<C>(
+    fixture: &str,
+    attach: impl FnOnce(u16) -> Result<C>,
+) -> Result<JvmHandle<C>> {
+    let (jvm_process, port) = launch(fixture)?;
+
+    Ok(JvmHandle {
+        jdwp_client: attach(port)?,
+        jvm_process,
+        port,
+    })
+}
+
+pub fn launch_and_attach_vm(fixture: &str) -> Result<JvmHandle<VM>> {
+    let (jvm_process, port) = launch(fixture)?;
+
+    let vm = VM::attach(("localhost", port)).expect("Can't connect to the JVM");
+
+    Ok(JvmHandle {
+        jdwp_client: vm,
+        jvm_process,
+        port,
+    })
+}
+
+/// Counts the commands with the given ID sent through the high-level API from
+/// now on, replacing the observer of the client, e.g. to check that something
+/// was cached.
+pub fn count_commands(vm: &VM, command: CommandId) -> Arc<AtomicUsize> {
+    let count = Arc::new(AtomicUsize::new(0));
+    vm.client().set_observer({
+        let count = count.clone();
+        move |id| {
+            if id == command {
+                count.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    });
+    count
+}
+
+/// Sets a one-shot breakpoint at the given line of the method and returns
+/// the thread that hits it, e.g. because the invokes only work in threads
+/// suspended by an event.
+pub fn break_at(
+    vm: &VM,
+    signature: &str,
+    method: &str,
+    line: u32,
+    policy: SuspendPolicy,
+) -> Result<ThreadReference> {
+    let class = &vm.classes_by_signature(signature)?[0];
+    let method = class.method_by_name(method)?.unwrap();
+    let location = method.locations_of_line(line)?.remove(0);
+
+    let (tx, rx) = mpsc::channel();
+    vm.event_request(EventKind::Breakpoint)
+        .suspend_policy(policy)
+        .location_only(location)
+        .once()
+        .subscribe(tx)?;
+    match rx.recv_timeout(Duration::from_secs(5))? {
+        Event::Breakpoint(e) => Ok(ThreadReference::new(vm.client().clone(), e.thread)),
+        e => panic!("Unexpected event: {:?}", e),
+    }
+}
+
+fn launch(fixture: &str) -> Result<(Child, u16)> {
     // ensure the logger was init
     let _ = env_logger::builder()
         .is_test(true)
@@ -120,13 +220,24 @@ pub fn launch_and_attach(fixture: &str) -> Result<JvmHandle> {
             port
         ))
         .args(["-cp", &classpath, &class_name])
+        .stdin(Stdio::piped()) // for the fixtures that take commands
         .stdout(Stdio::piped())
         .stderr(Stdio::null()) // literally to disable _JAVA_OPTIONS spam
         .spawn()
         .expect("Failed to start the JVM");
 
     // Wait for the output to ensure this JVM is fully up
+    if let Err(e) = wait_until_up(&mut jvm_process) {
+        // don't leave a zombie behind
+        let _ = jvm_process.kill();
+        jvm_process.wait()?;
+        return Err(e);
+    }
+
+    Ok((jvm_process, port))
+}
 
+fn wait_until_up(jvm_process: &mut Child) -> Result {
     let mut stdout = BufReader::new(jvm_process.stdout.take().unwrap()).lines();
 
     // "Listening for transport dt_socket at address: {port}"
@@ -136,13 +247,7 @@ pub fn launch_and_attach(fixture: &str) -> Result<JvmHandle> {
     // "up" is printed by the java fixture class
     assert_eq!(stdout.next().unwrap()?, "up");
 
-    let jdwp_client = JdwpClient::attach(("localhost", port)).expect("Can't connect to the JVM");
-
-    Ok(JvmHandle {
-        jdwp_client,
-        jvm_process,
-        port,
-    })
+    Ok(())
 }
 
 pub fn java_version() -> u32 {
@@ -1,9 +1,13 @@
 use jdwp::{
     commands::{
-        event::Event, event_request, reference_type::Fields, virtual_machine::ClassesBySignature,
+        event::Event,
+        event_request,
+        method::LineTable,
+        reference_type::{Fields, Methods},
+        virtual_machine::ClassesBySignature,
     },
     enums::{EventKind, SuspendPolicy},
-    types::{FieldOnly, Modifier, Value},
+    types::{FieldOnly, Location, LocationOnly, Modifier, Value},
 };
 
 mod common;
@@ -57,3 +61,54 @@ fn field_modification() -> Result {
 
     Ok(())
 }
+
+#[test]
+fn breakpoint_events() -> Result {
+    let mut client = common::launch_and_attach("basic")?;
+
+    let type_id = client.send(ClassesBySignature::new("LBasic;"))?[0].type_id;
+
+    let tick = client
+        .send(Methods::new(*type_id))?
+        .into_iter()
+        .find(|m| m.name == "tick")
+        .unwrap();
+    let line_table = client.send(LineTable::new(*type_id, tick.method_id))?;
+    let line = line_table
+        .lines
+        .iter()
+        .find(|l| l.line_number == 13)
+        .unwrap();
+
+    let location = Location {
+        reference_id: type_id,
+        method_id: tick.method_id,
+        index: line.line_code_index,
+    };
+
+    let request_id = client.send(event_request::Set::new(
+        EventKind::Breakpoint,
+        SuspendPolicy::None,
+        vec![Modifier::LocationOnly(LocationOnly {
+            location: location.clone(),
+        })],
+    ))?;
+
+    // the sends interleaving with the events should not lose or consume them
+    client.ping()?;
+
+    for _ in 0..2 {
+        let composite = client.events().next().unwrap();
+        match &composite.events[..] {
+            [Event::Breakpoint(breakpoint)] => {
+                assert_eq!(breakpoint.location, location);
+            }
+            e => panic!("Unexpected event set received: {:#?}", e),
+        }
+        client.ping()?;
+    }
+
+    client.send(event_request::Clear::new(EventKind::Breakpoint, request_id))?;
+
+    Ok(())
+}
@@ -0,0 +1,39 @@
+import java.io.BufferedReader;
+import java.io.InputStreamReader;
+
+class Exceptions {
+
+    static class Boom extends RuntimeException {
+    }
+
+    public static void main(String[] args) throws Exception {
+        // make sure it's loaded before the test looks it up
+        Class.forName("Exceptions$Boom");
+
+        BufferedReader stdin = new BufferedReader(new InputStreamReader(System.in));
+
+        System.out.println("up"); // tell the test we're ready
+
+        String command;
+        while ((command = stdin.readLine()) != null) {
+            switch (command) {
+                case "caught":
+                    try {
+                        throw new Boom();
+                    } catch (Boom e) {
+                        // noop
+                    }
+                    break;
+                case "uncaught":
+                    Thread thread = new Thread(Exceptions::boom);
+                    thread.start();
+                    thread.join();
+                    break;
+            }
+        }
+    }
+
+    private static void boom() {
+        throw new Boom();
+    }
+}
@@ -0,0 +1,18 @@
+class Locals {
+
+    public static void main(String[] args) throws Exception {
+        System.out.println("up"); // tell the test we're ready
+
+        while (true) {
+            compute(21, 2.5);
+            Thread.sleep(50L);
+        }
+    }
+
+    static long compute(int x, double factor) {
+        String label = "answer";
+        long result = x * 2L;
+        int[] values = {x};
+        return result + label.length() + values.length + (long) factor;
+    }
+}
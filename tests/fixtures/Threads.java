@@ -0,0 +1,29 @@
+import java.io.BufferedReader;
+import java.io.InputStreamReader;
+import java.util.ArrayList;
+import java.util.List;
+
+class Threads {
+
+    // keep the workers around so that they are never collected
+    static List<Thread> workers = new ArrayList<>();
+
+    public static void main(String[] args) throws Exception {
+        BufferedReader stdin = new BufferedReader(new InputStreamReader(System.in));
+
+        System.out.println("up"); // tell the test we're ready
+
+        // start a thread named after each line the test sends
+        String name;
+        while ((name = stdin.readLine()) != null) {
+            Thread worker = new Thread(Threads::work, name);
+            workers.add(worker);
+            worker.start();
+            worker.join();
+        }
+    }
+
+    private static void work() {
+        // noop
+    }
+}
@@ -0,0 +1,68 @@
+use jdwp::{
+    client::ClientError,
+    commands::array_type::NewInstance,
+    highlevel::ArrayReference,
+    types::{ArrayRegion, TaggedReferenceTypeID, Value},
+};
+
+mod common;
+
+use common::Result;
+
+#[test]
+fn set_and_get() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let int_array = &vm.classes_by_signature("[I")?[0];
+    let TaggedReferenceTypeID::Array(array_type) = int_array.id() else {
+        panic!("Not an array type: {:?}", int_array.id());
+    };
+    let new_array = vm.client().send(NewInstance::new(array_type, 5))?.new_array;
+    let array = ArrayReference::new(vm.client().clone(), new_array);
+
+    assert_eq!(array.length()?, 5);
+
+    array.set_values(1, &[Value::Int(42), Value::Int(-7)])?;
+    assert_eq!(array.get_all()?, ArrayRegion::Int(vec![0, 42, -7, 0, 0]));
+    assert_eq!(array.get_region(2..4)?, ArrayRegion::Int(vec![-7, 0]));
+    assert_eq!(array.get_region(5..5)?, ArrayRegion::Int(vec![]));
+
+    let new_array = vm.client().send(NewInstance::new(array_type, 0))?.new_array;
+    let empty = ArrayReference::new(vm.client().clone(), new_array);
+    assert_eq!(empty.get_all()?, ArrayRegion::Int(vec![]));
+
+    // the bounds are checked before anything is sent
+    let result = array.get_region(3..6);
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+    let result = array.set_values(4, &[Value::Int(1), Value::Int(2)]);
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+    assert_eq!(array.get_all()?, ArrayRegion::Int(vec![0, 42, -7, 0, 0]));
+
+    Ok(())
+}
+
+#[test]
+fn boolean_array() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let boolean_array = &vm.classes_by_signature("[Z")?[0];
+    let TaggedReferenceTypeID::Array(array_type) = boolean_array.id() else {
+        panic!("Not an array type: {:?}", boolean_array.id());
+    };
+    let new_array = vm.client().send(NewInstance::new(array_type, 4))?.new_array;
+    let array = ArrayReference::new(vm.client().clone(), new_array);
+
+    array.set_values(1, &[Value::Boolean(true), Value::Boolean(true)])?;
+    assert_eq!(
+        array.get_all()?,
+        ArrayRegion::Boolean(vec![false, true, true, false])
+    );
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use jdwp::{
+    client::ClientError,
+    commands::{class_type::NewInstanceReply, object_reference::InvokeMethodReply},
+    enums::{InvokeOptions, SuspendPolicy},
+    highlevel::{ClassType, ObjectReference, ReferenceType, VM},
+    types::{ObjectID, TaggedReferenceTypeID, Value},
+};
+
+mod common;
+
+use common::Result;
+
+fn class(vm: &VM, signature: &str) -> Result<ClassType> {
+    let reference_type: &ReferenceType = &vm.classes_by_signature(signature)?[0];
+    match reference_type.id() {
+        TaggedReferenceTypeID::Class(id) => Ok(ClassType::new(vm.client().clone(), id)),
+        id => panic!("{signature} is not a class: {id:?}"),
+    }
+}
+
+#[test]
+fn set_static() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = class(&vm, "LBasic;")?;
+    basic.set_static("staticInt", Value::Int(7))?;
+
+    let value = basic.field_by_name("staticInt")?.unwrap().read(None)?;
+    assert_eq!(value, Value::Int(7));
+
+    Ok(())
+}
+
+#[test]
+fn set_static_final() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let integer = class(&vm, "Ljava/lang/Integer;")?;
+    let result = integer.set_static("MAX_VALUE", Value::Int(0));
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn set_static_values() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = class(&vm, "LBasic;")?;
+    let field = basic.field_by_name("staticInt")?.unwrap();
+    basic.set_static_values(&[(field.id(), Value::Int(11))])?;
+
+    assert_eq!(field.read(None)?, Value::Int(11));
+
+    Ok(())
+}
+
+#[test]
+fn invoke_static() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    let basic = class(&vm, "LBasic;")?;
+    let ping = basic.method_by_name("ping")?.unwrap();
+    // SAFETY: it's a null
+    let null = Value::Object(unsafe { ObjectID::new(0) });
+
+    let reply = basic.invoke_static(&thread, &ping, &[null], InvokeOptions::empty())?;
+    assert_eq!(reply, InvokeMethodReply::Value(Value::Void));
+
+    let tick = basic.method_by_name("tick")?.unwrap();
+    let result = basic.invoke_static(&thread, &tick, &[], InvokeOptions::empty());
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn new_instance() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    let basic = class(&vm, "LBasic;")?;
+    let constructor = basic.method_by_name("<init>")?.unwrap();
+
+    let reply = basic.new_instance(&thread, &constructor, &[], InvokeOptions::empty())?;
+    let NewInstanceReply::Instance(instance) = reply else {
+        panic!("The constructor threw: {reply:?}");
+    };
+    let instance = ObjectReference::new(vm.client().clone(), *instance);
+    assert_eq!(instance.reference_type()?, *basic);
+
+    Ok(())
+}
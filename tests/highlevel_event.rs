@@ -0,0 +1,54 @@
+use std::{io::Write, sync::mpsc, time::Duration};
+
+use jdwp::{
+    commands::{event::Event, event_request},
+    enums::{EventKind, SuspendPolicy},
+    highlevel::ExceptionEvent,
+    types::{ExceptionOnly, Modifier},
+};
+
+mod common;
+
+use common::Result;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn exception_catch_location() -> Result {
+    let mut vm = common::launch_and_attach_vm("exceptions")?;
+
+    let boom = &vm.classes_by_signature("LExceptions$Boom;")?[0];
+
+    let (tx, rx) = mpsc::channel();
+    vm.client().lock().subscribe(
+        event_request::Set::new(
+            EventKind::Exception,
+            SuspendPolicy::None,
+            vec![Modifier::ExceptionOnly(ExceptionOnly {
+                exception: Some(*boom.id()),
+                caught: true,
+                uncaught: true,
+            })],
+        ),
+        tx,
+    )?;
+
+    let mut throw = |command: &str| -> Result<ExceptionEvent> {
+        writeln!(vm.jvm_process.stdin.as_mut().unwrap(), "{command}")?;
+        match rx.recv_timeout(TIMEOUT)? {
+            Event::Exception(e) => Ok(ExceptionEvent::new(vm.client().clone(), e)),
+            e => panic!("Unexpected event: {:?}", e),
+        }
+    };
+
+    let caught = throw("caught")?;
+    assert!(caught.is_caught());
+    let position = caught.catch_position()?.unwrap();
+    assert_eq!(position.to_string(), "Exceptions.java:23");
+
+    let uncaught = throw("uncaught")?;
+    assert!(!uncaught.is_caught());
+    assert_eq!(uncaught.catch_position()?, None);
+
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use jdwp::{client::ClientError, highlevel::ObjectReference, types::Value};
+
+mod common;
+
+use common::Result;
+
+#[test]
+fn read_dispatch() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let field = |name| basic.field_by_name(name).map(Option::unwrap);
+
+    assert_eq!(field("staticInt")?.read(None)?, Value::Int(42));
+
+    let Value::Object(id) = field("secondInstance")?.read(None)? else {
+        panic!("secondInstance is not an object");
+    };
+    let second_instance = ObjectReference::new(vm.client().clone(), id);
+    assert_eq!(second_instance.reference_type()?, *basic);
+
+    // the second instance is never ticked
+    let ticks = field("ticks")?;
+    assert_eq!(ticks.read(Some(&second_instance))?, Value::Long(0));
+
+    let result = ticks.read(None);
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn read_inherited_static() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    // both Integer and Number declare their own serialVersionUID
+    let integer = &vm.classes_by_signature("Ljava/lang/Integer;")?[0];
+    let number = &vm.classes_by_signature("Ljava/lang/Number;")?[0];
+
+    let fields = integer.all_fields()?;
+    let mut serial_versions = fields.iter().filter(|f| f.name() == "serialVersionUID");
+
+    let own = serial_versions.next().unwrap();
+    assert_eq!(own.declaring_type(), *integer);
+    assert_eq!(own.read(None)?, Value::Long(1360826667806852920));
+
+    let inherited = serial_versions.next().unwrap();
+    assert_eq!(inherited.declaring_type(), *number);
+    assert_eq!(inherited.read(None)?, Value::Long(-8742448824652078965));
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use std::sync::atomic::Ordering;
+
+use jdwp::commands::{
+    method::{Bytecodes, LineTable},
+    virtual_machine::{RedefineClasses, RedefiningClass},
+    Command,
+};
+
+mod common;
+
+use common::Result;
+
+#[test]
+fn line_table_is_cached() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let line_tables = common::count_commands(&vm, LineTable::ID);
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+
+    // `++ticks;` and the closing brace
+    assert_eq!(tick.locations_of_line(13)?.len(), 1);
+    assert_eq!(tick.locations_of_line(14)?.len(), 1);
+
+    // same method from a different lookup still hits the cache
+    let tick = basic.method_by_name("tick")?.unwrap();
+    assert!(tick.locations_of_line(42)?.is_empty());
+
+    assert_eq!(line_tables.load(Ordering::SeqCst), 1);
+
+    // a no-op redefine still invalidates the cache
+    let bytes = std::fs::read(format!(
+        "target/java_{}/Basic.class",
+        common::java_version()
+    ))?;
+    vm.client()
+        .send(RedefineClasses::new(vec![RedefiningClass::new(
+            *basic.id(),
+            bytes,
+        )]))?;
+
+    assert_eq!(tick.locations_of_line(13)?.len(), 1);
+    assert_eq!(line_tables.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
+
+#[test]
+fn argument_slots() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let object = &vm.classes_by_signature("Ljava/lang/Object;")?[0];
+    // void wait(long timeoutMillis, int nanos)
+    let wait = object
+        .methods()?
+        .into_iter()
+        .find(|m| m.name() == "wait" && m.signature() == "(JI)V")
+        .unwrap();
+    assert_eq!(wait.argument_slots()?, [1, 3]);
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+    assert_eq!(tick.argument_slots()?, []);
+
+    Ok(())
+}
+
+#[test]
+fn line_for_index() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+
+    // aload_0, dup, getfield, lconst_1, ladd, putfield and then return
+    assert_eq!(tick.line_for_index(0)?, Some(13));
+    assert_eq!(tick.line_for_index(5)?, Some(13));
+    assert_eq!(tick.line_for_index(10)?, Some(14));
+    assert_eq!(tick.line_for_index(11)?, None);
+
+    let object = &vm.classes_by_signature("Ljava/lang/Object;")?[0];
+    let hash_code = object.method_by_name("hashCode")?.unwrap();
+    assert_eq!(hash_code.line_for_index(0)?, None);
+    assert!(hash_code.locations_of_line(1)?.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn bytecodes() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let fetches = common::count_commands(&vm, Bytecodes::ID);
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+
+    let bytecodes = tick.bytecodes()?;
+    assert_eq!(bytecodes.len(), 11);
+    // aload_0 and return
+    assert_eq!((bytecodes[0], bytecodes[10]), (0x2a, 0xb1));
+
+    assert_eq!(tick.bytecodes()?, bytecodes);
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+    assert!(!tick.is_obsolete()?);
+
+    Ok(())
+}
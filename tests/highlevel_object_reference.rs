@@ -0,0 +1,326 @@
+use std::sync::atomic::Ordering;
+
+use jdwp::{
+    client::ClientError,
+    commands::{
+        array_reference, class_type,
+        event_request::ClearAllBreakpoints,
+        object_reference::{InvokeMethod, InvokeMethodReply},
+        reference_type::Fields,
+        string_reference,
+        virtual_machine::{CreateString, DisposeObjects, ObjectRef},
+        Command,
+    },
+    enums::{InvokeOptions, SuspendPolicy, Tag},
+    highlevel::{ClassType, ObjectReference, VM},
+    jvm::FieldType,
+    types::{ArrayID, ArrayRegion, TaggedReferenceTypeID, Value},
+};
+
+#[macro_use]
+mod common;
+
+use common::Result;
+
+fn second_instance(vm: &VM) -> Result<ObjectReference> {
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let field = basic.field_by_name("secondInstance")?.unwrap();
+    let Value::Object(id) = field.read(None)? else {
+        panic!("secondInstance is not an object");
+    };
+    Ok(ObjectReference::new(vm.client().clone(), id))
+}
+
+#[test]
+fn invoke_nonvirtual() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    let Value::Object(second_instance) =
+        basic.field_by_name("secondInstance")?.unwrap().read(None)?
+    else {
+        panic!("secondInstance is not an object");
+    };
+    let second_instance = ObjectReference::new(vm.client().clone(), second_instance);
+    let Value::Object(hello) = basic
+        .field_by_name("unused")?
+        .unwrap()
+        .read(Some(&second_instance))?
+    else {
+        panic!("unused is not an object");
+    };
+    let hello = ObjectReference::new(vm.client().clone(), hello);
+
+    let object = match vm.classes_by_signature("Ljava/lang/Object;")?[0].id() {
+        TaggedReferenceTypeID::Class(id) => ClassType::new(vm.client().clone(), id),
+        id => panic!("Object is not a class: {:?}", id),
+    };
+    let to_string = object.method_by_name("toString")?.unwrap();
+
+    let read_string = |reply| -> Result<String> {
+        match reply {
+            InvokeMethodReply::Value(Value::Object(id)) => {
+                Ok(vm.client().send(string_reference::Value::new(id))?)
+            }
+            reply => panic!("Unexpected invoke reply: {:?}", reply),
+        }
+    };
+
+    // String overrides toString, so a virtual call just returns the string
+    let virtual_call = vm.client().send(InvokeMethod::new(
+        hello.id(),
+        thread.id(),
+        object.id(),
+        to_string.id(),
+        vec![],
+        InvokeOptions::empty(),
+    ))?;
+    assert_eq!(read_string(virtual_call)?, "hello");
+
+    let nonvirtual_call = hello.invoke_nonvirtual(&thread, &object, &to_string, &[])?;
+    let result = read_string(nonvirtual_call)?;
+    assert!(result.starts_with("java.lang.String@"), "{result}");
+
+    Ok(())
+}
+
+#[test]
+fn invoke_with_mistyped_arguments() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let second_instance = second_instance(&vm)?;
+    // the arguments are checked before anything is sent, so no need to break
+    let thread = vm.thread_by_name("main")?.unwrap();
+
+    let object = match vm.classes_by_signature("Ljava/lang/Object;")?[0].id() {
+        TaggedReferenceTypeID::Class(id) => ClassType::new(vm.client().clone(), id),
+        id => panic!("Object is not a class: {:?}", id),
+    };
+    let equals = object.method_by_name("equals")?.unwrap();
+
+    let result = second_instance.invoke_nonvirtual(&thread, &object, &equals, &[Value::Int(42)]);
+    assert!(
+        matches!(
+            result,
+            Err(ClientError::ArgumentTypeMismatch {
+                index: 0,
+                expected: FieldType::Object(ref class),
+                actual: Tag::Int,
+            }) if class == "java/lang/Object"
+        ),
+        "{result:?}"
+    );
+
+    let result = second_instance.invoke_nonvirtual(&thread, &object, &equals, &[]);
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn identity_hash_is_cached() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let second_instance = second_instance(&vm)?;
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    let invokes = common::count_commands(&vm, class_type::InvokeMethod::ID);
+
+    let hash = second_instance.identity_hash(&thread)?;
+    assert_eq!(second_instance.identity_hash(&thread)?, hash);
+    assert_eq!(
+        second_instance.inspect(Some(&thread))?.identity_hash,
+        Some(hash)
+    );
+
+    assert_eq!(invokes.load(Ordering::SeqCst), 1);
+
+    // a disposed ID can be reused for another object, so it is forgotten
+    vm.client().send(DisposeObjects::new(vec![ObjectRef::new(
+        second_instance.id(),
+        1,
+    )]))?;
+    let second_instance = self::second_instance(&vm)?;
+    assert_eq!(second_instance.identity_hash(&thread)?, hash);
+
+    assert_eq!(invokes.load(Ordering::SeqCst), 2);
+
+    Ok(())
+}
+
+#[test]
+fn inspect() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let second_instance = second_instance(&vm)?;
+
+    let inspection = second_instance.inspect(None)?;
+    assert_eq!(inspection.identity_hash, None);
+
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+    let mut inspection = second_instance.inspect(Some(&thread))?;
+    // it's random, so just check that it's there
+    assert!(inspection.identity_hash.take().is_some());
+
+    assert_snapshot!(inspection, @r###"
+    ObjectInspection {
+        signature: "LBasic;",
+        fields: [
+            (
+                Field {
+                    id: [opaque_id],
+                    name: "ticks",
+                    signature: "J",
+                },
+                Long(
+                    0,
+                ),
+            ),
+            (
+                Field {
+                    id: [opaque_id],
+                    name: "unused",
+                    signature: "Ljava/lang/String;",
+                },
+                Object(
+                    [opaque_id],
+                ),
+            ),
+        ],
+        identity_hash: None,
+    }
+    "###);
+
+    Ok(())
+}
+
+#[test]
+fn unbox() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    // the boxed values from -128 to 127 are always cached, so take one of those
+    let cache = &vm.classes_by_signature("Ljava/lang/Integer$IntegerCache;")?[0];
+    let Value::Object(cache) = cache.field_by_name("cache")?.unwrap().read(None)? else {
+        panic!("IntegerCache.cache is not an object");
+    };
+    let region = vm.client().send(array_reference::GetValues::new(
+        unsafe { ArrayID::new(cache) },
+        128 + 42,
+        1,
+    ))?;
+    let ArrayRegion::Object(boxed) = region else {
+        panic!("Unexpected array region: {:?}", region);
+    };
+    let boxed = ObjectReference::new(vm.client().clone(), *boxed[0]);
+
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    assert_eq!(boxed.unbox(&thread)?, Some(Value::Int(42)));
+    assert_eq!(second_instance(&vm)?.unbox(&thread)?, None);
+
+    Ok(())
+}
+
+#[test]
+fn string_from_fields() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    // the first two are LATIN1 compact strings, the others are UTF16
+    for contents in ["hello", "héllo", "привет", "surrogate 😀 pair"] {
+        let string = vm.client().send(CreateString::new(contents))?;
+        let from_command = vm.client().send(string_reference::Value::new(*string))?;
+
+        let object = ObjectReference::new(vm.client().clone(), *string);
+        assert_eq!(object.string_from_fields()?, from_command);
+        assert_eq!(from_command, contents);
+    }
+
+    let result = second_instance(&vm)?.string_from_fields();
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn fields_by_name() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let second_instance = second_instance(&vm)?;
+    assert_eq!(second_instance.get_field("ticks")?, Value::Long(0));
+
+    let fields_sent = common::count_commands(&vm, Fields::ID);
+
+    second_instance.set_field("ticks", Value::Long(42))?;
+    assert_eq!(second_instance.get_field("ticks")?, Value::Long(42));
+    // the field map of Basic was cached by the first lookup
+    assert_eq!(fields_sent.load(Ordering::SeqCst), 0);
+
+    let result = second_instance.get_field("staticInt");
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+    let result = second_instance.set_field("ticks", Value::Void);
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+
+    assert!(!second_instance.is_collected()?);
+
+    Ok(())
+}
+
+#[test]
+fn invoke() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let second_instance = second_instance(&vm)?;
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+    // or else the invoked tick hits it again and waits for us forever
+    vm.client().send(ClearAllBreakpoints)?;
+
+    let reply = second_instance.invoke(&thread, "tick", "()V", &[], InvokeOptions::empty())?;
+    assert!(matches!(reply, InvokeMethodReply::Value(Value::Void)));
+    assert_eq!(second_instance.get_field("ticks")?, Value::Long(1));
+
+    // inherited from Object
+    let reply = second_instance.invoke(
+        &thread,
+        "equals",
+        "(Ljava/lang/Object;)Z",
+        &[Value::Object(second_instance.id())],
+        InvokeOptions::empty(),
+    )?;
+    assert!(matches!(
+        reply,
+        InvokeMethodReply::Value(Value::Boolean(true))
+    ));
+
+    let result = second_instance.invoke(&thread, "tick", "()I", &[], InvokeOptions::empty());
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+    let result = second_instance.invoke(
+        &thread,
+        "ping",
+        "(Ljava/lang/Object;)V",
+        &[Value::Object(second_instance.id())],
+        InvokeOptions::empty(),
+    );
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use std::sync::atomic::Ordering;
+
+use jdwp::{
+    client::ClientError,
+    commands::{class_object_reference::ReflectedType, reference_type::GetValues, Command},
+    enums::ClassStatus,
+    highlevel::{ObjectReference, ReferenceType},
+    jvm::{GenericType, TypeParameter},
+    types::{ClassObjectID, Value},
+};
+
+#[macro_use]
+mod common;
+
+use common::Result;
+
+#[test]
+fn all_interfaces() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let array_list = &vm.classes_by_signature("Ljava/util/ArrayList;")?[0];
+
+    let mut interfaces = array_list
+        .all_interfaces()?
+        .iter()
+        .map(|i| i.signature())
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    interfaces.sort_unstable();
+
+    let len = interfaces.len();
+    interfaces.dedup();
+    assert_eq!(interfaces.len(), len, "duplicates in {interfaces:?}");
+
+    // newer versions add things like SequencedCollection
+    for expected in [
+        "Ljava/io/Serializable;",
+        "Ljava/lang/Cloneable;",
+        "Ljava/lang/Iterable;",
+        "Ljava/util/Collection;",
+        "Ljava/util/List;",
+        "Ljava/util/RandomAccess;",
+    ] {
+        assert!(
+            interfaces.iter().any(|i| i == expected),
+            "{expected} not in {interfaces:?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn primitive_type() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let integer = &vm.classes_by_signature("Ljava/lang/Integer;")?[0];
+    assert!(!integer.is_primitive()?);
+    assert!(integer.status()?.contains(ClassStatus::INITIALIZED));
+    assert!(!integer.initialization_error()?);
+
+    let Value::Object(int_class) = integer.field_by_name("TYPE")?.unwrap().read(None)? else {
+        panic!("Integer.TYPE is not an object");
+    };
+    // SAFETY: Integer.TYPE is a Class
+    let int_class = unsafe { ClassObjectID::new(int_class) };
+    let int = vm.client().send(ReflectedType::new(int_class))?;
+    let int = ReferenceType::new(vm.client().clone(), int);
+
+    assert_eq!(int.signature()?, "I");
+    assert!(int.is_primitive()?);
+    // it is not a lambda by its name alone
+    assert!(!int.is_lambda()?);
+    for result in [
+        int.modifiers().map(drop),
+        int.status().map(drop),
+        int.is_synthetic().map(drop),
+        int.class_file_version().map(drop),
+    ] {
+        assert!(
+            matches!(result, Err(ClientError::NotApplicableToPrimitive)),
+            "{result:?}"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn class_file_version() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let version = basic.class_file_version()?;
+    // 52 is Java 8, the fixtures are compiled by the same JDK they run on
+    assert_eq!(
+        (version.major_version, version.minor_version),
+        (44 + common::java_version() as i32, 0)
+    );
+
+    let int_array = &vm.classes_by_signature("[I")?[0];
+    let result = int_array.class_file_version();
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn get_values_chunked() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let static_int = basic.field_by_name("staticInt")?.unwrap().id();
+    let second_instance = basic.field_by_name("secondInstance")?.unwrap().id();
+    // the order is visible with the two fields taking turns
+    let fields = [static_int, second_instance]
+        .into_iter()
+        .cycle()
+        .take(1000)
+        .collect::<Vec<_>>();
+
+    let get_values = common::count_commands(&vm, GetValues::ID);
+
+    let values = basic.get_values_chunked(&fields, 128)?;
+    assert_eq!(get_values.load(Ordering::SeqCst), 8);
+
+    assert_eq!(values.len(), 1000);
+    for pair in values.chunks(2) {
+        assert!(
+            matches!(pair, [Value::Int(42), second @ Value::Object(_)] if !second.is_null()),
+            "{pair:?}"
+        );
+    }
+    assert_eq!(
+        values,
+        vm.client().send(GetValues::new(*basic.id(), fields))?
+    );
+
+    let result = basic.get_values_chunked(&[static_int], 0);
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn resolve_method() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    // println is overloaded for all the primitives, char[], String and Object
+    let print_stream = &vm.classes_by_signature("Ljava/io/PrintStream;")?[0];
+    let println = |args: &[Value]| -> Result<String> {
+        let method = print_stream.resolve_method("println", args)?.unwrap();
+        Ok(method.signature().to_owned())
+    };
+
+    assert_eq!(println(&[Value::Int(42)])?, "(I)V");
+    assert_eq!(println(&[Value::Long(42)])?, "(J)V");
+    assert_eq!(println(&[])?, "()V");
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let field = |name| basic.field_by_name(name).map(Option::unwrap);
+    let second_instance = field("secondInstance")?.read(None)?;
+    let Value::Object(id) = second_instance else {
+        panic!("secondInstance is not an object");
+    };
+    let unused = field("unused")?.read(Some(&ObjectReference::new(vm.client().clone(), id)))?;
+
+    // the exact String overload wins over the Object one
+    assert_eq!(println(&[unused])?, "(Ljava/lang/String;)V");
+    assert_eq!(println(&[second_instance])?, "(Ljava/lang/Object;)V");
+
+    let no_overload = print_stream.resolve_method("println", &[Value::Int(1), Value::Int(2)])?;
+    assert!(no_overload.is_none());
+
+    Ok(())
+}
+
+#[test]
+fn all_methods() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let integer = &vm.classes_by_signature("Ljava/lang/Integer;")?[0];
+
+    let mut to_strings = integer
+        .all_methods()?
+        .into_iter()
+        .filter(|m| m.name() == "toString")
+        .map(|m| Ok((m.declaring_type().signature()?, m.signature().to_owned())))
+        .collect::<Result<Vec<_>>>()?;
+    to_strings.sort_unstable();
+
+    // the Integer override shadows the Object one
+    assert_snapshot!(to_strings, @r###"
+    [
+        (
+            "Ljava/lang/Integer;",
+            "()Ljava/lang/String;",
+        ),
+        (
+            "Ljava/lang/Integer;",
+            "(I)Ljava/lang/String;",
+        ),
+        (
+            "Ljava/lang/Integer;",
+            "(II)Ljava/lang/String;",
+        ),
+    ]
+    "###);
+
+    Ok(())
+}
+
+#[test]
+fn type_parameters() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    assert_eq!(basic.type_parameters()?, []);
+
+    let hash_map = &vm.classes_by_signature("Ljava/util/HashMap;")?[0];
+    let object = GenericType::Class {
+        name: "java/lang/Object".to_owned(),
+        type_arguments: vec![],
+    };
+    assert_eq!(
+        hash_map.type_parameters()?,
+        ["K", "V"].map(|name| TypeParameter {
+            name: name.to_owned(),
+            class_bound: Some(object.clone()),
+            interface_bounds: vec![],
+        })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn synthetic_and_lambda() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    assert!(!basic.is_synthetic()?);
+    assert!(!basic.is_lambda()?);
+
+    Ok(())
+}
@@ -0,0 +1,85 @@
+use jdwp::{
+    enums::{SuspendPolicy, Tag},
+    types::Value,
+};
+
+mod common;
+
+use common::Result;
+
+#[test]
+fn all_locals() -> Result {
+    let vm = common::launch_and_attach_vm("locals")?;
+
+    // the return line, where all of the locals are visible
+    let thread = common::break_at(&vm, "LLocals;", "compute", 16, SuspendPolicy::EventThread)?;
+
+    let frame = thread.frames()?.remove(0);
+    let locals = frame.all_locals()?;
+
+    let names = locals.iter().map(|(n, _)| n.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["x", "factor", "label", "result", "values"]);
+
+    assert_eq!(locals[0].1, Value::Int(21));
+    assert_eq!(locals[1].1, Value::Double(2.5));
+    assert!(matches!(locals[2].1, Value::Object(_)));
+    assert_eq!(locals[3].1, Value::Long(42));
+    assert!(matches!(locals[4].1, Value::Object(_)));
+
+    Ok(())
+}
+
+#[test]
+fn get_and_set_values() -> Result {
+    let vm = common::launch_and_attach_vm("locals")?;
+
+    let thread = common::break_at(&vm, "LLocals;", "compute", 16, SuspendPolicy::EventThread)?;
+    let frame = thread.frames()?.remove(0);
+
+    // the arguments come first, and the double takes two slots
+    let slots = [(0, Tag::Int), (1, Tag::Double), (4, Tag::Long)];
+    assert_eq!(
+        frame.get_values(&slots)?,
+        [Value::Int(21), Value::Double(2.5), Value::Long(42)]
+    );
+
+    frame.set_values(&[(0, Value::Int(1)), (4, Value::Long(7))])?;
+    assert_eq!(
+        frame.get_values(&slots)?,
+        [Value::Int(1), Value::Double(2.5), Value::Long(7)]
+    );
+
+    Ok(())
+}
+
+#[test]
+fn this_object() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    // ++ticks in Basic.tick
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+    let frames = thread.frames()?;
+
+    let this = frames[0].this_object()?.unwrap();
+    assert_eq!(this.reference_type()?.signature()?, "LBasic;");
+    // and the static main at the bottom has none
+    assert_eq!(frames.last().unwrap().this_object()?, None);
+
+    Ok(())
+}
+
+#[test]
+fn pop() -> Result {
+    let vm = common::launch_and_attach_vm("locals")?;
+
+    let thread = common::break_at(&vm, "LLocals;", "compute", 16, SuspendPolicy::EventThread)?;
+    let frames = thread.frames()?;
+    frames[0].pop()?;
+
+    // back in main, at the call of compute
+    let after = thread.frames()?;
+    assert_eq!(after.len(), frames.len() - 1);
+    assert_eq!(after[0].location(), frames[1].location());
+
+    Ok(())
+}
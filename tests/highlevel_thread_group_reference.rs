@@ -0,0 +1,43 @@
+use jdwp::highlevel::ThreadTreeNode;
+
+mod common;
+
+use common::Result;
+
+#[test]
+fn walk_to_main_thread() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let groups = vm.top_level_thread_groups()?;
+    let system = groups.iter().find(|g| g.name().unwrap() == "system");
+    let system = system.expect("no system thread group");
+    assert_eq!(system.parent()?, None);
+
+    let mut main_path = None;
+    let mut path = Vec::new();
+    system.walk(|depth, node| {
+        path.truncate(depth);
+        let name = match node {
+            ThreadTreeNode::Group(group) => group.name().unwrap(),
+            ThreadTreeNode::Thread(thread) => thread.name().unwrap(),
+        };
+        if let ThreadTreeNode::Thread(_) = node {
+            if name == "main" {
+                main_path = Some(path.clone());
+            }
+        }
+        path.push(name);
+    })?;
+
+    assert_eq!(
+        main_path,
+        Some(vec!["system".to_owned(), "main".to_owned()])
+    );
+
+    let (threads, groups) = system.children()?;
+    let main_group = groups.iter().find(|g| g.name().unwrap() == "main").unwrap();
+    assert_eq!(main_group.parent()?.as_ref(), Some(system));
+    assert!(threads.iter().all(|t| t.name().unwrap() != "main"));
+
+    Ok(())
+}
@@ -0,0 +1,387 @@
+use std::{
+    io::Write,
+    panic::AssertUnwindSafe,
+    sync::{atomic::Ordering, mpsc},
+    time::Duration,
+};
+
+use jdwp::{
+    client::ClientError,
+    commands::{
+        event::Event,
+        event_request, thread_reference,
+        virtual_machine::{RedefineClasses, RedefiningClass, Resume},
+        Command,
+    },
+    enums::{EventKind, StepDepth, StepSize, SuspendPolicy},
+    highlevel::{ThreadEvent, ThreadReference},
+    types::{ClassMatch, LocationOnly, Modifier, ThreadOnly, Value},
+};
+
+#[macro_use]
+mod common;
+
+use common::Result;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn subscribe_threads() -> Result {
+    let mut vm = common::launch_and_attach_vm("threads")?;
+
+    let threads = vm.subscribe_threads()?;
+
+    // ask the fixture to start a thread
+    writeln!(vm.jvm_process.stdin.as_mut().unwrap(), "worker")?;
+
+    let started = match threads.recv_timeout(TIMEOUT)? {
+        ThreadEvent::Started(thread) => thread,
+        e => panic!("Unexpected thread event: {:?}", e),
+    };
+    assert_eq!(started.name()?, "worker");
+
+    assert_eq!(threads.recv_timeout(TIMEOUT)?, ThreadEvent::Died(started));
+
+    Ok(())
+}
+
+#[test]
+fn current_location() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+    let location = tick.locations_of_line(13)?.remove(0);
+
+    let (tx, rx) = mpsc::channel();
+    vm.client().subscribe(
+        event_request::Set::new(
+            EventKind::Breakpoint,
+            SuspendPolicy::EventThread,
+            vec![Modifier::LocationOnly(LocationOnly {
+                location: location.clone(),
+            })],
+        ),
+        tx,
+    )?;
+
+    let thread = match rx.recv_timeout(TIMEOUT)? {
+        Event::Breakpoint(e) => ThreadReference::new(vm.client().clone(), e.thread),
+        e => panic!("Unexpected event: {:?}", e),
+    };
+
+    let current = thread.current_location()?.unwrap();
+    assert_eq!(current, location);
+
+    let method = basic
+        .methods()?
+        .into_iter()
+        .find(|m| m.id() == current.method_id)
+        .unwrap();
+    assert_eq!(method.name(), "tick");
+
+    Ok(())
+}
+
+#[test]
+fn no_obsolete_frames_after_noop_redefine() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+    let location = tick.locations_of_line(13)?.remove(0);
+
+    let (tx, rx) = mpsc::channel();
+    vm.client().subscribe(
+        event_request::Set::new(
+            EventKind::Breakpoint,
+            SuspendPolicy::EventThread,
+            vec![Modifier::LocationOnly(LocationOnly { location })],
+        ),
+        tx,
+    )?;
+    let thread = match rx.recv_timeout(TIMEOUT)? {
+        Event::Breakpoint(e) => ThreadReference::new(vm.client().clone(), e.thread),
+        e => panic!("Unexpected event: {:?}", e),
+    };
+
+    let bytes = std::fs::read(format!(
+        "target/java_{}/Basic.class",
+        common::java_version()
+    ))?;
+    vm.client()
+        .send(RedefineClasses::new(vec![RedefiningClass::new(
+            *basic.id(),
+            bytes,
+        )]))?;
+
+    // the same bytecode is equivalent, so nothing became obsolete
+    assert!(!tick.is_obsolete()?);
+    assert!(!thread.has_obsolete_frames()?);
+
+    Ok(())
+}
+
+#[test]
+fn printable_stack_trace() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    assert_eq!(
+        thread.printable_stack_trace()?,
+        "\tat Basic.tick(Basic.java:13)\n\
+         \tat Basic.run(Basic.java:32)\n\
+         \tat Basic.main(Basic.java:42)\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn annotated_stack() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    // the frames themselves have the ids that differ between runs
+    let annotated = thread
+        .annotated_stack()?
+        .into_iter()
+        .map(|f| {
+            (
+                f.method_name,
+                f.position,
+                f.is_native,
+                f.is_obsolete,
+                f.is_synthetic,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_snapshot!(annotated, @r###"
+    [
+        (
+            Some(
+                "tick",
+            ),
+            SourcePosition {
+                source_file: Some(
+                    "Basic.java",
+                ),
+                line: Some(
+                    13,
+                ),
+            },
+            false,
+            false,
+            false,
+        ),
+        (
+            Some(
+                "run",
+            ),
+            SourcePosition {
+                source_file: Some(
+                    "Basic.java",
+                ),
+                line: Some(
+                    32,
+                ),
+            },
+            false,
+            false,
+            false,
+        ),
+        (
+            Some(
+                "main",
+            ),
+            SourcePosition {
+                source_file: Some(
+                    "Basic.java",
+                ),
+                line: Some(
+                    42,
+                ),
+            },
+            false,
+            false,
+            false,
+        ),
+    ]
+    "###);
+
+    Ok(())
+}
+
+#[test]
+fn recursion_depth() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    // the fixture has no recursion, so tick is on the stack only once
+    assert_eq!(thread.recursion_depth(&tick)?, 1);
+    let ping = basic.method_by_name("ping")?.unwrap();
+    assert_eq!(thread.recursion_depth(&ping)?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn force_early_return() -> Result {
+    let vm = common::launch_and_attach_vm("locals")?;
+
+    let locals = &vm.classes_by_signature("LLocals;")?[0];
+    let compute = locals.method_by_name("compute")?.unwrap();
+    // the first line of compute
+    let location = compute.locations_of_line(13)?.remove(0);
+
+    let (tx, rx) = mpsc::channel();
+    let breakpoint = vm
+        .event_request(EventKind::Breakpoint)
+        .suspend_policy(SuspendPolicy::EventThread)
+        .modifier(Modifier::LocationOnly(LocationOnly { location }))
+        .subscribe(tx)?;
+    let thread = match rx.recv_timeout(TIMEOUT)? {
+        Event::Breakpoint(e) => ThreadReference::new(vm.client().clone(), e.thread),
+        e => panic!("Unexpected event: {:?}", e),
+    };
+    vm.client()
+        .send(event_request::Clear::new(EventKind::Breakpoint, breakpoint))?;
+
+    // compute returns a long
+    let result = thread.force_early_return(Value::Int(1));
+    assert!(
+        matches!(result, Err(ClientError::IllegalArgument(_))),
+        "{result:?}"
+    );
+
+    let (tx, rx) = mpsc::channel();
+    vm.event_request(EventKind::MethodExitWithReturnValue)
+        .suspend_policy(SuspendPolicy::None)
+        .modifier(Modifier::ThreadOnly(ThreadOnly {
+            thread: thread.id(),
+        }))
+        .modifier(Modifier::ClassMatch(ClassMatch {
+            class_pattern: "Locals".to_owned(),
+        }))
+        .subscribe(tx)?;
+
+    thread.force_early_return(Value::Long(1234))?;
+    vm.client().send(Resume)?;
+
+    match rx.recv_timeout(TIMEOUT)? {
+        Event::MethodExitWithReturnValue(e) => {
+            assert_eq!(e.location.method_id, compute.id());
+            assert_eq!(e.value, Value::Long(1234));
+        }
+        e => panic!("Unexpected event: {:?}", e),
+    }
+
+    // and it's not suspended anymore
+    let result = thread.force_early_return(Value::Long(1));
+    assert!(
+        matches!(result, Err(ClientError::IllegalState(_))),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn name_is_cached() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let names = common::count_commands(&vm, thread_reference::Name::ID);
+
+    let main = vm.thread_by_name("main")?.unwrap();
+    let fetched = names.load(Ordering::SeqCst);
+
+    // the other references to the thread share the cache too
+    let again = ThreadReference::new(vm.client().clone(), main.id());
+    assert_eq!(main.name()?, "main");
+    assert_eq!(again.name()?, "main");
+    assert_eq!(names.load(Ordering::SeqCst), fetched);
+
+    assert_eq!(main.refresh_name()?, "main");
+    assert_eq!(names.load(Ordering::SeqCst), fetched + 1);
+
+    Ok(())
+}
+
+#[test]
+fn with_suspended() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let main = vm.thread_by_name("main")?.unwrap();
+    let suspend_count = || {
+        vm.client()
+            .send(thread_reference::SuspendCount::new(main.id()))
+    };
+    assert_eq!(suspend_count()?, 0);
+
+    let (frame_count, frames) = main.with_suspended(|thread| {
+        assert_eq!(suspend_count()?, 1);
+        Ok((thread.frame_count()?, thread.frames()?))
+    })?;
+    // main and run at least, with the thread sleeping or ticking on top
+    assert!(frame_count >= 2, "{frame_count}");
+    assert_eq!(frames.len(), frame_count as usize);
+    assert_eq!(suspend_count()?, 0);
+
+    // resumed when the closure fails
+    let result = main.with_suspended::<()>(|_| Err(ClientError::IllegalState("failed".to_owned())));
+    assert!(matches!(result, Err(ClientError::IllegalState(_))));
+    assert_eq!(suspend_count()?, 0);
+
+    // and when it panics
+    let panicked = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        main.with_suspended::<()>(|_| panic!("expected"))
+    }));
+    assert!(panicked.is_err());
+    assert_eq!(suspend_count()?, 0);
+
+    // a suspend from before stays in place
+    main.suspend()?;
+    main.with_suspended(|_| Ok(()))?;
+    assert_eq!(suspend_count()?, 1);
+    main.resume()?;
+
+    Ok(())
+}
+
+#[test]
+fn step() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+    let run = basic.method_by_name("run")?.unwrap();
+
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    // main is already in its endless loop when the tests attach, so this
+    // can't step in main itself and steps in the tick that the loop calls
+    let location = thread.step(StepSize::Line, StepDepth::Over, TIMEOUT)?;
+    assert_eq!(location.method_id, tick.id());
+    assert_eq!(tick.line_for_index(location.index)?, Some(14));
+
+    // a line step finishes at the start of a line, so it's the sleep after
+    // the call to tick
+    let location = thread.step(StepSize::Line, StepDepth::Out, TIMEOUT)?;
+    assert_eq!(location.method_id, run.id());
+    assert_eq!(run.line_for_index(location.index)?, Some(34));
+
+    assert_eq!(thread.current_location()?, Some(location));
+    assert!(!vm
+        .active_requests()
+        .iter()
+        .any(|r| r.event_kind == EventKind::SingleStep));
+
+    Ok(())
+}
@@ -0,0 +1,590 @@
+use std::{
+    io,
+    sync::{
+        atomic::Ordering,
+        mpsc::{self, RecvTimeoutError},
+    },
+    time::Duration,
+};
+
+use jdwp::{
+    client::ClientError,
+    commands::{
+        event::Event,
+        event_request::{Clear, Set},
+        object_reference::InvokeMethodReply,
+        reference_type::Methods,
+        virtual_machine::{CapabilitiesNew, CreateString},
+        Command,
+    },
+    enums::{ClassStatus, EventKind, InvokeOptions, ModifierKind, SuspendPolicy},
+    highlevel::{
+        ClassType, LaunchConfig, LocationInfo, ObjectReference, RedefineCapability, RedefineError,
+        ThreadReference, VM,
+    },
+    types::{ClassMatch, Location, LocationOnly, Modifier, TaggedReferenceTypeID, Value},
+};
+
+mod common;
+
+use common::Result;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+#[test]
+fn java_version() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    assert_eq!(vm.java_version()?, Some(common::java_version()));
+
+    Ok(())
+}
+
+#[test]
+fn redefine_capability() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    // HotSpot can only redefine method bodies
+    assert_eq!(vm.redefine_capability()?, RedefineCapability::Restricted);
+
+    Ok(())
+}
+
+#[test]
+fn capabilities() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let fetched = common::count_commands(&vm, CapabilitiesNew::ID);
+
+    let capabilities = vm.capabilities()?;
+    assert!(capabilities.can_get_bytecodes());
+    assert!(capabilities.can_redefine_classes());
+    assert!(capabilities.can_force_early_return());
+    assert!(!capabilities.can_unrestrictedly_redefine_classes());
+
+    // cached for the rest of the session
+    assert_eq!(vm.capabilities()?, capabilities);
+    vm.redefine_capability()?;
+    assert_eq!(fetched.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[test]
+fn value_to_string() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let field = |name| basic.field_by_name(name).map(Option::unwrap);
+
+    let static_int = field("staticInt")?.read(None)?;
+    assert_eq!(vm.value_to_string(&static_int)?, "42");
+
+    let Value::Object(id) = field("secondInstance")?.read(None)? else {
+        panic!("secondInstance is not an object");
+    };
+    let second_instance = ObjectReference::new(vm.client().clone(), id);
+    let unused = field("unused")?.read(Some(&second_instance))?;
+    assert_eq!(vm.value_to_string(&unused)?, "hello");
+
+    Ok(())
+}
+
+#[test]
+fn value_array_length() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    // the boxed values from -128 to 127 are always cached in an array
+    let cache = &vm.classes_by_signature("Ljava/lang/Integer$IntegerCache;")?[0];
+    let cache = cache.field_by_name("cache")?.unwrap().read(None)?;
+    assert_eq!(vm.value_array_length(&cache)?, Some(256));
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let second_instance = basic.field_by_name("secondInstance")?.unwrap().read(None)?;
+    assert_eq!(vm.value_array_length(&second_instance)?, None);
+    assert_eq!(vm.value_array_length(&Value::Int(42))?, None);
+
+    Ok(())
+}
+
+#[test]
+fn class_overview() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let signatures = ["LBasic;", "[I", "Ljava/lang/String;"];
+    let types = signatures
+        .iter()
+        .map(|signature| Ok(*vm.classes_by_signature(signature)?[0].id()))
+        .collect::<Result<Vec<_>>>()?;
+
+    let overview = vm.class_overview(&types)?;
+
+    let listed = overview
+        .iter()
+        .map(|o| (o.signature.as_str(), o.source_file.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        listed,
+        [
+            ("LBasic;", Some("Basic.java")),
+            ("[I", None),
+            ("Ljava/lang/String;", Some("String.java")),
+        ]
+    );
+    assert_eq!(overview[0].status, ClassStatus::OK);
+
+    Ok(())
+}
+
+#[test]
+fn heap_histogram() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let histogram = vm.heap_histogram(usize::MAX)?;
+    assert!(histogram.windows(2).all(|w| w[0].1 >= w[1].1));
+
+    // the one running and the secondInstance
+    let basic = histogram
+        .iter()
+        .find(|(signature, _)| signature == "LBasic;");
+    assert_eq!(basic, Some(&("LBasic;".to_owned(), 2)));
+
+    assert_eq!(vm.heap_histogram(3)?.len(), 3);
+
+    Ok(())
+}
+
+#[test]
+fn all_threads() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let names = vm
+        .all_threads()?
+        .iter()
+        .map(ThreadReference::name)
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    assert!(names.iter().any(|name| name == "main"), "{names:?}");
+
+    Ok(())
+}
+
+#[test]
+fn modules() -> Result {
+    // there are no modules before Java 9
+    if common::java_version() < 9 {
+        return Ok(());
+    }
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let modules = vm.modules()?;
+    let mut java_base = None;
+    for module in &modules {
+        if module.name()? == "java.base" {
+            java_base = Some(module);
+        }
+    }
+    let java_base = java_base.expect("no java.base module");
+
+    // it is loaded by the bootstrap class loader
+    assert_eq!(java_base.class_loader()?, None);
+
+    Ok(())
+}
+
+#[test]
+fn thread_by_name() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let main = vm.thread_by_name("main")?.unwrap();
+    assert_eq!(main.name()?, "main");
+    assert_eq!(vm.threads_by_name("main")?, [main]);
+
+    assert_eq!(vm.thread_by_name("no such thread")?, None);
+
+    Ok(())
+}
+
+#[test]
+fn launch_and_wait_start() -> Result {
+    let (classpath, main_class) = common::ensure_fixture_is_compiled("basic")?;
+
+    let mut config = LaunchConfig::new(main_class);
+    config.vm_options = vec!["-cp".into(), classpath];
+
+    let (_vm, start) = VM::launch_and_wait_start(&config, TIMEOUT)?;
+
+    assert_eq!(start.thread.name()?, "main");
+    assert!(start.other_events.is_empty(), "{:?}", start.other_events);
+
+    Ok(())
+}
+
+#[test]
+fn await_start() -> Result {
+    let (classpath, main_class) = common::ensure_fixture_is_compiled("basic")?;
+
+    let mut config = LaunchConfig::new(main_class);
+    config.vm_options = vec!["-cp".into(), classpath];
+    config.suspend = true;
+
+    let vm = VM::launch(&config)?;
+    let start = vm.await_start(TIMEOUT)?;
+    assert_eq!(start.suspend_policy, SuspendPolicy::All);
+    assert!(start.vm_suspended);
+    assert_eq!(start.thread.name()?, "main");
+    assert!(start.thread.is_suspended()?);
+
+    config.suspend = false;
+
+    let vm = VM::launch(&config)?;
+    let start = vm.await_start(TIMEOUT)?;
+    assert_eq!(start.suspend_policy, SuspendPolicy::None);
+    assert!(!start.vm_suspended);
+
+    // the event is only sent once
+    let result = vm.await_start(Duration::from_millis(100));
+    assert!(
+        matches!(&result, Err(ClientError::IoError(e)) if e.kind() == io::ErrorKind::TimedOut),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn active_requests() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let class_match = Modifier::ClassMatch(ClassMatch {
+        class_pattern: "Basic$*".into(),
+    });
+    let class_prepare = vm.client().send(Set::new(
+        EventKind::ClassPrepare,
+        SuspendPolicy::None,
+        vec![class_match.clone()],
+    ))?;
+    let threads = vm.subscribe_threads()?;
+
+    let requests = vm
+        .active_requests()
+        .into_iter()
+        .map(|r| (r.event_kind, r.request_id, r.modifiers))
+        .collect::<Vec<_>>();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(
+        requests[0],
+        (EventKind::ClassPrepare, class_prepare, vec![class_match])
+    );
+    assert_eq!(requests[1].0, EventKind::ThreadStart);
+    assert_eq!(requests[2].0, EventKind::ThreadDeath);
+
+    vm.client()
+        .send(Clear::new(EventKind::ClassPrepare, class_prepare))?;
+    let kinds = vm
+        .active_requests()
+        .into_iter()
+        .map(|r| r.event_kind)
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [EventKind::ThreadStart, EventKind::ThreadDeath]);
+
+    drop(threads);
+    assert_eq!(vm.active_requests(), []);
+
+    Ok(())
+}
+
+#[test]
+fn one_shot_breakpoint() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+    let location = tick.locations_of_line(13)?.remove(0);
+
+    let (tx, rx) = mpsc::channel();
+    vm.event_request(EventKind::Breakpoint)
+        .suspend_policy(SuspendPolicy::None)
+        .modifier(Modifier::LocationOnly(LocationOnly { location }))
+        .once()
+        .subscribe(tx)?;
+
+    assert!(matches!(rx.recv_timeout(TIMEOUT)?, Event::Breakpoint(_)));
+
+    // it ticks every 50ms, so this would be plenty
+    let result = rx.recv_timeout(Duration::from_millis(500));
+    assert_eq!(result.err(), Some(RecvTimeoutError::Timeout));
+
+    Ok(())
+}
+
+#[test]
+fn breakpoint_with_count() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+    let location = tick.locations_of_line(13)?.remove(0);
+
+    // every hit, to count them
+    let (all_tx, all_rx) = mpsc::channel();
+    vm.event_request(EventKind::Breakpoint)
+        .suspend_policy(SuspendPolicy::None)
+        .location_only(location.clone())
+        .subscribe(all_tx)?;
+
+    let (tx, rx) = mpsc::channel();
+    vm.event_request(EventKind::Breakpoint)
+        .suspend_policy(SuspendPolicy::EventThread)
+        .location_only(location.clone())
+        .count(3)
+        .subscribe(tx)?;
+    assert!(matches!(rx.recv_timeout(TIMEOUT)?, Event::Breakpoint(_)));
+
+    // the thread is suspended at the third hit, so there are no more
+    let hits = std::iter::from_fn(|| all_rx.recv_timeout(Duration::from_millis(500)).ok());
+    assert_eq!(hits.count(), 3);
+
+    let result = vm
+        .event_request(EventKind::ThreadStart)
+        .location_only(location)
+        .send();
+    assert!(
+        matches!(
+            result,
+            Err(ClientError::ModifierNotApplicable {
+                modifier: ModifierKind::LocationOnly,
+                event_kind: EventKind::ThreadStart,
+            })
+        ),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn find_classes() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let in_java_util = |name: &str| {
+        name.strip_prefix("java.util.")
+            .map_or(false, |rest| !rest.contains('.'))
+    };
+    let classes =
+        vm.find_classes(|class| class.class_name().map_or(false, |n| in_java_util(&n)))?;
+
+    // the arrays of them count too
+    assert!(classes.iter().all(|class| class
+        .signature
+        .trim_start_matches('[')
+        .starts_with("Ljava/util/")));
+    let hash_map = classes
+        .iter()
+        .find(|class| class.signature == "Ljava/util/HashMap;")
+        .unwrap();
+    assert_eq!(hash_map.class_name().as_deref(), Some("java.util.HashMap"));
+    assert!(hash_map
+        .generic_signature
+        .starts_with("<K:Ljava/lang/Object;V:Ljava/lang/Object;>"));
+    let nodes = classes
+        .iter()
+        .find(|class| class.signature == "[Ljava/util/HashMap$Node;")
+        .unwrap();
+    assert_eq!(
+        nodes.class_name().as_deref(),
+        Some("java.util.HashMap$Node[]")
+    );
+    // subpackages are not in java.util.*
+    assert!(!classes
+        .iter()
+        .any(|class| class.signature.starts_with("Ljava/util/concurrent/")));
+
+    Ok(())
+}
+
+#[test]
+fn describe_location() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+    let tick = basic.method_by_name("tick")?.unwrap();
+
+    let (tx, rx) = mpsc::channel();
+    vm.event_request(EventKind::Breakpoint)
+        .suspend_policy(SuspendPolicy::None)
+        .location_only(tick.locations_of_line(13)?.remove(0))
+        .once()
+        .subscribe(tx)?;
+    let Event::Breakpoint(breakpoint) = rx.recv_timeout(TIMEOUT)? else {
+        panic!("not a breakpoint");
+    };
+
+    let info = vm.describe_location(&breakpoint.location)?;
+    assert_eq!(
+        info,
+        LocationInfo {
+            class_signature: "LBasic;".to_owned(),
+            method_name: "tick".to_owned(),
+            source_file: Some("Basic.java".to_owned()),
+            line: Some(13),
+        }
+    );
+    assert_eq!(info.to_string(), "Basic.tick(Basic.java:13)");
+
+    // native, so there is no line
+    let object = &vm.classes_by_signature("Ljava/lang/Object;")?[0];
+    let hash_code = object.method_by_name("hashCode")?.unwrap();
+    let info = vm.describe_location(&Location {
+        reference_id: object.id(),
+        method_id: hash_code.id(),
+        index: 0,
+    })?;
+    assert_eq!(info.line, None);
+    assert_eq!(info.to_string(), "java.lang.Object.hashCode(Object.java)");
+
+    Ok(())
+}
+
+#[test]
+fn thread_dump() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let _basic = &vm.classes_by_signature("LBasic;")?[0];
+
+    // stop the main thread at a known place
+    let main = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    let dump = vm.thread_dump()?;
+
+    // the system threads differ between the versions
+    assert!(dump.iter().any(|info| info.name == "Reference Handler"));
+    let main_info = dump.iter().find(|info| info.thread == main).unwrap();
+
+    insta::with_settings!({
+        filters => vec![
+            (r"ThreadID\(\d+\)", "[opaque_id]"),
+            (r"\.java:\d+", ".java:[line]"),
+        ]
+    }, {
+        insta::assert_debug_snapshot!(main_info, @r###"
+        ThreadInfo {
+            thread: ThreadReference(
+                [opaque_id],
+            ),
+            name: "main",
+            status: Running,
+            stack_trace: "\tat Basic.tick(Basic.java:[line])\n\tat Basic.run(Basic.java:[line])\n\tat Basic.main(Basic.java:[line])\n",
+        }
+        "###);
+    });
+
+    // the VM is resumed, while the main thread is still at the breakpoint
+    assert!(main.is_suspended()?);
+    for info in &dump {
+        if info.thread != main {
+            assert!(!info.thread.is_suspended()?, "{}", info.name);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn method_info() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+    let location = thread.current_location()?.unwrap();
+
+    let fetched = common::count_commands(&vm, Methods::ID);
+
+    let info = ("tick".to_owned(), "()V".to_owned());
+    assert_eq!(vm.method_info(&location)?, info);
+    assert_eq!(vm.method_info(&location)?, info);
+    // the methods of the type are only fetched once
+    assert_eq!(fetched.load(Ordering::SeqCst), 1);
+
+    Ok(())
+}
+
+#[test]
+fn describe_exception() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    // invokes only work in threads suspended by an event
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    let TaggedReferenceTypeID::Class(integer) =
+        vm.classes_by_signature("Ljava/lang/Integer;")?[0].id()
+    else {
+        panic!("Integer is not a class");
+    };
+    let integer = ClassType::new(vm.client().clone(), integer);
+    let input = Value::Object(*vm.client().send(CreateString::new("nope"))?);
+    let parse_int = integer.resolve_method("parseInt", &[input])?.unwrap();
+
+    let reply = integer.invoke_static(&thread, &parse_int, &[input], InvokeOptions::empty())?;
+    let InvokeMethodReply::Exception(exception) = reply else {
+        panic!("parseInt did not throw: {reply:?}");
+    };
+    assert_eq!(
+        vm.describe_exception(exception, &thread)?,
+        r#"java.lang.NumberFormatException: For input string: "nope""#
+    );
+
+    Ok(())
+}
+
+#[test]
+fn redefine_classes() -> Result {
+    let vm = common::launch_and_attach_vm("basic")?;
+
+    // compile a copy of Basic that ticks by a thousand
+    let dir = format!("target/java_{}/redefined", common::java_version());
+    std::fs::create_dir_all(&dir)?;
+    let source = std::fs::read_to_string("tests/fixtures/Basic.java")?;
+    std::fs::write(
+        format!("{dir}/Basic.java"),
+        source.replace("++ticks;", "ticks += 1000;"),
+    )?;
+    let status = std::process::Command::new("javac")
+        .args([&format!("{dir}/Basic.java"), "-d", &dir])
+        .status()?;
+    assert!(status.success());
+    let redefined = std::fs::read(format!("{dir}/Basic.class"))?;
+
+    let basic = &vm.classes_by_signature("LBasic;")?[0];
+
+    // invokes only work in threads suspended by an event
+    let thread = common::break_at(&vm, "LBasic;", "tick", 13, SuspendPolicy::EventThread)?;
+
+    // redefining clears the breakpoint, so the invoke does not hit it
+    vm.redefine_classes(&[(basic.clone(), &redefined)])?;
+
+    let Value::Object(second_instance) =
+        basic.field_by_name("secondInstance")?.unwrap().read(None)?
+    else {
+        panic!("secondInstance is not an object");
+    };
+    let second_instance = ObjectReference::new(vm.client().clone(), second_instance);
+    second_instance.invoke(&thread, "tick", "()V", &[], InvokeOptions::empty())?;
+    assert_eq!(second_instance.get_field("ticks")?, Value::Long(1000));
+
+    let string = &vm.classes_by_signature("Ljava/lang/String;")?[0];
+    let result = vm.redefine_classes(&[(string.clone(), &redefined)]);
+    assert!(
+        matches!(result, Err(RedefineError::NamesDontMatch)),
+        "{result:?}"
+    );
+
+    let result = vm.redefine_classes(&[(basic.clone(), b"not a class file")]);
+    assert!(
+        matches!(
+            result,
+            Err(RedefineError::Client(ClientError::IllegalArgument(_)))
+        ),
+        "{result:?}"
+    );
+
+    Ok(())
+}
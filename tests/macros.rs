@@ -0,0 +1,35 @@
+use jdwp::{
+    codec::ByteTag,
+    enums::Tag,
+    types::{ArrayRegion, Value},
+};
+
+#[test]
+fn command_attr_errors() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}
+
+#[test]
+fn byte_tag_errors() {
+    trybuild::TestCases::new().compile_fail("tests/ui/byte_tag/*.rs");
+}
+
+#[derive(ByteTag)]
+#[repr(u8)]
+enum CustomTag {
+    First = 1,
+    Letter = b'x',
+    Next,
+}
+
+#[test]
+fn byte_tag() {
+    assert_eq!(CustomTag::First.byte_tag(), 1);
+    assert_eq!(CustomTag::Letter.byte_tag(), b'x');
+    assert_eq!(CustomTag::Next.byte_tag(), b'y');
+
+    // the same trait covers the tags and the tagged values of the crate
+    assert_eq!(Tag::Int.byte_tag(), b'I');
+    assert_eq!(Value::Int(42).byte_tag(), b'I');
+    assert_eq!(ArrayRegion::Long(vec![]).byte_tag(), b'J');
+}
@@ -0,0 +1,87 @@
+use jdwp::{
+    commands::{
+        object_reference::{GetValues, ReferringObjects},
+        reference_type::{self, Fields},
+        virtual_machine::ClassesBySignature,
+    },
+    jvm::FieldModifiers,
+    types::{Limit, Value},
+};
+
+#[macro_use]
+mod common;
+
+use common::Result;
+
+#[test]
+fn get_values() -> Result {
+    let mut client = common::launch_and_attach("basic")?;
+
+    let id = client.send(ClassesBySignature::new("LBasic;"))?[0].type_id;
+
+    let mut fields = client.send(Fields::new(*id))?;
+    fields.sort_by_key(|f| f.name.clone());
+
+    let second_instance = fields
+        .iter()
+        .find(|f| f.name == "secondInstance")
+        .unwrap()
+        .field_id;
+    let Value::Object(object) =
+        client.send(reference_type::GetValues::new(*id, vec![second_instance]))?[0]
+    else {
+        panic!("secondInstance is not an object");
+    };
+
+    let fields = fields
+        .into_iter()
+        .filter_map(|f| (!f.mod_bits.contains(FieldModifiers::STATIC)).then_some(f.field_id))
+        .collect::<Vec<_>>();
+
+    let values = client.send(GetValues::new(object, fields))?;
+
+    assert_snapshot!(values, @r###"
+    [
+        Long(
+            0,
+        ),
+        Object(
+            [opaque_id],
+        ),
+    ]
+    "###);
+
+    Ok(())
+}
+
+#[test]
+fn referring_objects() -> Result {
+    let mut client = common::launch_and_attach("basic")?;
+
+    let id = client.send(ClassesBySignature::new("LBasic;"))?[0].type_id;
+
+    let fields = client.send(Fields::new(*id))?;
+    let second_instance = fields
+        .iter()
+        .find(|f| f.name == "secondInstance")
+        .unwrap()
+        .field_id;
+    let Value::Object(object) =
+        client.send(reference_type::GetValues::new(*id, vec![second_instance]))?[0]
+    else {
+        panic!("secondInstance is not an object");
+    };
+
+    let referrers = client.send(ReferringObjects::new(object, Limit::All))?;
+
+    // the static field is stored in the class object
+    assert_snapshot!(referrers, @r###"
+    [
+        ClassObject(
+            Class[opaque_id],
+        ),
+    ]
+    "###);
+
+    Ok(())
+}
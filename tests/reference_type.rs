@@ -1,7 +1,7 @@
 use std::{assert_eq, io::Cursor};
 
 use jdwp::{
-    client::JdwpClient,
+    client::{ClientError, JdwpClient},
     commands::{
         class_object_reference::ReflectedType,
         reference_type::{
@@ -11,8 +11,9 @@ use jdwp::{
         virtual_machine::ClassesBySignature,
         Command,
     },
+    enums::ErrorCode,
     jvm::{ConstantPoolItem, ConstantPoolValue, FieldModifiers},
-    types::{InterfaceID, ReferenceTypeID, TaggedReferenceTypeID},
+    types::{InterfaceID, Limit, ReferenceTypeID, TaggedReferenceTypeID},
 };
 
 #[macro_use]
@@ -427,7 +428,7 @@ fn instances() -> Result {
     let mut client = common::launch_and_attach("basic")?;
 
     let id = client.send(ClassesBySignature::new(OUR_CLS))?[0].type_id;
-    let instances = client.send(Instances::new(*id, 10))?;
+    let instances = client.send(Instances::new(*id, Limit::Exactly(10)))?;
 
     // the running instance and the one in the static field
     assert_snapshot!(instances, @r###"
@@ -444,6 +445,25 @@ fn instances() -> Result {
     Ok(())
 }
 
+#[test]
+fn invalid_reference_type() -> Result {
+    let mut client = common::launch_and_attach("basic")?;
+
+    // SAFETY: it's intentionally invalid
+    let ref_type = unsafe { ReferenceTypeID::new(0xDEAD) };
+
+    let result = client.send(Signature::new(ref_type));
+    assert!(
+        matches!(
+            result,
+            Err(ClientError::HostError(ErrorCode::InvalidObject))
+        ),
+        "{result:?}"
+    );
+
+    Ok(())
+}
+
 #[test]
 fn class_file_version() -> Result {
     let mut client = common::launch_and_attach("basic")?;
@@ -473,10 +493,16 @@ fn constant_pool() -> Result {
 
     let id = client.send(ClassesBySignature::new(OUR_CLS))?[0].type_id;
     let constant_pool = client.send(ConstantPool::new(*id))?;
+    let constant_pool_bytes = constant_pool.cpbytes.clone();
     let mut reader = Cursor::new(constant_pool.cpbytes);
 
     // pfew lol why did I bother so much
     let items = ConstantPoolItem::read_all(constant_pool.count, &mut reader)?;
+
+    // the lazy reader gives the same items, stubs included
+    let iterated = ConstantPoolItem::iter(constant_pool.count, constant_pool_bytes.as_slice())
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    assert_eq!(format!("{iterated:?}"), format!("{items:?}"));
     let values = ConstantPoolValue::resolve(&items)?;
 
     let mut values = values
@@ -609,3 +635,74 @@ fn constant_pool() -> Result {
 
     Ok(())
 }
+
+#[test]
+fn dynamic_entries() -> Result {
+    let mut client = common::launch_and_attach("threads")?;
+
+    // it has a method reference, which is an invokedynamic call site
+    let id = client.send(ClassesBySignature::new("LThreads;"))?[0].type_id;
+    let constant_pool = client.send(ConstantPool::new(*id))?;
+    let mut reader = Cursor::new(constant_pool.cpbytes);
+
+    let items = ConstantPoolItem::read_all(constant_pool.count, &mut reader)?;
+    let values = ConstantPoolValue::resolve(&items)?;
+
+    assert_snapshot!(ConstantPoolValue::dynamic_entries(&values), @r###"
+    [
+        DynamicEntry {
+            kind: InvokeDynamic,
+            bootstrap_method_attr_index: 0,
+            name: "run",
+            descriptor: "()Ljava/lang/Runnable;",
+        },
+    ]
+    "###);
+
+    Ok(())
+}
+
+#[test]
+fn method_handles() -> Result {
+    let mut client = common::launch_and_attach("threads")?;
+
+    // the method reference brings in the handles of both the bootstrap method
+    // and the referenced method
+    let id = client.send(ClassesBySignature::new("LThreads;"))?[0].type_id;
+    let constant_pool = client.send(ConstantPool::new(*id))?;
+    let mut reader = Cursor::new(constant_pool.cpbytes);
+
+    let items = ConstantPoolItem::read_all(constant_pool.count, &mut reader)?;
+    let values = ConstantPoolValue::resolve(&items)?;
+    let handles = values
+        .iter()
+        .filter_map(ConstantPoolValue::as_method_handle)
+        .collect::<Vec<_>>();
+
+    // and the same handles are resolved from their indexes alone
+    let mut pool = vec![None; items.len()];
+    let by_index = (0..items.len() as u16)
+        .filter(|&i| matches!(items[i as usize], ConstantPoolItem::MethodHandle { .. }))
+        .map(|i| ConstantPoolValue::resolve_method_handle(&mut pool, &items, i))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    assert_eq!(by_index, handles);
+
+    assert_snapshot!(handles, @r###"
+    [
+        ResolvedMethodHandle {
+            kind: InvokeStatic,
+            owner: "java/lang/invoke/LambdaMetafactory",
+            name: "metafactory",
+            descriptor: "(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodHandle;Ljava/lang/invoke/MethodType;)Ljava/lang/invoke/CallSite;",
+        },
+        ResolvedMethodHandle {
+            kind: InvokeStatic,
+            owner: "Threads",
+            name: "work",
+            descriptor: "()V",
+        },
+    ]
+    "###);
+
+    Ok(())
+}
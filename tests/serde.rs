@@ -0,0 +1,37 @@
+#![cfg(feature = "serde")]
+
+use jdwp::types::{ClassID, Location, MethodID, ReferenceTypeID, TaggedReferenceTypeID, Value};
+
+mod common;
+
+use common::Result;
+
+#[test]
+fn location_round_trip() -> Result {
+    // SAFETY: they are never sent anywhere
+    let location = unsafe {
+        Location {
+            reference_id: TaggedReferenceTypeID::Class(ClassID::new(ReferenceTypeID::new(1))),
+            method_id: MethodID::new(2),
+            index: 3,
+        }
+    };
+
+    let json = serde_json::to_string(&location)?;
+    // the ids are just the raw numbers
+    assert_eq!(
+        json,
+        r#"{"reference_id":{"Class":1},"method_id":2,"index":3}"#
+    );
+    assert_eq!(serde_json::from_str::<Location>(&json)?, location);
+
+    Ok(())
+}
+
+#[test]
+fn value() -> Result {
+    let json = serde_json::to_string(&[Value::Int(42), Value::Void])?;
+    assert_eq!(json, r#"[{"Int":42},"Void"]"#);
+
+    Ok(())
+}
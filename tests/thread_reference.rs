@@ -0,0 +1,30 @@
+use jdwp::{
+    client::ClientError,
+    commands::thread_reference::Suspend,
+    enums::ErrorCode,
+    types::{ObjectID, ThreadID},
+};
+
+mod common;
+
+use common::Result;
+
+#[test]
+fn unit_reply_error() -> Result {
+    let mut client = common::launch_and_attach("basic")?;
+
+    // SAFETY: it's intentionally invalid
+    let thread = unsafe { ThreadID::new(ObjectID::new(0xDEAD)) };
+
+    // Suspend has a () reply, make sure the host error is not swallowed
+    let result = client.send(Suspend::new(thread));
+    assert!(
+        matches!(
+            result,
+            Err(ClientError::HostError(ErrorCode::InvalidObject))
+        ),
+        "{result:?}"
+    );
+
+    Ok(())
+}
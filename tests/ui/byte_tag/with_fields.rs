@@ -0,0 +1,10 @@
+use jdwp::codec::ByteTag;
+
+#[derive(ByteTag)]
+#[repr(u8)]
+enum Kind {
+    First = 1,
+    Second(u32) = 2,
+}
+
+fn main() {}
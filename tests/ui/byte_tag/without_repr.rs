@@ -0,0 +1,9 @@
+use jdwp::codec::ByteTag;
+
+#[derive(ByteTag)]
+enum Kind {
+    First = 1,
+    Second = 2,
+}
+
+fn main() {}
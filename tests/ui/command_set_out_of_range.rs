@@ -0,0 +1,6 @@
+use jdwp_macros::jdwp_command;
+
+#[jdwp_command((), 256, 1)]
+struct Command {}
+
+fn main() {}
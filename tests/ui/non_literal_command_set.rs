@@ -0,0 +1,8 @@
+use jdwp_macros::jdwp_command;
+
+const SET: u8 = 1;
+
+#[jdwp_command((), SET, 2)]
+struct Command {}
+
+fn main() {}
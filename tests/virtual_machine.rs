@@ -1,6 +1,19 @@
+use std::{
+    process::{Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
 use jdwp::{
-    client::ClientError,
-    commands::{string_reference::Value, thread_reference, virtual_machine::*},
+    client::{ClientError, JdwpClient, TraceEvent},
+    codec::{JdwpReadable, JdwpReader},
+    commands::{
+        event_request, reference_type, string_reference::Value, thread_reference,
+        virtual_machine::*, Command as _,
+    },
+    enums::{ErrorCode, EventKind, SuspendPolicy},
+    types::{FieldOnly, Modifier},
 };
 
 mod common;
@@ -31,6 +44,121 @@ fn version() -> Result {
     Ok(())
 }
 
+#[test]
+fn trace() -> Result {
+    let mut client = common::launch_and_attach("basic")?;
+
+    let (tx, rx) = mpsc::channel();
+    client.set_trace(move |event| {
+        let owned = match event {
+            TraceEvent::Command {
+                id, command, data, ..
+            } => (id, format!("command {command}"), data.to_vec()),
+            TraceEvent::Reply {
+                id,
+                error_code,
+                data,
+                ..
+            } => (id, format!("reply {error_code:?}"), data.to_vec()),
+            TraceEvent::Event { id, data, .. } => (id, "event".to_owned(), data.to_vec()),
+        };
+        // the Dispose on drop is traced too, when the receiver is gone
+        let _ = tx.send(owned);
+    });
+
+    let reply = client.send(Version)?;
+
+    let traced = rx.try_iter().collect::<Vec<_>>();
+    assert_eq!(traced.len(), 2, "{traced:?}");
+    let (command_id, command, data) = &traced[0];
+    assert_eq!(*command, format!("command {}", Version::ID));
+    assert!(data.is_empty());
+
+    let (reply_id, error, data) = &traced[1];
+    assert_eq!(reply_id, command_id);
+    assert_eq!(error, "reply None");
+    let traced_reply =
+        VersionReply::read(&mut JdwpReader::new(&data[..], client.id_sizes().clone()))?;
+    assert_eq!(traced_reply.description, reply.description);
+
+    Ok(())
+}
+
+#[test]
+fn listen() -> Result {
+    let (classpath, class_name) = common::ensure_fixture_is_compiled("basic")?;
+
+    let listener = JdwpClient::listen("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+
+    // the JVM connects back to us and stays suspended until resumed
+    let mut jvm_process = Command::new("java")
+        .arg(format!(
+            "-agentlib:jdwp=transport=dt_socket,server=n,suspend=y,address=127.0.0.1:{port}"
+        ))
+        .args(["-cp", &classpath, &class_name])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    let result = listener
+        .accept()
+        .and_then(|mut client| client.send(Version));
+
+    jvm_process.kill()?;
+    jvm_process.wait()?;
+
+    assert!(result?.vm_name.contains("VM"));
+
+    Ok(())
+}
+
+#[test]
+fn dispose_on_drop() -> Result {
+    let mut port = 0;
+    let mut handle = common::launch_and_attach_with("basic", |p| {
+        port = p;
+        Ok(Some(JdwpClient::attach(("localhost", p))?))
+    })?;
+
+    let mut client = handle.take().unwrap();
+    // the debugger-suspended threads should be resumed by the dispose
+    client.send(Suspend)?;
+    drop(client);
+
+    // the JVM starts listening again once it's done with the old connection
+    let mut attempts = 0;
+    let mut client = loop {
+        match JdwpClient::attach(("localhost", port)) {
+            Err(ClientError::IoError(e)) if attempts < 100 => {
+                log::debug!("Reattaching failed, retrying: {e}");
+                attempts += 1;
+                thread::sleep(Duration::from_millis(50));
+            }
+            result => break result?,
+        }
+    };
+
+    // and the main thread is ticking again
+    let type_id = client.send(ClassesBySignature::new("LBasic;"))?[0].type_id;
+    let ticks = client
+        .send(reference_type::Fields::new(*type_id))?
+        .into_iter()
+        .find(|f| f.name == "ticks")
+        .unwrap();
+    client.send(event_request::Set::new(
+        EventKind::FieldModification,
+        SuspendPolicy::None,
+        vec![Modifier::FieldOnly(FieldOnly {
+            declaring: *type_id,
+            field_id: ticks.field_id,
+        })],
+    ))?;
+    client.host_events().recv_timeout(Duration::from_secs(5))?;
+
+    Ok(())
+}
+
 #[test]
 fn class_by_signature() -> Result {
     let mut client = common::launch_and_attach("basic")?;
@@ -94,7 +222,7 @@ fn all_classes() -> Result {
     let classes = client.send(AllClasses)?;
     let mut filtered = classes
         .iter()
-        .filter_map(|c| CASES.contains(&&*c.signature).then_some(c))
+        .filter(|c| CASES.contains(&&*c.signature))
         .collect::<Vec<_>>();
     filtered.sort_unstable_by_key(|c| c.signature.clone());
 
@@ -196,6 +324,60 @@ fn dispose() -> Result {
     Ok(())
 }
 
+#[test]
+fn send_streaming() -> Result {
+    let mut client = common::launch_and_attach("basic")?;
+
+    // so that no classes are loaded in between
+    client.send(Suspend)?;
+
+    let classes = client.send(AllClasses)?;
+
+    let mut streamed = 0;
+    let mut found = Vec::new();
+    let count = client.send_streaming(AllClasses, |class| {
+        streamed += 1;
+        if CASES.contains(&&*class.signature) {
+            found.push(class.signature);
+        }
+    })?;
+    found.sort_unstable();
+
+    assert_eq!(count as usize, classes.len());
+    assert_eq!(streamed, classes.len());
+    assert_eq!(found, CASES);
+
+    client.send(Resume)?;
+
+    Ok(())
+}
+
+#[test]
+fn send_raw() -> Result {
+    let mut client = common::launch_and_attach("basic")?;
+
+    // the same as Version, just not through the typed path
+    let (error_code, data) = client.send_raw(1, 1, &[])?;
+    assert_eq!(error_code, ErrorCode::None);
+
+    let id_sizes = client.send(IDSizes)?;
+    let raw = VersionReply::read(&mut JdwpReader::new(data.as_slice(), id_sizes))?;
+    let typed = client.send(Version)?;
+    assert_eq!(raw.description, typed.description);
+    assert_eq!(
+        (raw.version_major, raw.version_minor),
+        (typed.version_major, typed.version_minor)
+    );
+    assert_eq!(raw.vm_version, typed.vm_version);
+
+    // nobody defines this one, what error it gives depends on the VM
+    let (error_code, data) = client.send_raw(1, 200, &[])?;
+    assert_ne!(error_code, ErrorCode::None);
+    assert!(data.is_empty());
+
+    Ok(())
+}
+
 #[test]
 fn id_sizes() -> Result {
     let mut client = common::launch_and_attach("basic")?;
@@ -213,6 +395,9 @@ fn id_sizes() -> Result {
     }
     "###);
 
+    // the ones fetched when attaching
+    assert_eq!(client.id_sizes(), &id_sizes);
+
     Ok(())
 }
 
@@ -257,6 +442,22 @@ fn string_roundtrip() -> Result {
     Ok(())
 }
 
+#[test]
+fn non_ascii_string_roundtrip() -> Result {
+    let mut client = common::launch_and_attach("basic")?;
+
+    // both are encoded differently in the modified UTF-8 of the JVM
+    let string = "emoji 😀 and a \0 nul";
+
+    let string_id = client.send(CreateString::new(string))?;
+
+    let string_value = client.send(Value::new(*string_id))?;
+
+    assert_eq!(string_value, string);
+
+    Ok(())
+}
+
 #[test]
 fn capabilities() -> Result {
     let mut client = common::launch_and_attach("basic")?;